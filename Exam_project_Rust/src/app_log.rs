@@ -0,0 +1,206 @@
+use std::{collections::VecDeque, fmt, path::PathBuf, sync::Mutex};
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Subscriber,
+};
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+    filter::{LevelFilter, Targets},
+    fmt as format,
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    EnvFilter, Layer,
+};
+use crate::{model::TaskId, paths};
+
+/// How many entries are kept before the oldest are dropped.
+const CAPACITY: usize = 5000;
+/// Daily log files kept in the data directory before the oldest is deleted.
+const LOG_FILES_KEPT: usize = 7;
+
+/// Recent yt-dlp output and app events, shown in the Logs window.
+static LOG: Lazy<Mutex<VecDeque<LogEntry>>> = Lazy::new(|| Mutex::new(VecDeque::with_capacity(CAPACITY)));
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Clone)]
+pub struct LogEntry {
+    pub time: DateTime<Local>,
+    /// Task the entry belongs to, `None` for app-wide events.
+    pub task: Option<TaskId>,
+    pub level: Level,
+    pub text: String,
+}
+
+impl LogEntry {
+    /// "12:34:56 text", as shown and copied.
+    pub fn to_line(&self) -> String {
+        format!("{} {}", self.time.format("%H:%M:%S"), self.text)
+    }
+}
+
+/// Sends tracing events to stderr, to the Logs window and, if `to_file`, to daily
+/// files in `log_dir`. `RUST_LOG` filters stderr (default "warn") and the file
+/// (default "info"); the Logs window gets info and up from this app either way.
+/// The guard flushes the file when dropped, so keep it until exit.
+pub fn init(to_file: bool) -> Option<WorkerGuard> {
+    let env_filter = |default: &str| EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default));
+    let stderr = format::layer().with_writer(std::io::stderr).with_filter(env_filter("warn"));
+
+    let appender = to_file.then(file_appender).flatten();
+    let (file, guard) = match appender {
+        Some(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = format::layer().with_ansi(false).with_writer(writer).with_filter(env_filter("info"));
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    let ours = Targets::new()
+        .with_target(env!("CARGO_CRATE_NAME"), LevelFilter::INFO)
+        .with_target("exam_project_rust", LevelFilter::INFO);
+    tracing_subscriber::registry().with(stderr).with(file).with(BufferLayer.with_filter(ours)).init();
+    guard
+}
+
+/// Where `init` writes log files.
+pub fn log_dir() -> Option<PathBuf> {
+    Some(paths::data_dir()?.join("logs"))
+}
+
+fn file_appender() -> Option<RollingFileAppender> {
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("ytdlgui")
+        .filename_suffix("log")
+        .max_log_files(LOG_FILES_KEPT)
+        .build(log_dir()?)
+        .inspect_err(|e| eprintln!("Not writing a log file: {}", e))
+        .ok()
+}
+
+/// Feeds tracing events into the Logs window, tagged with the `task_id` field of the
+/// event or of the closest span that has one.
+struct BufferLayer;
+
+/// Stored on spans that were given a `task_id`.
+struct SpanTask(TaskId);
+
+/// The message and task of an event; other fields are appended as "name=value".
+#[derive(Default)]
+struct Fields {
+    text: String,
+    task: Option<TaskId>,
+}
+
+impl Visit for Fields {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "task_id" {
+            self.task = Some(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.text.insert_str(0, &format!("{:?}", value));
+        } else {
+            self.text.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for BufferLayer {
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        if let Some(task) = fields.task
+            && let Some(span) = ctx.span(id)
+        {
+            span.extensions_mut().insert(SpanTask(task));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let task = fields.task.or_else(|| {
+            ctx.event_scope(event)?.find_map(|span| span.extensions().get::<SpanTask>().map(|t| t.0))
+        });
+        let level = match *event.metadata().level() {
+            tracing::Level::ERROR => Level::Error,
+            tracing::Level::WARN => Level::Warning,
+            _ => Level::Info,
+        };
+        push(task, level, fields.text);
+    }
+}
+
+fn push(task: Option<TaskId>, level: Level, text: String) {
+    let mut log = LOG.lock().unwrap();
+    if log.len() == CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(LogEntry { time: Local::now(), task, level, text });
+}
+
+fn emit(task: Option<TaskId>, level: Level, text: &str) {
+    match level {
+        Level::Info => tracing::info!(task_id = task, "{}", text),
+        Level::Warning => tracing::warn!(task_id = task, "{}", text),
+        Level::Error => tracing::error!(task_id = task, "{}", text),
+    }
+}
+
+/// A line of yt-dlp output, leveled by its "ERROR:" / "WARNING:" prefix.
+pub fn output(task: TaskId, line: String) {
+    let level = if line.starts_with("ERROR:") {
+        Level::Error
+    } else if line.starts_with("WARNING:") {
+        Level::Warning
+    } else {
+        Level::Info
+    };
+    emit(Some(task), level, &line);
+}
+
+/// Something the app did with a task, like starting or finishing it.
+pub fn task_event(task: TaskId, level: Level, text: impl Into<String>) {
+    emit(Some(task), level, &text.into());
+}
+
+/// Something the app did that isn't about one task.
+pub fn info(text: impl Into<String>) {
+    emit(None, Level::Info, &text.into());
+}
+
+/// An app-wide problem.
+pub fn warn(text: impl Into<String>) {
+    emit(None, Level::Warning, &text.into());
+}
+
+pub fn error(text: impl Into<String>) {
+    emit(None, Level::Error, &text.into());
+}
+
+/// Copies out the entries `keep` accepts, oldest first.
+pub fn snapshot(keep: impl Fn(&LogEntry) -> bool) -> Vec<LogEntry> {
+    LOG.lock().unwrap().iter().filter(|e| keep(e)).cloned().collect()
+}
+
+pub fn clear() {
+    LOG.lock().unwrap().clear();
+}
@@ -0,0 +1,121 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use once_cell::sync::Lazy;
+use crate::paths;
+
+/// Default upper bound for the thumbnail cache (50 MB).
+pub const DEFAULT_CACHE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Shared thumbnail cache, `None` if no cache directory could be created.
+pub static THUMBNAIL_CACHE: Lazy<Option<ThumbnailCache>> =
+    Lazy::new(|| ThumbnailCache::open_default(DEFAULT_CACHE_BYTES));
+
+/// On-disk cache of raw thumbnail bytes keyed by video id.
+///
+/// Every entry is a file `<video_id>.thumb` holding an 8-byte little-endian
+/// last-used timestamp followed by the image bytes. When the total size grows
+/// past `max_bytes` the least recently used entries are deleted.
+pub struct ThumbnailCache {
+    dir: PathBuf,
+    max_bytes: AtomicU64,
+}
+
+impl ThumbnailCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> std::io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, max_bytes: AtomicU64::new(max_bytes) })
+    }
+
+    /// Opens the cache under the app's cache directory.
+    pub fn open_default(max_bytes: u64) -> Option<Self> {
+        Self::new(paths::cache_dir()?.join("thumbnails"), max_bytes)
+            .inspect_err(|e| tracing::warn!(error = %e, "Thumbnail cache unavailable"))
+            .ok()
+    }
+
+    pub fn set_max_bytes(&self, max_bytes: u64) {
+        self.max_bytes.store(max_bytes, Ordering::Relaxed);
+        self.evict();
+    }
+
+    /// Returns the cached bytes for `video_id` and marks the entry as recently used.
+    pub fn get(&self, video_id: &str) -> Option<Vec<u8>> {
+        let path = self.entry_path(video_id)?;
+        let mut f = OpenOptions::new().read(true).write(true).open(path).ok()?;
+        let mut buf = Vec::new();
+        f.read_to_end(&mut buf).ok()?;
+        if buf.len() <= 8 {
+            drop(f);
+            self.remove(video_id);
+            return None;
+        }
+        f.seek(SeekFrom::Start(0)).ok()?;
+        let _ = f.write_all(&now_secs().to_le_bytes());
+        Some(buf.split_off(8))
+    }
+
+    pub fn put(&self, video_id: &str, bytes: &[u8]) {
+        let Some(path) = self.entry_path(video_id) else { return };
+        let written = File::create(&path).and_then(|mut f| {
+            f.write_all(&now_secs().to_le_bytes())?;
+            f.write_all(bytes)
+        });
+        if let Err(e) = written {
+            tracing::warn!(video_id, error = %e, "Caching thumbnail failed");
+            let _ = fs::remove_file(&path);
+        }
+        self.evict();
+    }
+
+    pub fn remove(&self, video_id: &str) {
+        if let Some(path) = self.entry_path(video_id) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// Deletes least recently used entries until the cache fits in `max_bytes`.
+    fn evict(&self) {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else { return };
+        let mut entries: Vec<(u64, u64, PathBuf)> = read_dir
+            .flatten()
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "thumb"))
+            .filter_map(|e| {
+                let size = e.metadata().ok()?.len();
+                let mut stamp = [0u8; 8];
+                File::open(e.path()).ok()?.read_exact(&mut stamp).ok();
+                Some((u64::from_le_bytes(stamp), size, e.path()))
+            })
+            .collect();
+
+        let max = self.max_bytes.load(Ordering::Relaxed);
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        if total <= max {
+            return;
+        }
+        entries.sort_by_key(|(stamp, _, _)| *stamp);
+        for (_, size, path) in entries {
+            if total <= max {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+
+    /// Only plain ids are used as file names so a crafted id can't escape the cache dir.
+    fn entry_path(&self, video_id: &str) -> Option<PathBuf> {
+        let valid = !video_id.is_empty()
+            && video_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        valid.then(|| self.dir.join(format!("{}.thumb", video_id)))
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,98 @@
+//! Splits a full-album upload into one audio file per chapter, numbered and tagged
+//! as tracks of an album named after the video.
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+use crate::{downloader::sanitize_title, ffmpeg, metadata::Chapter, paths::TempFile};
+
+/// What the tracks of one video are made of and tagged with.
+#[derive(Clone)]
+pub struct Album {
+    /// The video title.
+    pub title: String,
+    pub artist: Option<String>,
+    pub chapters: Vec<Chapter>,
+}
+
+/// Folder the tracks of `file` go to: next to it, named like it without the extension.
+pub fn tracks_folder(file: &Path) -> PathBuf {
+    file.with_extension("")
+}
+
+/// File name of track `number` (from 1) of `total`, e.g. "03 - Intro.mp3". Numbers
+/// are padded to the same width so the files sort in album order.
+pub fn track_file_name(number: usize, total: usize, title: &str, extension: &str) -> String {
+    let width = total.to_string().len().max(2);
+    let title = sanitize_title(title);
+    let title = if title.is_empty() { format!("Track {}", number) } else { title };
+    format!("{:0width$} - {}.{}", number, title, extension, width = width)
+}
+
+/// ffmpeg arguments that copy the audio of chapter `number` of `album` from `source`
+/// into `output` with the track's tags, without re-encoding.
+pub fn track_args(source: &Path, output: &Path, album: &Album, number: usize) -> Vec<OsString> {
+    let chapter = &album.chapters[number - 1];
+    let mut args: Vec<OsString> = ["-v", "error", "-y", "-nostats"].map(OsString::from).into();
+    args.extend(["-ss".into(), format!("{:.3}", chapter.start_time).into()]);
+    args.extend(["-to".into(), format!("{:.3}", chapter.end_time).into()]);
+    args.extend(["-i".into(), source.into()]);
+    args.extend(["-map", "0:a:0", "-map_metadata", "-1", "-c", "copy"].map(OsString::from));
+    let mut tags = vec![
+        ("track", format!("{}/{}", number, album.chapters.len())),
+        ("title", chapter.title.clone()),
+        ("album", album.title.clone()),
+    ];
+    if let Some(artist) = &album.artist {
+        tags.push(("artist", artist.clone()));
+    }
+    for (key, value) in tags {
+        args.extend(["-metadata".into(), format!("{}={}", key, value).into()]);
+    }
+    args.push(output.into());
+    args
+}
+
+/// Writes one file per chapter of `album` from the audio file `source` into
+/// `tracks_folder`, calling `on_track` with the number of tracks done so far, and returns
+/// their paths in order. The original stays. On an error, or when the future is dropped,
+/// the tracks written so far are deleted again, and the folder too if this made it.
+pub async fn split(
+    source: &Path,
+    album: &Album,
+    mut on_track: impl FnMut(usize),
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error + Send + Sync>> {
+    let extension = source.extension().ok_or("The file has no extension")?.to_string_lossy().into_owned();
+    let folder = tracks_folder(source);
+    // Declared before the tracks, so it is dropped after their files are gone
+    let created = NewFolder((!folder.exists()).then(|| folder.clone()));
+    fs::create_dir_all(&folder)?;
+    let total = album.chapters.len();
+    let mut tracks = Vec::with_capacity(total);
+    for (i, chapter) in album.chapters.iter().enumerate() {
+        let track = TempFile(folder.join(track_file_name(i + 1, total, &chapter.title, &extension)));
+        ffmpeg::run(track_args(source, &track.0, album, i + 1)).await?;
+        tracks.push(track);
+        on_track(i + 1);
+    }
+    created.keep();
+    Ok(tracks.into_iter().map(TempFile::keep).collect())
+}
+
+/// A folder `split` made for the tracks, removed when dropped if it is empty by then.
+struct NewFolder(Option<PathBuf>);
+
+impl NewFolder {
+    fn keep(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for NewFolder {
+    fn drop(&mut self) {
+        if let Some(folder) = &self.0 {
+            let _ = fs::remove_dir(folder);
+        }
+    }
+}
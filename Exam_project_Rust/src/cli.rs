@@ -0,0 +1,199 @@
+use tokio::{
+    runtime::Runtime,
+    sync::{mpsc::unbounded_channel, oneshot, watch},
+    task::JoinSet,
+};
+use crate::{
+    downloader::{spawn_download, DownloadJob},
+    events::{DownloadEvent, Outcome, Progress},
+    model::{format_size, AudioFormat, Quality, TaskId},
+    queue,
+    settings::Settings,
+};
+
+/// Exit code for bad arguments; a failed download exits with 1.
+pub const USAGE_ERROR: i32 = 2;
+
+/// A headless run, e.g. `--url <link> --quality 720p --folder ~/Videos`.
+struct CliOptions {
+    urls: Vec<String>,
+    quality: Quality,
+    audio_format: AudioFormat,
+    folder: String,
+    /// Downloads running at once.
+    jobs: usize,
+    overwrite: bool,
+}
+
+/// Whether the arguments ask for the command line mode rather than the window.
+pub fn requested(args: &[String]) -> bool {
+    args.first().is_some_and(|a| a.starts_with('-'))
+}
+
+/// Downloads what the arguments ask for without opening a window, printing progress to
+/// the terminal. Returns the process exit code.
+pub fn run(args: &[String], settings: &Settings, runtime: &Runtime) -> i32 {
+    let options = match parse(args, settings) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", t!("cli.usage"));
+            return 0;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, t!("cli.usage"));
+            return USAGE_ERROR;
+        }
+    };
+    let total = options.urls.len();
+    let jobs: Vec<DownloadJob> = options
+        .urls
+        .into_iter()
+        .map(|url| DownloadJob {
+            url,
+            quality: options.quality,
+            audio_format: options.audio_format,
+            folder: options.folder.clone(),
+            resume: false,
+            overwrite: options.overwrite,
+            file_stem: None,
+            clip: None,
+            normalize: false,
+            keep_original: false,
+            split: None,
+            encode: None,
+        })
+        .collect();
+
+    let failed = runtime.block_on(async {
+        let mut pending = jobs.into_iter().enumerate();
+        let mut running = JoinSet::new();
+        let mut failed = 0;
+        loop {
+            // Same as the window's queue: start in order, up to the limit
+            while queue::has_slot(running.len(), options.jobs)
+                && let Some((i, job)) = pending.next()
+            {
+                running.spawn(download(i + 1, total, job));
+            }
+            let Some(result) = running.join_next().await else { break };
+            if !result.unwrap_or(false) {
+                failed += 1;
+            }
+        }
+        failed
+    });
+
+    if total > 1 {
+        println!("{}", t!("cli.summary", done = total - failed, failed = failed));
+    }
+    if failed == 0 { 0 } else { 1 }
+}
+
+/// `Ok(None)` for `--help`. `--folder` and `--quality` were already taken out by
+/// `Overrides::parse` and applied to `settings`.
+fn parse(args: &[String], settings: &Settings) -> Result<Option<CliOptions>, String> {
+    let mut options = CliOptions {
+        urls: Vec::new(),
+        quality: settings.quality,
+        audio_format: settings.audio_format,
+        folder: settings.download_folder.clone(),
+        jobs: 1,
+        overwrite: false,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| t!("cli.missing_value", flag = arg));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--url" => options.urls.push(value()?.clone()),
+            "--audio-format" => {
+                let v = value()?;
+                options.audio_format = AudioFormat::ALL
+                    .into_iter()
+                    .find(|f| f.codec() == v.to_lowercase())
+                    .ok_or_else(|| t!("cli.bad_value", flag = arg, value = v))?;
+            }
+            "--jobs" => {
+                let v = value()?;
+                options.jobs = v
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| t!("cli.bad_value", flag = arg, value = v))?;
+            }
+            "--overwrite" => options.overwrite = true,
+            _ => return Err(t!("cli.unknown_flag", flag = arg)),
+        }
+    }
+    if options.urls.is_empty() {
+        return Err(t!("cli.no_urls"));
+    }
+    Ok(Some(options))
+}
+
+/// Runs one download, printing a line every 10% and the result. `true` if it finished.
+async fn download(n: usize, total: usize, job: DownloadJob) -> bool {
+    let url = job.url.clone();
+    let (tx, mut rx) = unbounded_channel();
+    let (progress_tx, mut progress) = watch::channel(Progress::default());
+    // Never sent; nothing cancels a headless download but Ctrl+C
+    let (_cancel_tx, cancel_rx) = oneshot::channel();
+    let handle = tokio::spawn(spawn_download(n as TaskId, job, progress_tx, tx, cancel_rx));
+
+    let printer = {
+        let url = url.clone();
+        async move {
+            let mut shown_step = None;
+            let mut shown_post_processing = false;
+            while progress.changed().await.is_ok() {
+                let p = *progress.borrow_and_update();
+                if p.post_processing {
+                    if !shown_post_processing {
+                        shown_post_processing = true;
+                        println!("[{}/{}] {}  {}", n, total, t!("cli.post_processing"), url);
+                    }
+                    continue;
+                }
+                shown_post_processing = false;
+                let step = (p.percent * 10.0) as u32;
+                if shown_step != Some(step) {
+                    shown_step = Some(step);
+                    let speed = p.speed.map(|s| format!("  {}/s", format_size(s as u64))).unwrap_or_default();
+                    println!("[{}/{}] {:>3.0}%{}  {}", n, total, p.percent * 100.0, speed, url);
+                }
+            }
+        }
+    };
+    let warnings = async {
+        while let Some((_, event)) = rx.recv().await {
+            match event {
+                DownloadEvent::Warning(line) | DownloadEvent::StepFailed(line) => eprintln!("[{}/{}] {}", n, total, line),
+                // spawn_download returns the result instead of sending it; no thumbnails here
+                DownloadEvent::Started(_)
+                | DownloadEvent::Line(_)
+                | DownloadEvent::Step(_)
+                | DownloadEvent::Tracks(_)
+                | DownloadEvent::Thumbnail { .. }
+                | DownloadEvent::Finished(_) => {}
+            }
+        }
+    };
+    tokio::join!(printer, warnings);
+
+    match handle.await {
+        Ok(Ok(Outcome::Finished { path, .. })) => {
+            let file = path.map_or_else(|| url.clone(), |p| p.display().to_string());
+            println!("[{}/{}] {}", n, total, t!("cli.done", file = file));
+            true
+        }
+        Ok(Ok(Outcome::Cancelled)) => false,
+        Ok(Err(e)) => {
+            eprintln!("[{}/{}] {}", n, total, t!("cli.failed", url = url, error = e));
+            false
+        }
+        Err(e) => {
+            eprintln!("[{}/{}] {}", n, total, t!("cli.failed", url = url, error = e));
+            false
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::{collections::HashMap, time::{Duration, Instant}};
+use serde::{Deserialize, Serialize};
+use crate::{app_log, links::is_youtube_url};
+
+/// How often the clipboard is read while watching is enabled.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a link that was already offered is ignored when it is copied again.
+const REPEAT_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+/// What happens when a new YouTube link shows up on the clipboard.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ClipboardAction {
+    /// Ask first with a small non-modal prompt.
+    #[default]
+    Prompt,
+    /// Start the download right away.
+    AutoQueue,
+}
+
+/// Polls the system clipboard for YouTube links. The clipboard is only opened on the
+/// first poll, so nothing is read while the "Watch clipboard" toggle is off.
+#[derive(Default)]
+pub struct ClipboardWatcher {
+    clipboard: Option<arboard::Clipboard>,
+    unavailable: bool,
+    /// Last clipboard text seen, so the same content is reported only once.
+    last_seen: Option<String>,
+    last_poll: Option<Instant>,
+    /// Links reported recently, so copying one over and over prompts only once.
+    offered: HashMap<String, Instant>,
+}
+
+impl ClipboardWatcher {
+    /// Returns a link copied since the last poll, if any. The first poll only records the
+    /// current content so turning the watcher on doesn't pick up an old copy.
+    pub fn poll(&mut self) -> Option<String> {
+        if self.unavailable || self.last_poll.is_some_and(|t| t.elapsed() < POLL_INTERVAL) {
+            return None;
+        }
+        let first = self.last_poll.is_none();
+        self.last_poll = Some(Instant::now());
+
+        let text = read_text(&mut self.clipboard, &mut self.unavailable)?;
+        if self.last_seen.as_ref() == Some(&text) {
+            return None;
+        }
+        self.last_seen = Some(text.clone());
+        if first || !is_youtube_url(&text) {
+            return None;
+        }
+        self.offered.retain(|_, at| at.elapsed() < REPEAT_COOLDOWN);
+        if self.offered.contains_key(&text) {
+            return None;
+        }
+        self.offered.insert(text.clone(), Instant::now());
+        Some(text)
+    }
+
+    /// Releases the clipboard and forgets what was seen, called when watching is turned off.
+    pub fn stop(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Offers a YouTube link from the clipboard for the URL field when the window gains
+/// focus. Lighter than watching: the clipboard is only read at that moment.
+#[derive(Default)]
+pub struct FocusPaste {
+    clipboard: Option<arboard::Clipboard>,
+    unavailable: bool,
+    was_focused: bool,
+    /// Last clipboard text offered, so the same copy is offered only once.
+    offered: Option<String>,
+}
+
+impl FocusPaste {
+    /// Returns the clipboard's link if the window just gained focus, the link wasn't
+    /// offered before and `wanted` accepts it.
+    pub fn on_focus(&mut self, focused: bool, wanted: impl FnOnce(&str) -> bool) -> Option<String> {
+        let gained = focused && !self.was_focused;
+        self.was_focused = focused;
+        if !gained || self.unavailable {
+            return None;
+        }
+        let text = read_text(&mut self.clipboard, &mut self.unavailable)?;
+        if !is_youtube_url(&text) || self.offered.as_ref() == Some(&text) || !wanted(&text) {
+            return None;
+        }
+        self.offered = Some(text.clone());
+        Some(text)
+    }
+}
+
+/// Reads the clipboard's text, trimmed, opening the clipboard on first use. A clipboard
+/// that can't be opened is logged once and not tried again.
+fn read_text(clipboard: &mut Option<arboard::Clipboard>, unavailable: &mut bool) -> Option<String> {
+    if clipboard.is_none() {
+        match arboard::Clipboard::new() {
+            Ok(c) => *clipboard = Some(c),
+            Err(e) => {
+                app_log::warn(format!("Clipboard unavailable: {}", e));
+                *unavailable = true;
+                return None;
+            }
+        }
+    }
+    Some(clipboard.as_mut()?.get_text().ok()?.trim().to_string())
+}
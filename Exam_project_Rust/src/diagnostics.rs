@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use crate::{downloader::ytdlp_path, paths};
+
+/// Versions of everything involved in a download, for the About dialog and bug reports.
+#[derive(Clone)]
+pub struct Diagnostics {
+    pub app_version: &'static str,
+    /// Where the embedded yt-dlp was extracted to, or why that failed.
+    pub ytdlp_path: Result<PathBuf, String>,
+    pub ytdlp_version: Option<String>,
+    /// `None` if ffmpeg isn't on the PATH.
+    pub ffmpeg_version: Option<String>,
+    pub os: String,
+}
+
+impl Diagnostics {
+    /// Runs the version probes. Meant to be called once, off the UI thread.
+    pub async fn probe() -> Self {
+        let ytdlp_path = ytdlp_path().map_err(|e| e.to_string());
+        let ytdlp_version = match &ytdlp_path {
+            Ok(path) => first_line(path, "--version").await,
+            Err(_) => None,
+        };
+        // "ffmpeg version 6.1.1-3ubuntu5 Copyright (c) …"
+        let ffmpeg_version = first_line(Path::new("ffmpeg"), "-version").await.map(|line| {
+            let version = line.strip_prefix("ffmpeg version ").unwrap_or(&line);
+            version.split_whitespace().next().unwrap_or(version).to_string()
+        });
+        Self {
+            app_version: env!("CARGO_PKG_VERSION"),
+            ytdlp_path,
+            ytdlp_version,
+            ffmpeg_version,
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        }
+    }
+
+    /// Plain-text block for pasting into a bug report.
+    pub fn to_text(&self) -> String {
+        let path = match &self.ytdlp_path {
+            Ok(path) => path.display().to_string(),
+            Err(e) => format!("unavailable ({})", e),
+        };
+        let or_missing = |v: &Option<String>| v.clone().unwrap_or_else(|| "not found".to_string());
+        let mut text = format!(
+            "YouTube Downloader {}\nyt-dlp: {} at {}\nffmpeg: {}\nOS: {}",
+            self.app_version,
+            or_missing(&self.ytdlp_version),
+            path,
+            or_missing(&self.ffmpeg_version),
+            self.os,
+        );
+        if let Some(root) = paths::portable_root() {
+            text.push_str(&format!("\nPortable: {}", root.display()));
+        }
+        text
+    }
+}
+
+/// First line `program arg` prints, `None` if it can't be run or fails.
+async fn first_line(program: &Path, arg: &str) -> Option<String> {
+    let output = Command::new(program).arg(arg).output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    text.lines().next().map(|l| l.trim().to_string()).filter(|l| !l.is_empty())
+}
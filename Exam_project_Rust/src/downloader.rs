@@ -1,62 +1,409 @@
-﻿use std::{fs::File, io::Write, process::Stdio};
+﻿use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
 use rust_embed::RustEmbed;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
 use tokio::{
     io::{AsyncBufReadExt, BufReader},
     process::Command,
-    sync::mpsc::UnboundedSender,
+    sync::oneshot,
+};
+use crate::{
+    chapters::{self, Album},
+    encode::{self, EncodeProfile},
+    events::{DownloadEvent, EventSender, Outcome, ProgressSender},
+    loudnorm,
+    model::{AudioFormat, Clip, PostStep, Quality, TaskId},
+    paths,
+    progress::{
+        is_post_processing_line, parse_destination_from_line, parse_progress_from_line,
+        parse_resolution_from_line, parse_transfer_from_line,
+    },
 };
-use crate::progress::parse_progress_from_line;
 
 #[derive(RustEmbed)]
 #[folder = "assets/"]
-pub struct Asset;
+pub(crate) struct Asset;
 
-pub async fn spawn_download(
-    url: String,
-    quality: String,
-    download_folder: String,
-    progress_tx: UnboundedSender<f32>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// Environment variable naming a yt-dlp to run instead of the embedded one, such as a
+/// newer system install or the fake one the tests use.
+pub const YTDLP_PATH_VAR: &str = "YTDLP_PATH";
+
+/// Returns the yt-dlp to run: the one in `YTDLP_PATH` if set, otherwise the embedded
+/// binary, extracted to the temp dir (once).
+pub fn ytdlp_path() -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(path) = std::env::var_os(YTDLP_PATH_VAR).filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
     let bin = if cfg!(target_os = "windows") { "yt-dlp.exe" } else { "yt-dlp" };
     let data = Asset::get(bin).ok_or("Missing yt-dlp")?;
-    let tmp = std::env::temp_dir().join(bin);
+    let dir = paths::bin_dir();
+    let tmp = dir.join(bin);
     if !tmp.exists() {
+        std::fs::create_dir_all(&dir)?;
         let mut f = File::create(&tmp)?;
         f.write_all(&data.data)?;
         #[cfg(unix)]
         std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o755))?;
+        tracing::debug!(path = %tmp.display(), "Extracted yt-dlp");
     }
+    Ok(tmp)
+}
+
+/// Everything yt-dlp needs to know about one download.
+pub struct DownloadJob {
+    pub url: String,
+    pub quality: Quality,
+    /// Format the audio is extracted to when `quality` is audio only.
+    pub audio_format: AudioFormat,
+    pub folder: String,
+    /// Continue from the .part file of an earlier attempt.
+    pub resume: bool,
+    /// Replace an existing file instead of skipping the download.
+    pub overwrite: bool,
+    /// File name (without extension) to use instead of the video title.
+    pub file_stem: Option<String>,
+    /// Download only this part of the video.
+    pub clip: Option<Clip>,
+    /// Normalize the loudness of an audio only download afterwards.
+    pub normalize: bool,
+    /// Keep the file as downloaded next to the normalized one, as "<name>.orig".
+    pub keep_original: bool,
+    /// Split an audio only download into one file per chapter of this album afterwards.
+    pub split: Option<Album>,
+    /// Re-encode a video download to this afterwards, unless it's in that format already.
+    pub encode: Option<EncodeProfile>,
+}
 
-    let mut args = vec!["-f".to_owned(), format!("best[height<={}]", match quality.as_str() {
-        "1080p" => "1080",
-        "720p" => "720",
-        "480p" => "480",
-        "360p" => "360",
-        "Audio Only" => "bestaudio",
-        _ => "best",
-    })];
+/// Arguments yt-dlp is started with for `job`, the URL last.
+pub fn ytdlp_args(job: &DownloadJob) -> Vec<String> {
+    let DownloadJob { url, quality, audio_format, folder, resume, overwrite, file_stem, clip, .. } = job;
+    let (quality, audio_format) = (*quality, *audio_format);
+
+    let mut args = if quality == Quality::AudioOnly {
+        let mut args = vec!["-f".to_owned(), audio_format.format_selector().to_owned()];
+        args.extend(["--extract-audio", "--audio-format", audio_format.codec()].map(str::to_owned));
+        if let Some(bitrate) = audio_format.bitrate() {
+            args.push("--audio-quality".to_owned());
+            args.push(bitrate.to_owned());
+        }
+        args
+    } else {
+        vec!["-f".to_owned(), quality.format_selector()]
+    };
 
     args.push("--progress-template".to_owned());
-    args.push("downloaded_bytes:%(progress._percent_str)s".to_owned());
+    args.push("downloaded_bytes:%(progress._percent_str)s bytes:%(progress.downloaded_bytes)s speed:%(progress.speed)s resolution:%(info.resolution)s".to_owned());
     args.push("--newline".to_owned());
 
+    // Pick up the .part file left by a failed attempt
+    if *resume {
+        args.push("--continue".to_owned());
+    }
+    if *overwrite {
+        args.push("--force-overwrites".to_owned());
+    }
+    // Cutting at keyframes only would make the clip start up to several seconds early
+    if let Some(clip) = clip {
+        args.extend(["--download-sections".to_owned(), clip.section(), "--force-keyframes-at-cuts".to_owned()]);
+    }
+
+    // A literal file name must have its % escaped for the output template
+    let mut stem = file_stem.as_ref().map_or_else(|| "%(title)s".to_owned(), |s| s.replace('%', "%%"));
+    if let Some(clip) = clip {
+        stem = format!("{} [{}]", stem, clip.file_suffix());
+    }
     args.push("-o".to_owned());
-    args.push(format!("{}/%(title)s.%(ext)s", download_folder));
-    args.push(url);
+    args.push(format!("{}/{}.%(ext)s", folder, stem));
+    args.push(url.clone());
+    args
+}
+
+/// `argv` (program first) as one line to paste into this platform's shell: POSIX
+/// quoting, or on Windows the quoting cmd.exe hands to programs unchanged.
+pub fn command_line(argv: &[String]) -> String {
+    let quote = if cfg!(windows) { windows_quote } else { posix_quote };
+    argv.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" ")
+}
+
+/// `arg` for sh and friends: as is if it's plain, otherwise in single quotes, with
+/// single quotes inside closed, escaped and reopened.
+pub fn posix_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// `arg` for a Windows command line as `CommandLineToArgvW` splits it: in double
+/// quotes if it has spaces, tabs or quotes, with the backslashes before a quote doubled.
+pub fn windows_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        // Backslashes only escape when a quote follows
+        let escapes = if c == '"' { backslashes * 2 + 1 } else { backslashes };
+        quoted.extend(std::iter::repeat_n('\\', escapes));
+        quoted.push(c);
+        backslashes = 0;
+    }
+    // The closing quote follows the last ones
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Runs yt-dlp for `job`, reporting its progress on `progress` and everything else it
+/// prints as task `id`'s events, then the post-processing steps `job` asks for. Sending
+/// on `cancel` kills the process; dropping the sender without sending lets the download
+/// run to completion. The caller sends the `Finished` event with the result.
+pub async fn spawn_download(
+    id: TaskId,
+    job: DownloadJob,
+    progress: ProgressSender,
+    events: EventSender,
+    mut cancel: oneshot::Receiver<()>,
+) -> Result<Outcome, Box<dyn std::error::Error + Send + Sync>> {
+    let tmp = ytdlp_path()?;
+    let args = ytdlp_args(&job);
+    tracing::debug!(?args, "Starting yt-dlp");
+    let argv = std::iter::once(tmp.display().to_string()).chain(args.iter().cloned()).collect();
+    let _ = events.send((id, DownloadEvent::Started(argv)));
 
     let mut child = Command::new(tmp)
         .args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
+        .kill_on_drop(true)
         .spawn()?;
 
+    // Drain stderr alongside stdout so a chatty yt-dlp can't block on a full pipe
+    let err = child.stderr.take().unwrap();
+    let err_tx = events.clone();
+    let stderr_task = tokio::spawn(async move {
+        let mut last_error = None;
+        let mut lines = BufReader::new(err).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let event = if line.starts_with("WARNING:") {
+                DownloadEvent::Warning(line.clone())
+            } else {
+                DownloadEvent::Line(line.clone())
+            };
+            let _ = err_tx.send((id, event));
+            if line.starts_with("ERROR:") {
+                last_error = Some(line);
+            }
+        }
+        last_error
+    });
+
     let out = child.stdout.take().unwrap();
     let mut lines = BufReader::new(out).lines();
-    while let Some(line) = lines.next_line().await? {
-        println!("DBG> {}", line);
+    let mut parser = OutputParser::default();
+    // A finished oneshot must not be polled again
+    let mut cancel_open = true;
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => line?,
+            sent = &mut cancel, if cancel_open => {
+                if sent.is_err() {
+                    cancel_open = false;
+                    continue;
+                }
+                child.kill().await?;
+                tracing::debug!("Killed yt-dlp");
+                return Ok(Outcome::Cancelled);
+            }
+        };
+        let Some(line) = line else { break };
+        parser.line(id, line, &progress, &events);
+    }
+
+    let status = child.wait().await?;
+    let last_error = stderr_task.await.unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Reading yt-dlp's stderr failed");
+        None
+    });
+    if !status.success() {
+        tracing::debug!(%status, ?last_error, "yt-dlp failed");
+        return Err(last_error.unwrap_or_else(|| format!("yt-dlp exited with {}", status)).into());
+    }
+
+    // Cancelling a step only skips it and the ones after: the download itself is complete
+    if job.quality == Quality::AudioOnly
+        && let Some(path) = &parser.destination
+    {
+        progress.send_modify(|p| p.post_processing = true);
+        let mut cancelled = false;
+        if job.normalize {
+            let _ = events.send((id, DownloadEvent::Step(PostStep::Normalizing)));
+            let warning = match until_cancelled(loudnorm::normalize(path, job.keep_original), &mut cancel, &mut cancel_open).await {
+                Some(Ok(())) => None,
+                Some(Err(e)) => Some(t!("loudnorm.failed", error = e)),
+                None => {
+                    cancelled = true;
+                    Some(t!("loudnorm.cancelled"))
+                }
+            };
+            if let Some(warning) = warning {
+                let _ = events.send((id, DownloadEvent::StepFailed(warning)));
+            }
+        }
+        if let Some(album) = job.split.filter(|a| !a.chapters.is_empty())
+            && !cancelled
+        {
+            let total = album.chapters.len();
+            let step = |done| DownloadEvent::Step(PostStep::Splitting { done, total });
+            let _ = events.send((id, step(0)));
+            let splitting = chapters::split(path, &album, |done| {
+                let _ = events.send((id, step(done)));
+            });
+            let event = match until_cancelled(splitting, &mut cancel, &mut cancel_open).await {
+                Some(Ok(tracks)) => DownloadEvent::Tracks(tracks),
+                Some(Err(e)) => DownloadEvent::StepFailed(t!("chapters.failed", error = e)),
+                None => DownloadEvent::StepFailed(t!("chapters.cancelled")),
+            };
+            let _ = events.send((id, event));
+        }
+    }
+    let mut destination = parser.destination;
+    if job.quality != Quality::AudioOnly
+        && let Some(profile) = &job.encode
+        && let Some(path) = destination.clone()
+    {
+        progress.send_modify(|p| p.post_processing = true);
+        let _ = events.send((id, DownloadEvent::Step(PostStep::Encoding { percent: 0 })));
+        // ffmpeg reports several times a second; whole percents are all the card shows
+        let mut shown = 0;
+        let encoding = encode::reencode(&path, profile, |done| {
+            let percent = (done * 100.0) as u8;
+            if percent != shown {
+                shown = percent;
+                let _ = events.send((id, DownloadEvent::Step(PostStep::Encoding { percent })));
+            }
+        });
+        let warning = match until_cancelled(encoding, &mut cancel, &mut cancel_open).await {
+            Some(Ok(output)) => {
+                destination = output.or(destination);
+                None
+            }
+            Some(Err(e)) => Some(t!("encode.failed", error = e)),
+            None => Some(t!("encode.cancelled")),
+        };
+        if let Some(warning) = warning {
+            let _ = events.send((id, DownloadEvent::StepFailed(warning)));
+        }
+    }
+    Ok(Outcome::Finished { path: destination, resolution: parser.resolution })
+}
+
+/// Runs `job` to the end, or drops it and returns `None` once `cancel` is sent on.
+/// `cancel_open` is cleared when the sender is dropped without sending.
+async fn until_cancelled<T>(job: impl Future<Output = T>, cancel: &mut oneshot::Receiver<()>, cancel_open: &mut bool) -> Option<T> {
+    let mut job = std::pin::pin!(job);
+    loop {
+        tokio::select! {
+            result = &mut job => return Some(result),
+            sent = &mut *cancel, if *cancel_open => {
+                *cancel_open = false;
+                if sent.is_ok() {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Reads yt-dlp's stdout, remembering the output file and resolution for the result.
+#[derive(Default)]
+pub struct OutputParser {
+    pub destination: Option<PathBuf>,
+    pub resolution: Option<String>,
+}
+
+impl OutputParser {
+    /// Handles one line. Progress lines only update `progress`, which keeps the latest
+    /// value, so however many yt-dlp prints nothing piles up; other lines become events.
+    pub fn line(&mut self, id: TaskId, line: String, progress: &ProgressSender, events: &EventSender) {
         if let Some(pct) = parse_progress_from_line(&line) {
-            let _ = progress_tx.send(pct);
+            let transfer = parse_transfer_from_line(&line);
+            progress.send_modify(|p| {
+                p.percent = pct;
+                p.post_processing = false;
+                if let Some((bytes, speed)) = transfer {
+                    p.bytes = Some(bytes);
+                    p.speed = speed;
+                }
+            });
+            // Merged downloads fetch the video stream and then the audio one, keep the video's
+            let audio = |r: &String| r == "audio only";
+            if let Some(res) = parse_resolution_from_line(&line)
+                && (self.resolution.is_none() || self.resolution.as_ref().is_some_and(audio) && !audio(&res))
+            {
+                self.resolution = Some(res);
+            }
+            return;
         }
+        // "[ExtractAudio] Destination: …" is both
+        if let Some(path) = parse_destination_from_line(&line) {
+            self.destination = Some(path);
+        }
+        if is_post_processing_line(&line) {
+            progress.send_modify(|p| {
+                p.post_processing = true;
+                p.speed = None;
+            });
+        }
+        let _ = events.send((id, DownloadEvent::Line(line)));
     }
-    Ok(())
-}
\ No newline at end of file
+}
+
+/// Approximates the file name yt-dlp makes out of a title: characters that aren't
+/// allowed in file names become their full-width look-alikes, as yt-dlp does by default.
+pub fn sanitize_title(title: &str) -> String {
+    let replaced: String = title
+        .chars()
+        .map(|c| match c {
+            '/' => '⧸',
+            '\\' => '⧹',
+            ':' => '：',
+            '*' => '＊',
+            '?' => '？',
+            '"' => '＂',
+            '<' => '＜',
+            '>' => '＞',
+            '|' => '｜',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    replaced.trim().trim_end_matches('.').to_string()
+}
+
+/// A finished file in `folder` named `stem` with any extension, ignoring partial downloads.
+pub fn existing_output(folder: &Path, stem: &str) -> Option<PathBuf> {
+    std::fs::read_dir(folder).ok()?.flatten().map(|e| e.path()).find(|p| {
+        let partial = p.extension().is_some_and(|ext| ext == "part" || ext == "ytdl");
+        !partial && p.file_stem().is_some_and(|s| s == stem)
+    })
+}
+
+/// First "<stem> (n)" with no existing file in `folder`.
+pub fn free_stem(folder: &Path, stem: &str) -> String {
+    (1..)
+        .map(|n| format!("{} ({})", stem, n))
+        .find(|candidate| existing_output(folder, candidate).is_none())
+        .unwrap_or_else(|| stem.to_string())
+}
@@ -0,0 +1,54 @@
+use eframe::egui;
+
+/// Where the `…` goes when text is too wide.
+#[derive(Clone, Copy)]
+pub enum Elide {
+    /// "A very long vid…", for titles.
+    End,
+    /// "C:\Users\…\Videos", for paths whose end matters most.
+    Middle,
+}
+
+/// `text` cut down with `…` to fit `max_width` in `font`, or `None` if it already fits.
+/// Measures laid out text, so wide CJK glyphs and narrow "i"s are both handled.
+pub fn elide(ui: &egui::Ui, text: &str, font: &egui::FontId, max_width: f32, mode: Elide) -> Option<String> {
+    let width = |s: String| ui.fonts(|f| f.layout_no_wrap(s, font.clone(), egui::Color32::PLACEHOLDER).size().x);
+    if width(text.to_string()) <= max_width {
+        return None;
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let shortened = |keep: usize| -> String {
+        match mode {
+            Elide::End => format!("{}…", chars[..keep].iter().collect::<String>().trim_end()),
+            Elide::Middle => {
+                let head = keep.div_ceil(2);
+                let tail = keep - head;
+                let head: String = chars[..head].iter().collect();
+                let tail: String = chars[chars.len() - tail..].iter().collect();
+                format!("{}…{}", head, tail)
+            }
+        }
+    };
+    // Most characters that still fit; width only grows with more of them
+    let (mut low, mut high) = (0, chars.len().saturating_sub(1));
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if width(shortened(mid)) <= max_width {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Some(shortened(low))
+}
+
+/// A single-line label cut to the available width, with the whole text as a tooltip
+/// when it had to be shortened.
+pub fn label(ui: &mut egui::Ui, text: &str, style: egui::TextStyle, mode: Elide) -> egui::Response {
+    let font = style.resolve(ui.style());
+    let shown = elide(ui, text, &font, ui.available_width(), mode);
+    let response = ui.add(
+        egui::Label::new(egui::RichText::new(shown.as_deref().unwrap_or(text)).text_style(style)).wrap(false),
+    );
+    if shown.is_some() { response.on_hover_text(text) } else { response }
+}
@@ -0,0 +1,220 @@
+//! Re-encoding finished videos to a chosen codec and container with ffmpeg, for players
+//! and editors that can't take what YouTube serves (VP9 or AV1 in WebM, mostly).
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+use serde::{Deserialize, Serialize};
+use crate::{ffmpeg, paths::TempFile};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum VideoCodec {
+    H264,
+    H265,
+    Vp9,
+    Av1,
+}
+
+impl VideoCodec {
+    pub const ALL: [VideoCodec; 4] = [VideoCodec::H264, VideoCodec::H265, VideoCodec::Vp9, VideoCodec::Av1];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "H.264",
+            VideoCodec::H265 => "H.265",
+            VideoCodec::Vp9 => "VP9",
+            VideoCodec::Av1 => "AV1",
+        }
+    }
+
+    /// Name ffmpeg gives the codec when describing a file.
+    pub fn ffmpeg_name(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "h264",
+            VideoCodec::H265 => "hevc",
+            VideoCodec::Vp9 => "vp9",
+            VideoCodec::Av1 => "av1",
+        }
+    }
+
+    fn encoder(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "libx264",
+            VideoCodec::H265 => "libx265",
+            VideoCodec::Vp9 => "libvpx-vp9",
+            VideoCodec::Av1 => "libaom-av1",
+        }
+    }
+
+    /// Highest CRF the encoder takes.
+    pub fn max_crf(self) -> u8 {
+        match self {
+            VideoCodec::H264 | VideoCodec::H265 => 51,
+            VideoCodec::Vp9 | VideoCodec::Av1 => 63,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Container {
+    Mp4,
+    Mkv,
+    Webm,
+}
+
+impl Container {
+    pub const ALL: [Container; 3] = [Container::Mp4, Container::Mkv, Container::Webm];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Container::Mp4 => "mp4",
+            Container::Mkv => "mkv",
+            Container::Webm => "webm",
+        }
+    }
+
+    /// WebM only takes the royalty-free codecs.
+    pub fn supports(self, codec: VideoCodec) -> bool {
+        self != Container::Webm || matches!(codec, VideoCodec::Vp9 | VideoCodec::Av1)
+    }
+
+    /// Audio encoder arguments: MP4 and WebM each have one audio codec every player
+    /// takes, Matroska takes whatever the download has.
+    fn audio_args(self) -> [&'static str; 4] {
+        match self {
+            Container::Mp4 => ["-c:a", "aac", "-b:a", "192k"],
+            Container::Webm => ["-c:a", "libopus", "-b:a", "160k"],
+            Container::Mkv => ["-c:a", "copy", "-c:s", "copy"],
+        }
+    }
+}
+
+/// How the encoder spends bits: constant quality or an average bitrate.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum RateControl {
+    /// Constant rate factor, lower is better and bigger.
+    Crf(u8),
+    /// Average video bitrate in kbit/s.
+    Bitrate(u32),
+}
+
+/// A named target format downloads can be re-encoded to.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct EncodeProfile {
+    pub name: String,
+    pub codec: VideoCodec,
+    pub container: Container,
+    pub rate: RateControl,
+}
+
+impl EncodeProfile {
+    /// Profiles offered before the user defines their own.
+    pub fn defaults() -> Vec<EncodeProfile> {
+        vec![
+            EncodeProfile {
+                name: "H.264 MP4".to_string(),
+                codec: VideoCodec::H264,
+                container: Container::Mp4,
+                rate: RateControl::Crf(23),
+            },
+            EncodeProfile {
+                name: "H.265 MKV".to_string(),
+                codec: VideoCodec::H265,
+                container: Container::Mkv,
+                rate: RateControl::Crf(28),
+            },
+            EncodeProfile {
+                name: "VP9 WebM".to_string(),
+                codec: VideoCodec::Vp9,
+                container: Container::Webm,
+                rate: RateControl::Crf(32),
+            },
+        ]
+    }
+
+    /// Whether `file`, whose video ffmpeg reports as `video_codec`, is already in this
+    /// format, so there is nothing to do.
+    pub fn matches(&self, file: &Path, video_codec: Option<&str>) -> bool {
+        let extension = file.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        extension == self.container.extension() && video_codec == Some(self.codec.ffmpeg_name())
+    }
+}
+
+/// Where `source` is written in `profile`'s container: the same name with its extension.
+pub fn output_path(source: &Path, profile: &EncodeProfile) -> PathBuf {
+    source.with_extension(profile.container.extension())
+}
+
+/// ffmpeg arguments that re-encode the video of `source` into `output` as `profile`
+/// says, keeping the audio, tags and chapters.
+pub fn encode_args(source: &Path, output: &Path, profile: &EncodeProfile) -> Vec<OsString> {
+    let mut args: Vec<OsString> = ["-v", "error", "-y", "-nostats", "-progress", "pipe:1", "-i"].map(OsString::from).into();
+    args.push(source.into());
+    args.extend(["-map", "0:v:0", "-map", "0:a?", "-map_metadata", "0"].map(OsString::from));
+    if profile.container == Container::Mkv {
+        args.extend(["-map", "0:s?"].map(OsString::from));
+    }
+    args.extend(["-c:v", profile.codec.encoder()].map(OsString::from));
+    match profile.rate {
+        // The VP9 and AV1 encoders only do constant quality with the bitrate cap off
+        RateControl::Crf(crf) => {
+            args.extend(["-crf".into(), crf.to_string().into()]);
+            if matches!(profile.codec, VideoCodec::Vp9 | VideoCodec::Av1) {
+                args.extend(["-b:v", "0"].map(OsString::from));
+            }
+        }
+        RateControl::Bitrate(kbps) => args.extend(["-b:v".into(), format!("{}k", kbps).into()]),
+    }
+    if profile.codec == VideoCodec::Av1 {
+        // libaom's default speed takes hours for a few minutes of video
+        args.extend(["-cpu-used", "6", "-row-mt", "1"].map(OsString::from));
+    }
+    if profile.codec == VideoCodec::H265 && profile.container == Container::Mp4 {
+        // Apple players only take HEVC with this tag
+        args.extend(["-tag:v", "hvc1"].map(OsString::from));
+    }
+    args.extend(profile.container.audio_args().map(OsString::from));
+    if profile.container == Container::Mp4 {
+        args.extend(["-movflags", "+faststart"].map(OsString::from));
+    }
+    args.push(output.into());
+    args
+}
+
+/// Re-encodes `source` as `profile` says, calling `on_progress` with 0.0 to 1.0 as it
+/// goes, and returns the new file, or `None` if `source` is in that format already.
+/// The original is deleted once the new file is complete; on any error, or when the
+/// future is dropped, it stays as it was. ffmpeg is killed if the future is dropped.
+pub async fn reencode(
+    source: &Path,
+    profile: &EncodeProfile,
+    mut on_progress: impl FnMut(f32),
+) -> Result<Option<PathBuf>, Box<dyn std::error::Error + Send + Sync>> {
+    if !profile.container.supports(profile.codec) {
+        return Err(format!("{} can't hold {}", profile.container.extension(), profile.codec.label()).into());
+    }
+    let info = ffmpeg::input_info(source).await?;
+    if profile.matches(source, info.video_codec.as_deref()) {
+        return Ok(None);
+    }
+    let output = output_path(source, profile);
+    if output != source && output.exists() {
+        return Err(format!("{} already exists", output.display()).into());
+    }
+
+    let partial = TempFile::next_to(&output, "encode").ok_or("Not a file name")?;
+    ffmpeg::run_with_progress(encode_args(source, &partial.0, profile), |seconds| {
+        if let Some(duration) = info.duration.filter(|d| *d > 0.0) {
+            on_progress((seconds / duration).clamp(0.0, 1.0) as f32);
+        }
+    })
+    .await?;
+    fs::rename(&partial.0, &output)?;
+    if output != source
+        && let Err(e) = fs::remove_file(source)
+    {
+        tracing::warn!(error = %e, file = %source.display(), "Couldn't delete the file after re-encoding it");
+    }
+    Ok(Some(output))
+}
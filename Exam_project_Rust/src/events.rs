@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+use eframe::egui::ColorImage;
+use tokio::sync::{mpsc::UnboundedSender, watch};
+use crate::{
+    model::{DownloadStatus, DownloadTask, PostStep, SessionStats, TaskId},
+    thumbnail::ThumbnailError,
+};
+
+/// Latest progress of a yt-dlp run. Sent over a watch channel per run, so a UI that
+/// falls behind only ever finds the newest value rather than a backlog of old ones.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Progress {
+    /// Fraction of the current stream downloaded, 0.0 to 1.0.
+    pub percent: f32,
+    /// Bytes of the current stream downloaded so far, `None` until yt-dlp reports them.
+    pub bytes: Option<u64>,
+    /// Download speed in bytes per second, `None` while yt-dlp can't tell.
+    pub speed: Option<f64>,
+    /// The download is done and a post-processor (merge, conversion, …) is running.
+    pub post_processing: bool,
+}
+
+pub type ProgressSender = watch::Sender<Progress>;
+
+/// Something that happened to a task in the background that must not be lost or
+/// merged with the next one. Every background job sends these over one channel,
+/// tagged with the task's id, so they arrive in order.
+pub enum DownloadEvent {
+    /// yt-dlp is being started with this command, program first; the first event of a run.
+    Started(Vec<String>),
+    /// A "WARNING:" line from yt-dlp.
+    Warning(String),
+    /// Any other line of yt-dlp output, kept for the task's log.
+    Line(String),
+    /// yt-dlp is done with the file and the app's own post-processing step starts.
+    Step(PostStep),
+    /// A post-processing step failed or was cancelled, leaving the downloaded file as it was.
+    StepFailed(String),
+    /// The audio was split into these files, one per chapter.
+    Tracks(Vec<PathBuf>),
+    /// The thumbnail fetch for the task's video ended.
+    Thumbnail { video_id: String, image: Result<ColorImage, ThumbnailError> },
+    /// yt-dlp exited; always the last event of a run.
+    Finished(Result<Outcome, String>),
+}
+
+/// How a yt-dlp run ended when it didn't fail.
+pub enum Outcome {
+    /// Finished, with the output file and resolution if yt-dlp reported them.
+    Finished { path: Option<PathBuf>, resolution: Option<String> },
+    /// Stopped through the cancel channel; the .part file is kept for resuming.
+    Cancelled,
+}
+
+pub type EventSender = UnboundedSender<(TaskId, DownloadEvent)>;
+
+/// What is left for the app to do after `reduce`, beyond the task's own state.
+pub enum Effect {
+    None,
+    /// Add yt-dlp output to the task's log.
+    Output(String),
+    Warning(String),
+    /// The run ended as `task.status` now says: Paused, Done or Failed.
+    Ended,
+    /// Turn the image into a texture for every task showing this video.
+    Thumbnail { video_id: String, image: Result<ColorImage, ThumbnailError> },
+}
+
+/// Applies the latest progress of a run to its task, counting new bytes in `stats`.
+pub fn apply_progress(task: &mut DownloadTask, stats: &mut SessionStats, progress: Progress) {
+    if progress.post_processing {
+        task.status = DownloadStatus::PostProcessing;
+        task.speed = None;
+        return;
+    }
+    task.status = DownloadStatus::Downloading;
+    // Only increase, never go backwards
+    if progress.percent > task.progress {
+        task.progress = progress.percent;
+    }
+    if let Some(bytes) = progress.bytes {
+        stats.add_transfer(task.transferred, bytes);
+        task.transferred = Some(bytes);
+        task.speed = progress.speed;
+    }
+}
+
+/// Applies one event to the task it is for, counting finished downloads in `stats`.
+/// Events for a task that was removed are dropped, except thumbnails which may still
+/// be wanted by another task of the same video.
+pub fn reduce(tasks: &mut [DownloadTask], stats: &mut SessionStats, id: TaskId, event: DownloadEvent) -> Effect {
+    let task = tasks.iter_mut().find(|t| t.id == id);
+    match (event, task) {
+        (DownloadEvent::Thumbnail { video_id, image }, _) => return Effect::Thumbnail { video_id, image },
+        (_, None) => {}
+        (DownloadEvent::Started(argv), Some(task)) => task.command = Some(argv),
+        (DownloadEvent::Warning(line), Some(_)) => return Effect::Warning(line),
+        (DownloadEvent::Line(line), Some(_)) => return Effect::Output(line),
+        (DownloadEvent::Step(step), Some(task)) => {
+            task.status = DownloadStatus::PostProcessing;
+            task.post_step = Some(step);
+            task.speed = None;
+        }
+        (DownloadEvent::Tracks(tracks), Some(task)) => task.tracks = tracks,
+        (DownloadEvent::StepFailed(warning), Some(task)) => {
+            task.warning = Some(warning.clone());
+            return Effect::Warning(warning);
+        }
+        (DownloadEvent::Finished(result), Some(task)) => {
+            task.speed = None;
+            task.post_step = None;
+            match result {
+                Ok(Outcome::Cancelled) => task.status = DownloadStatus::Paused,
+                Ok(Outcome::Finished { path, resolution }) => {
+                    task.file_path = path;
+                    task.resolution = resolution;
+                    task.progress = 1.0;
+                    task.status = DownloadStatus::Done;
+                    stats.completed += 1;
+                }
+                Err(e) => {
+                    task.status = DownloadStatus::Failed(e);
+                    stats.failed += 1;
+                }
+            }
+            return Effect::Ended;
+        }
+    }
+    Effect::None
+}
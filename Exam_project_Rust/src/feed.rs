@@ -0,0 +1,87 @@
+//! YouTube's per-channel and per-playlist feeds
+//! (`https://www.youtube.com/feeds/videos.xml?channel_id=…`) and OPML lists of them.
+use std::time::Duration;
+use roxmltree::{Document, Node};
+
+const ATOM: &str = "http://www.w3.org/2005/Atom";
+const YT: &str = "http://www.youtube.com/xml/schemas/2015";
+
+/// Feeds are small; don't let a stuck server hold a thread for long.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One video of a feed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedEntry {
+    pub video_id: String,
+    pub title: String,
+    /// Name of the channel that uploaded it.
+    pub author: Option<String>,
+    /// RFC 3339 timestamp, e.g. "2024-05-01T12:00:00+00:00".
+    pub published: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Feed {
+    pub title: Option<String>,
+    /// Newest first, as YouTube lists them.
+    pub entries: Vec<FeedEntry>,
+}
+
+/// Whether `url` points at a YouTube feed.
+pub fn is_feed_url(url: &str) -> bool {
+    let url = url.trim();
+    ["https://", "http://"].iter().any(|p| url.starts_with(p)) && url.contains("youtube.com/feeds/videos.xml")
+}
+
+/// Reads an Atom feed as YouTube serves it. Entries without a video id are skipped.
+pub fn parse_feed(xml: &str) -> Result<Feed, Box<dyn std::error::Error + Send + Sync>> {
+    let doc = Document::parse(xml)?;
+    let root = doc.root_element();
+    if !root.has_tag_name((ATOM, "feed")) {
+        return Err("Not an Atom feed".into());
+    }
+    let entries = children(root, ATOM, "entry")
+        .filter_map(|entry| {
+            // <yt:videoId>, or the "yt:video:<id>" form of <id> if a feed lacks it
+            let video_id = child_text(entry, YT, "videoId").or_else(|| {
+                child_text(entry, ATOM, "id").and_then(|id| id.strip_prefix("yt:video:").map(str::to_string))
+            })?;
+            let title = child_text(entry, ATOM, "title").unwrap_or_else(|| video_id.clone());
+            let author = children(entry, ATOM, "author").find_map(|a| child_text(a, ATOM, "name"));
+            let published = child_text(entry, ATOM, "published");
+            Some(FeedEntry { video_id, title, author, published })
+        })
+        .collect();
+    Ok(Feed { title: child_text(root, ATOM, "title"), entries })
+}
+
+/// The feed URLs (`xmlUrl` attributes) in an OPML file, in order, nested outlines included.
+pub fn parse_opml(xml: &str) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let doc = Document::parse(xml)?;
+    if !doc.root_element().has_tag_name("opml") {
+        return Err("Not an OPML file".into());
+    }
+    Ok(doc
+        .descendants()
+        .filter(|n| n.has_tag_name("outline"))
+        .filter_map(|n| n.attribute("xmlUrl"))
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect())
+}
+
+/// Downloads and parses the feed at `url`. Blocking.
+pub fn fetch_feed(url: &str) -> Result<Feed, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::blocking::Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+    let text = client.get(url).send()?.error_for_status()?.text()?;
+    parse_feed(&text)
+}
+
+fn children<'a, 'input>(node: Node<'a, 'input>, ns: &'a str, name: &'a str) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |c| c.has_tag_name((ns, name)))
+}
+
+fn child_text(node: Node, ns: &str, name: &str) -> Option<String> {
+    let text = children(node, ns, name).next()?.text()?.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
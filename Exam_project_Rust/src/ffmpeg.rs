@@ -0,0 +1,112 @@
+//! Running ffmpeg for the post-processing steps and reading its progress.
+use std::{ffi::OsString, io, path::Path, process::Stdio};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::Command,
+};
+use crate::model::parse_timestamp;
+
+/// Seconds of output written so far, from an `out_time_us=` line of `-progress`.
+pub fn parse_progress_line(line: &str) -> Option<f64> {
+    let micros: u64 = line.strip_prefix("out_time_us=")?.trim().parse().ok()?;
+    Some(micros as f64 / 1_000_000.0)
+}
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// A failed start, with a missing program worded for the task's error line.
+fn spawn_error(e: io::Error) -> Error {
+    if e.kind() == io::ErrorKind::NotFound { "ffmpeg wasn't found".into() } else { e.into() }
+}
+
+/// The error of a failed run: the last line ffmpeg printed, which with `-v error` is
+/// the reason, or the exit status when it printed nothing.
+fn exit_error(status: std::process::ExitStatus, stderr: &str) -> Error {
+    match stderr.lines().rev().map(str::trim).find(|l| !l.is_empty()) {
+        Some(line) => line.to_string().into(),
+        None => format!("ffmpeg exited with {}", status).into(),
+    }
+}
+
+/// Runs ffmpeg with `args` to the end and returns what it printed to stderr, which
+/// holds the reports of analysis filters. ffmpeg is killed if the future is dropped.
+pub async fn run(args: Vec<OsString>) -> Result<String, Error> {
+    let output = Command::new("ffmpeg")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(spawn_error)?;
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    if !output.status.success() {
+        return Err(exit_error(output.status, &stderr));
+    }
+    Ok(stderr)
+}
+
+/// Runs ffmpeg with `args`, which must include `-progress pipe:1`, calling `on_time`
+/// with the seconds of output written as it goes. ffmpeg is killed if the future is dropped.
+pub async fn run_with_progress(args: Vec<OsString>, mut on_time: impl FnMut(f64)) -> Result<(), Error> {
+    let mut child = Command::new("ffmpeg")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(spawn_error)?;
+    let mut lines = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+    while let Some(line) = lines.next_line().await? {
+        if let Some(seconds) = parse_progress_line(&line) {
+            on_time(seconds);
+        }
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(exit_error(output.status, &String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(())
+}
+
+/// What ffmpeg says about an input file before converting it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputInfo {
+    /// Codec of the first video stream, e.g. "h264" or "vp9".
+    pub video_codec: Option<String>,
+    /// Length in seconds.
+    pub duration: Option<f64>,
+}
+
+/// Reads the input description ffmpeg prints to stderr ("Duration: 00:03:33.02, …",
+/// "Stream #0:0: Video: vp9 (Profile 0), …").
+pub fn parse_input_info(stderr: &str) -> InputInfo {
+    let mut info = InputInfo::default();
+    for line in stderr.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("Duration: ") {
+            info.duration = info.duration.or_else(|| parse_timestamp(rest.split(',').next()?));
+        } else if line.starts_with("Stream #")
+            && let Some((_, rest)) = line.split_once("Video: ")
+            && info.video_codec.is_none()
+        {
+            info.video_codec = rest.split([' ', ',']).next().map(str::to_string);
+        }
+    }
+    info
+}
+
+/// Asks ffmpeg to describe `file`. Without an output it exits with an error, so only a
+/// missing program counts as failure.
+pub async fn input_info(file: &Path) -> Result<InputInfo, Error> {
+    let output = Command::new("ffmpeg")
+        .arg("-hide_banner")
+        .arg("-i")
+        .arg(file)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(spawn_error)?;
+    Ok(parse_input_info(&String::from_utf8_lossy(&output.stderr)))
+}
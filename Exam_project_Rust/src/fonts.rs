@@ -0,0 +1,90 @@
+use std::fs;
+use eframe::egui::{self, FontData, FontDefinitions, FontFamily};
+use crate::app_log;
+
+/// Fonts shipped inside the binary for systems that lack them, e.g. a Noto Sans CJK
+/// subset. Only built with the `bundled-fonts` feature as they add megabytes.
+#[cfg(feature = "bundled-fonts")]
+#[derive(rust_embed::RustEmbed)]
+#[folder = "assets/fonts/"]
+struct BundledFonts;
+
+/// System fonts covering scripts egui's default fonts don't, one list per script.
+/// The first file found in each list is used.
+#[cfg(target_os = "windows")]
+const SYSTEM_FONTS: &[&[&str]] = &[
+    &["C:\\Windows\\Fonts\\msyh.ttc", "C:\\Windows\\Fonts\\simsun.ttc"],
+    &["C:\\Windows\\Fonts\\YuGothM.ttc", "C:\\Windows\\Fonts\\meiryo.ttc", "C:\\Windows\\Fonts\\msgothic.ttc"],
+    &["C:\\Windows\\Fonts\\malgun.ttf", "C:\\Windows\\Fonts\\gulim.ttc"],
+    &["C:\\Windows\\Fonts\\segoeui.ttf", "C:\\Windows\\Fonts\\arial.ttf"],
+    &["C:\\Windows\\Fonts\\Nirmala.ttf"],
+    &["C:\\Windows\\Fonts\\LeelawUI.ttf", "C:\\Windows\\Fonts\\tahoma.ttf"],
+];
+#[cfg(target_os = "macos")]
+const SYSTEM_FONTS: &[&[&str]] = &[
+    &["/System/Library/Fonts/PingFang.ttc", "/System/Library/Fonts/STHeiti Medium.ttc"],
+    &["/System/Library/Fonts/Hiragino Sans GB.ttc", "/System/Library/Fonts/ヒラギノ角ゴシック W3.ttc"],
+    &["/System/Library/Fonts/AppleSDGothicNeo.ttc"],
+    &["/System/Library/Fonts/GeezaPro.ttc", "/System/Library/Fonts/Supplemental/Arial Unicode.ttf"],
+    &["/System/Library/Fonts/Thonburi.ttc"],
+];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const SYSTEM_FONTS: &[&[&str]] = &[
+    &[
+        "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+        "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+        "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    ],
+    &[
+        "/usr/share/fonts/truetype/noto/NotoSansArabic-Regular.ttf",
+        "/usr/share/fonts/noto/NotoSansArabic-Regular.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+        "/usr/share/fonts/TTF/DejaVuSans.ttf",
+        "/usr/share/fonts/dejavu-sans-fonts/DejaVuSans.ttf",
+    ],
+    &["/usr/share/fonts/truetype/noto/NotoSansThai-Regular.ttf", "/usr/share/fonts/noto/NotoSansThai-Regular.ttf"],
+    &[
+        "/usr/share/fonts/truetype/noto/NotoSansDevanagari-Regular.ttf",
+        "/usr/share/fonts/noto/NotoSansDevanagari-Regular.ttf",
+    ],
+];
+
+/// Adds fallback fonts for non-Latin titles behind egui's own fonts, so every label,
+/// tooltip and text field picks them up for glyphs the defaults lack.
+pub fn install(ctx: &egui::Context) {
+    let mut fonts = FontDefinitions::default();
+    let mut added = Vec::new();
+
+    #[cfg(feature = "bundled-fonts")]
+    for name in BundledFonts::iter() {
+        if is_font_file(&name)
+            && let Some(file) = BundledFonts::get(&name)
+        {
+            fonts.font_data.insert(name.to_string(), FontData::from_owned(file.data.into_owned()));
+            added.push(name.to_string());
+        }
+    }
+
+    for candidates in SYSTEM_FONTS {
+        let Some((path, data)) = candidates.iter().find_map(|path| Some((*path, fs::read(path).ok()?))) else { continue };
+        fonts.font_data.insert(path.to_string(), FontData::from_owned(data));
+        added.push(path.to_string());
+    }
+
+    if added.is_empty() {
+        return;
+    }
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts.families.entry(family).or_default().extend(added.iter().cloned());
+    }
+    app_log::info(format!("Fallback fonts: {}", added.join(", ")));
+    ctx.set_fonts(fonts);
+}
+
+#[cfg(feature = "bundled-fonts")]
+fn is_font_file(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    [".ttf", ".otf", ".ttc"].iter().any(|ext| name.ends_with(ext))
+}
@@ -0,0 +1,102 @@
+//! Turns part of a finished video into a GIF for sharing, with ffmpeg's two-pass
+//! palette method: the first pass picks the 256 colours that suit the clip, the second
+//! maps the frames onto them, which looks far better than ffmpeg's default palette.
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+use tokio::sync::watch;
+use crate::{ffmpeg::run_with_progress, paths::TempFile};
+
+/// The part of the video to convert and how big the GIF gets.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GifOptions {
+    /// Seconds into the video.
+    pub start: f64,
+    /// Length of the clip in seconds.
+    pub duration: f64,
+    /// Width in pixels; the height follows the aspect ratio.
+    pub width: u32,
+    pub fps: u32,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self { start: 0.0, duration: 5.0, width: 480, fps: 12 }
+    }
+}
+
+impl GifOptions {
+    fn filter(&self) -> String {
+        format!("fps={},scale={}:-2:flags=lanczos", self.fps, self.width)
+    }
+}
+
+/// Where the GIF of `source` is written: next to it with a .gif extension, numbered
+/// if that name is taken.
+pub fn output_path(source: &Path) -> PathBuf {
+    let stem = source.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut path = source.with_file_name(format!("{}.gif", stem));
+    let mut n = 2;
+    while path.exists() {
+        path = source.with_file_name(format!("{} ({}).gif", stem, n));
+        n += 1;
+    }
+    path
+}
+
+/// ffmpeg arguments of the palette pass, which writes `palette`.
+pub fn palette_args(source: &Path, palette: &Path, options: &GifOptions) -> Vec<OsString> {
+    let mut args = input_args(source, options);
+    args.extend(["-vf".into(), format!("{},palettegen", options.filter()).into()]);
+    args.push(palette.into());
+    args
+}
+
+/// ffmpeg arguments of the pass that renders the frames with `palette` into `output`.
+pub fn render_args(source: &Path, palette: &Path, output: &Path, options: &GifOptions) -> Vec<OsString> {
+    let mut args = input_args(source, options);
+    args.extend(["-i".into(), palette.into()]);
+    args.extend(["-lavfi".into(), format!("{}[x];[x][1:v]paletteuse", options.filter()).into()]);
+    args.extend(["-f".into(), "gif".into(), output.into()]);
+    args
+}
+
+/// Seeking before `-i` jumps straight to the start instead of decoding up to it.
+fn input_args(source: &Path, options: &GifOptions) -> Vec<OsString> {
+    let mut args: Vec<OsString> = ["-v", "error", "-y", "-nostats", "-progress", "pipe:1"].map(OsString::from).into();
+    args.extend(["-ss".into(), format!("{:.3}", options.start).into()]);
+    args.extend(["-t".into(), format!("{:.3}", options.duration).into()]);
+    args.extend(["-i".into(), source.into()]);
+    args
+}
+
+/// Converts the clip of `source` that `options` describe into a GIF next to it and
+/// returns its path. `progress` goes from 0.0 to 1.0 over both passes. ffmpeg is
+/// killed if the future is dropped, which is how a conversion is cancelled.
+pub async fn convert(
+    source: &Path,
+    options: GifOptions,
+    progress: watch::Sender<f32>,
+) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+    let output = output_path(source);
+    let name = output.file_name().ok_or("Not a file name")?.to_string_lossy().into_owned();
+    let palette = TempFile(output.with_file_name(format!(".{}.palette.png", name)));
+    let partial = TempFile(output.with_file_name(format!(".{}.part", name)));
+
+    for (pass, args) in [
+        palette_args(source, &palette.0, &options),
+        render_args(source, &palette.0, &partial.0, &options),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        run_with_progress(args, |seconds| {
+            let done = (seconds / options.duration).clamp(0.0, 1.0) as f32;
+            progress.send_replace((pass as f32 + done) / 2.0);
+        })
+        .await?;
+    }
+    std::fs::rename(&partial.0, &output)?;
+    Ok(output)
+}
@@ -0,0 +1,217 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use serde::{Deserialize, Serialize, Serializer};
+use crate::{app_log, model::Quality, paths};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HistoryStatus {
+    Done,
+    Failed,
+}
+
+/// One finished (or failed) download, as shown in the History view.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub title: String,
+    pub video_id: String,
+    pub url: String,
+    pub quality: Quality,
+    pub folder: String,
+    pub file_path: Option<PathBuf>,
+    pub status: HistoryStatus,
+    /// Seconds since the Unix epoch.
+    pub finished_at: i64,
+    /// Size of the saved file in bytes.
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// Length of the video in seconds.
+    #[serde(default)]
+    pub duration: Option<u64>,
+    /// The file was deleted with "Delete file".
+    #[serde(default)]
+    pub file_deleted: bool,
+}
+
+impl HistoryEntry {
+    /// Local date and time the download finished, e.g. "2024-03-02 21:15".
+    pub fn date_string(&self) -> String {
+        chrono::DateTime::from_timestamp(self.finished_at, 0)
+            .map(|d| d.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.title.to_lowercase().contains(&query)
+            || self.video_id.to_lowercase().contains(&query)
+            || self.url.to_lowercase().contains(&query)
+    }
+}
+
+/// Download history persisted as JSON in the platform data directory.
+#[derive(Default)]
+pub struct History {
+    pub entries: Vec<HistoryEntry>,
+    /// Index into `entries` of each video's latest successful download.
+    done: HashMap<String, usize>,
+}
+
+impl History {
+    /// Loads the history file; a missing or corrupt file gives an empty history.
+    pub fn load() -> Self {
+        let entries: Vec<HistoryEntry> = history_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| match serde_json::from_str(&text) {
+                Ok(entries) => Some(entries),
+                Err(e) => {
+                    app_log::warn(format!("Ignoring corrupt history file: {}", e));
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let mut history = Self::default();
+        for entry in entries {
+            history.push(entry);
+        }
+        history
+    }
+
+    /// The latest successful download of `video_id`, if there was one.
+    pub fn last_done(&self, video_id: &str) -> Option<&HistoryEntry> {
+        self.done.get(video_id).map(|&i| &self.entries[i])
+    }
+
+    pub fn append(&mut self, entry: HistoryEntry) {
+        self.push(entry);
+        if let Err(e) = self.save() {
+            app_log::error(format!("Failed to save history: {}", e));
+        }
+    }
+
+    /// Notes that the file at `path` was deleted in every entry that saved it there.
+    pub fn mark_deleted(&mut self, path: &Path) {
+        let mut changed = false;
+        for entry in self.entries.iter_mut().filter(|e| e.file_path.as_deref() == Some(path)) {
+            entry.file_deleted = true;
+            changed = true;
+        }
+        if changed && let Err(e) = self.save() {
+            app_log::error(format!("Failed to save history: {}", e));
+        }
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        if entry.status == HistoryStatus::Done {
+            self.done.insert(entry.video_id.clone(), self.entries.len());
+        }
+        self.entries.push(entry);
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = history_path().ok_or("No data directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(&self.entries)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// One history entry as exported, with the columns in this order.
+#[derive(Serialize)]
+struct ExportRow<'a> {
+    date: String,
+    title: &'a str,
+    url: &'a str,
+    quality: &'static str,
+    size: Option<u64>,
+    duration: Option<u64>,
+    status: &'static str,
+    file_path: Option<String>,
+}
+
+impl<'a> ExportRow<'a> {
+    const COLUMNS: [&'static str; 8] = ["date", "title", "url", "quality", "size", "duration", "status", "file_path"];
+
+    fn new(entry: &'a HistoryEntry) -> Self {
+        Self {
+            date: chrono::DateTime::from_timestamp(entry.finished_at, 0)
+                .map(|d| d.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            title: &entry.title,
+            url: &entry.url,
+            quality: entry.quality.label(),
+            size: entry.size,
+            duration: entry.duration,
+            status: match entry.status {
+                HistoryStatus::Done => "done",
+                HistoryStatus::Failed => "failed",
+            },
+            file_path: entry.file_path.as_ref().map(|p| p.display().to_string()),
+        }
+    }
+
+    fn csv_fields(&self) -> [String; 8] {
+        let number = |n: Option<u64>| n.map(|n| n.to_string()).unwrap_or_default();
+        [
+            self.date.clone(),
+            self.title.to_string(),
+            self.url.to_string(),
+            self.quality.to_string(),
+            number(self.size),
+            number(self.duration),
+            self.status.to_string(),
+            self.file_path.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+/// Writes `entries` to `path` as JSON if it ends in ".json", otherwise as CSV. Rows
+/// are written one at a time, so a long history never has to fit in one string.
+pub fn export(entries: &[HistoryEntry], path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut out = BufWriter::new(File::create(path)?);
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+        serde_json::Serializer::pretty(&mut out).collect_seq(entries.iter().map(ExportRow::new))?;
+        writeln!(out)?;
+    } else {
+        write_csv_line(&mut out, &ExportRow::COLUMNS)?;
+        for entry in entries {
+            write_csv_line(&mut out, &ExportRow::new(entry).csv_fields())?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// One RFC 4180 line: fields with commas, quotes or line breaks are quoted, with
+/// quotes doubled.
+fn write_csv_line(out: &mut impl Write, fields: &[impl AsRef<str>]) -> std::io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        let field = field.as_ref();
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            out.write_all(field.as_bytes())?;
+        }
+    }
+    out.write_all(b"\r\n")
+}
+
+pub fn now_unix() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+fn history_path() -> Option<PathBuf> {
+    Some(paths::data_dir()?.join("history.json"))
+}
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, fmt::Display, sync::RwLock};
+use once_cell::sync::Lazy;
+use rust_embed::RustEmbed;
+
+/// UI strings, one flat `key -> text` JSON file per language. `{name}` marks a placeholder.
+#[derive(RustEmbed)]
+#[folder = "locales/"]
+struct Locales;
+
+/// Language used for keys missing from the selected one.
+const FALLBACK: &str = "en";
+
+struct Catalog {
+    code: String,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+static CATALOG: Lazy<RwLock<Catalog>> = Lazy::new(|| {
+    RwLock::new(Catalog { code: FALLBACK.to_string(), strings: HashMap::new(), fallback: load(FALLBACK) })
+});
+
+fn load(code: &str) -> HashMap<String, String> {
+    let Some(file) = Locales::get(&format!("{}.json", code)) else { return HashMap::new() };
+    serde_json::from_slice(&file.data).unwrap_or_else(|e| {
+        eprintln!("Ignoring broken locale {}: {}", code, e);
+        HashMap::new()
+    })
+}
+
+/// Shipped languages as (code, native name), e.g. ("de", "Deutsch").
+pub fn available() -> Vec<(String, String)> {
+    let mut langs: Vec<(String, String)> = Locales::iter()
+        .filter_map(|f| f.strip_suffix(".json").map(str::to_string))
+        .map(|code| {
+            let name = load(&code).remove("_name").unwrap_or_else(|| code.clone());
+            (code, name)
+        })
+        .collect();
+    langs.sort();
+    langs
+}
+
+/// Switches the UI language; `None` follows the system locale, falling back to English.
+pub fn set_language(code: Option<&str>) {
+    let system = sys_locale::get_locale();
+    let wanted = code.or(system.as_deref()).unwrap_or(FALLBACK);
+    // "de-DE" and "de_AT" both use de.json
+    let primary = wanted.split(['-', '_']).next().unwrap_or(FALLBACK).to_lowercase();
+    let code = if Locales::get(&format!("{}.json", primary)).is_some() { primary } else { FALLBACK.to_string() };
+
+    let mut catalog = CATALOG.write().unwrap();
+    if catalog.code != code || catalog.strings.is_empty() {
+        catalog.strings = load(&code);
+        catalog.code = code;
+    }
+}
+
+/// Text for `key` in the current language. Unknown keys come back unchanged so they stand out.
+pub fn tr(key: &str) -> String {
+    let catalog = CATALOG.read().unwrap();
+    catalog
+        .strings
+        .get(key)
+        .or_else(|| catalog.fallback.get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// Like `tr`, then replaces each `{name}` with its value.
+pub fn tr_args(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(tr(key), |text, (name, value)| text.replace(&format!("{{{}}}", name), &value.to_string()))
+}
+
+/// `t!("key")` or `t!("key", count = n, name = x)`.
+#[macro_export]
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::tr_args($key, &[$((stringify!($name), &$value as &dyn std::fmt::Display)),+])
+    };
+}
@@ -0,0 +1,25 @@
+//! Download logic without the window: yt-dlp invocation and progress parsing, the
+//! task model and queue rules, metadata and thumbnails, and handing files to the OS.
+//! The app and its command line mode are built on top of it.
+
+#[macro_use]
+pub mod i18n;
+pub mod cache;
+pub mod chapters;
+pub mod downloader;
+pub mod encode;
+pub mod events;
+pub mod feed;
+pub mod ffmpeg;
+pub mod gif;
+pub mod loudnorm;
+pub mod metadata;
+pub mod model;
+pub mod os_open;
+pub mod paths;
+pub mod progress;
+pub mod queue;
+pub mod rules;
+pub mod subscriptions;
+pub mod tags;
+pub mod thumbnail;
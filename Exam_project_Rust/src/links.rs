@@ -0,0 +1,73 @@
+use std::{fs, path::Path};
+
+/// Pulls candidate URLs out of a dropped or imported file.
+///
+/// `.url` shortcuts (as saved by browsers on Windows) contribute their `URL=` line,
+/// anything else is read as text with one URL per line. Blank lines and lines
+/// starting with `#` are skipped.
+pub fn urls_from_file(path: &Path) -> std::io::Result<Vec<String>> {
+    let text = fs::read_to_string(path)?;
+    let shortcut = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("url"));
+    if shortcut {
+        return Ok(text
+            .lines()
+            .filter_map(|l| l.trim().strip_prefix("URL="))
+            .map(|u| u.trim().to_string())
+            .collect());
+    }
+    Ok(urls_from_text(&text))
+}
+
+pub fn urls_from_text(text: &str) -> Vec<String> {
+    numbered_urls(text).into_iter().map(|(_, url)| url).collect()
+}
+
+/// Like `urls_from_text` but keeps the 1-based line number of every entry.
+pub fn numbered_urls(text: &str) -> Vec<(usize, String)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'))
+        .map(|(n, l)| (n, l.to_string()))
+        .collect()
+}
+
+/// The `list=` parameter of a YouTube playlist link.
+pub fn playlist_id(url: &str) -> Option<&str> {
+    let id = url.split(['?', '&']).find_map(|p| p.strip_prefix("list="))?;
+    let id = id.split('#').next().unwrap_or(id);
+    (!id.is_empty()).then_some(id)
+}
+
+/// Whether `text` looks like a `youtube.com/watch?v=` link, the form `extract_video_id` understands.
+pub fn is_youtube_url(text: &str) -> bool {
+    let text = text.trim();
+    ["https://", "http://"].iter().any(|p| text.starts_with(p))
+        && text.contains("youtube.com/")
+        && text.contains("v=")
+        && !text.contains(char::is_whitespace)
+}
+
+/// The uploads tab of a YouTube channel link (`/@name`, `/channel/…`, `/c/…` or
+/// `/user/…`), e.g. "https://www.youtube.com/@name/videos". A link to the Shorts or
+/// Live tab keeps it. `None` for anything else.
+pub fn channel_uploads_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    let rest = ["https://", "http://"].iter().find_map(|p| url.strip_prefix(p))?;
+    let rest = rest.split(['?', '#']).next()?;
+    let (host, path) = rest.split_once('/')?;
+    if !matches!(host, "youtube.com" | "www.youtube.com" | "m.youtube.com") {
+        return None;
+    }
+    let mut parts = path.split('/').filter(|p| !p.is_empty());
+    let first = parts.next()?;
+    let channel = if first.starts_with('@') {
+        first.to_string()
+    } else if matches!(first, "channel" | "c" | "user") {
+        format!("{}/{}", first, parts.next()?)
+    } else {
+        return None;
+    };
+    let tab = parts.next().filter(|tab| matches!(*tab, "shorts" | "streams")).unwrap_or("videos");
+    Some(format!("https://www.youtube.com/{}/{}", channel, tab))
+}
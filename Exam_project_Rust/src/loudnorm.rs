@@ -0,0 +1,136 @@
+//! Loudness normalization of audio downloads to EBU R128 with ffmpeg's loudnorm filter,
+//! in two passes: the first measures the file, the second applies one linear gain
+//! worked out from that, so the dynamics of the recording stay as they were.
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use serde::Deserialize;
+use crate::{ffmpeg, paths::TempFile};
+
+/// Integrated loudness, true peak and loudness range aimed for, as podcast platforms use.
+const TARGET: &str = "I=-16:TP=-1.5:LRA=11";
+
+/// What the measuring pass found, in LUFS, dBTP and LU.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Measurement {
+    pub input_i: f64,
+    pub input_tp: f64,
+    pub input_lra: f64,
+    pub input_thresh: f64,
+    pub target_offset: f64,
+}
+
+/// loudnorm prints its numbers as strings.
+#[derive(Deserialize)]
+struct RawMeasurement {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    target_offset: String,
+}
+
+/// Reads the JSON block loudnorm prints at the end of the measuring pass's stderr.
+/// `None` if there is none or it holds no finite numbers, as for a silent file.
+pub fn parse_measurement(stderr: &str) -> Option<Measurement> {
+    let start = stderr.rfind('{')?;
+    let end = start + stderr[start..].find('}')?;
+    let raw: RawMeasurement = serde_json::from_str(&stderr[start..=end]).ok()?;
+    let number = |text: &str| text.trim().parse::<f64>().ok().filter(|v| v.is_finite());
+    Some(Measurement {
+        input_i: number(&raw.input_i)?,
+        input_tp: number(&raw.input_tp)?,
+        input_lra: number(&raw.input_lra)?,
+        input_thresh: number(&raw.input_thresh)?,
+        target_offset: number(&raw.target_offset)?,
+    })
+}
+
+/// Sample rate of the first audio stream, from ffmpeg's description of the input
+/// ("Stream #0:0: Audio: aac (LC), 44100 Hz, stereo, …").
+pub fn parse_sample_rate(stderr: &str) -> Option<u32> {
+    let line = stderr.lines().find(|l| l.contains("Stream #") && l.contains("Audio:"))?;
+    line.split(',').find_map(|part| part.trim().strip_suffix(" Hz")?.parse().ok())
+}
+
+/// ffmpeg arguments of the measuring pass, which writes nothing.
+pub fn measure_args(source: &Path) -> Vec<OsString> {
+    let mut args: Vec<OsString> = ["-hide_banner", "-nostats", "-i"].map(OsString::from).into();
+    args.push(source.into());
+    args.extend(["-map", "0:a:0", "-af"].map(OsString::from));
+    args.push(format!("loudnorm={}:print_format=json", TARGET).into());
+    args.extend(["-f", "null", "-"].map(OsString::from));
+    args
+}
+
+/// ffmpeg arguments of the pass that writes the normalized audio to `output`, keeping
+/// tags and cover art. loudnorm works at 192 kHz, so the sample rate is set back.
+pub fn normalize_args(source: &Path, output: &Path, measured: &Measurement, sample_rate: u32) -> Vec<OsString> {
+    let filter = format!(
+        "loudnorm={}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true:print_format=none",
+        TARGET, measured.input_i, measured.input_tp, measured.input_lra, measured.input_thresh, measured.target_offset,
+    );
+    let mut args: Vec<OsString> = ["-v", "error", "-y", "-nostats", "-i"].map(OsString::from).into();
+    args.push(source.into());
+    args.extend(["-map", "0:a:0", "-map", "0:v?", "-map_metadata", "0", "-c", "copy"].map(OsString::from));
+    args.extend(["-af".into(), filter.into()]);
+    args.extend(encoder_args(output, sample_rate));
+    args.push(output.into());
+    args
+}
+
+/// Encoder for the audio by the file's extension, at a bitrate that loses little on top
+/// of the original encoding. Opus only knows 48 kHz among the usual rates.
+fn encoder_args(output: &Path, sample_rate: u32) -> Vec<OsString> {
+    let extension = output.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let (codec, bitrate, rate) = match extension.as_str() {
+        "mp3" => ("libmp3lame", Some("192k"), sample_rate),
+        "m4a" | "aac" => ("aac", Some("192k"), sample_rate),
+        "opus" => ("libopus", Some("160k"), 48000),
+        "flac" => ("flac", None, sample_rate),
+        "wav" => ("pcm_s16le", None, sample_rate),
+        _ => ("libvorbis", Some("192k"), sample_rate),
+    };
+    let mut args: Vec<OsString> = vec!["-c:a".into(), codec.into()];
+    if let Some(bitrate) = bitrate {
+        args.extend(["-b:a".into(), bitrate.into()]);
+    }
+    args.extend(["-ar".into(), rate.to_string().into()]);
+    args
+}
+
+/// Where `file` is kept when the original is wanted as well: "song.mp3.orig".
+pub fn original_path(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(".orig");
+    PathBuf::from(name)
+}
+
+/// Puts `normalized` in place of `file` with a rename, which replaces it atomically on
+/// the same drive. With `keep_original` the original is renamed to `original_path`
+/// first, and put back if the second rename fails.
+pub fn replace_file(file: &Path, normalized: &Path, keep_original: bool) -> io::Result<()> {
+    if !keep_original {
+        return fs::rename(normalized, file);
+    }
+    let original = original_path(file);
+    fs::rename(file, &original)?;
+    fs::rename(normalized, file).inspect_err(|_| {
+        let _ = fs::rename(&original, file);
+    })
+}
+
+/// Normalizes the loudness of the audio file `file` in place. On any error the file is
+/// left as it was. ffmpeg is killed if the future is dropped.
+pub async fn normalize(file: &Path, keep_original: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let report = ffmpeg::run(measure_args(file)).await?;
+    let measured = parse_measurement(&report).ok_or("ffmpeg couldn't measure the loudness, the audio may be silent")?;
+    let sample_rate = parse_sample_rate(&report).unwrap_or(48000);
+
+    let output = TempFile::next_to(file, "loudnorm").ok_or("Not a file name")?;
+    ffmpeg::run(normalize_args(file, &output.0, &measured, sample_rate)).await?;
+    replace_file(file, &output.0, keep_original)?;
+    Ok(())
+}
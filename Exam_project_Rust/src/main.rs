@@ -1,252 +1,5532 @@
-mod thumbnail;
-mod downloader;
-mod progress;
-mod model;
-use model::{DownloadTask, DownloadStatus};
+#[macro_use]
+extern crate exam_project_rust;
+mod settings;
+mod history;
+mod toast;
+mod links;
+mod clipboard_watch;
+mod notifications;
+mod app_log;
+mod diagnostics;
+mod session;
+mod schedule;
+mod power;
+mod fonts;
+mod elide;
+mod protocol;
+mod cli;
+mod overrides;
+mod player;
+mod status_style;
+mod watch_folder;
+#[cfg(feature = "tray")]
+mod tray;
+#[cfg(feature = "sound")]
+mod sound;
+#[cfg(windows)]
+mod taskbar;
+use exam_project_rust::{cache, chapters, downloader, encode, events, feed, gif, i18n, metadata, model, os_open, paths, queue, rules, subscriptions, tags, thumbnail};
+use model::{BandwidthHistory, format_duration, format_grouped, format_minutes, format_size, parse_timestamp, AudioFormat, Clip, DownloadTask, DownloadStatus, Playlist, PlaylistId, Quality, SessionStats, SortOrder, StatusFilter, TaskId, ThumbnailState};
+use metadata::{PlaylistEntry, PlaylistMetadata, SearchResult, VideoMetadata};
+use thumbnail::ThumbnailError;
+use settings::{ExitAction, Preset, Settings, ThemePreference, MAX_PARALLEL, SUBSCRIPTION_INTERVAL_RANGE, UI_SCALE_RANGE};
+#[cfg(feature = "sound")]
+use settings::SoundWhen;
+use history::{History, HistoryEntry, HistoryStatus};
+use toast::Toasts;
+use clipboard_watch::{ClipboardAction, ClipboardWatcher, FocusPaste};
+use watch_folder::WatchEvent;
+use app_log::Level;
+use diagnostics::Diagnostics;
+use overrides::Overrides;
+use subscriptions::{NewVideo, Subscription, Subscriptions};
+use session::{QueueImport, SavedStatus, SavedTask, Session};
+use tags::Tags;
+use power::AfterQueue;
+use elide::Elide;
+use status_style::{status_style, themed, Palette};
+use queue::Move;
+use rules::{Condition, MoveRule};
+use gif::GifOptions;
+use encode::{Container, EncodeProfile, RateControl, VideoCodec};
 
-use downloader::spawn_download;
+use downloader::{spawn_download, ytdlp_path, DownloadJob};
+use events::{DownloadEvent, Effect, EventSender, Progress};
 
 
+use chrono::{DateTime, Local, TimeZone};
 use eframe::{egui, App, Frame};
-use once_cell::sync::OnceCell;
 use rfd::FileDialog;
 use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    time::{Duration, Instant},
 };
+use tracing::Instrument;
 use tokio::{
-    runtime::Runtime,
-    sync::mpsc::{unbounded_channel, UnboundedReceiver},
+    runtime::{Handle, Runtime},
+    sync::{
+        mpsc::{unbounded_channel, UnboundedReceiver},
+        oneshot, watch,
+    },
+    task::{AbortHandle, JoinError},
 };
-use egui::{ColorImage, TextureOptions, Visuals};
+use egui::{ColorImage, TextureOptions};
 
 
-
-static RUNTIME: OnceCell<Arc<Runtime>> = OnceCell::new();
+/// Wait after a failed thumbnail fetch before trying again, doubled per failure.
+const THUMBNAIL_COOLDOWN: Duration = Duration::from_secs(15);
+/// Failed fetch rounds after which a thumbnail is given up on.
+const THUMBNAIL_MAX_ROUNDS: u32 = 5;
+/// Settings changes are written once they have been stable this long (e.g. typing a path).
+const SETTINGS_SAVE_DELAY: Duration = Duration::from_secs(1);
+/// Latest uploads listed per subscription check.
+const SUBSCRIPTION_WINDOW: usize = 15;
+/// Pause between two subscription checks, so many channels don't flood YouTube.
+const SUBSCRIPTION_SPACING: Duration = Duration::from_secs(30);
+/// Wait before checking subscriptions again after finding the machine offline.
+const OFFLINE_RETRY: Duration = Duration::from_secs(300);
+/// Search hits loaded at once, and by "Load more".
+const SEARCH_PAGE: usize = 10;
+/// How many queued tasks at least have their metadata fetched ahead of their turn;
+/// playlist entries start without it.
+const METADATA_AHEAD: usize = 3;
+/// How far outside the visible part of the downloads list cards still get thumbnails.
+const THUMBNAIL_LOOKAHEAD: f32 = 300.0;
+/// Height assumed for a downloads list card that hasn't been shown yet.
+const CARD_ROW_GUESS: f32 = 140.0;
+/// Same for compact rows and playlist headers.
+const LINE_ROW_GUESS: f32 = 24.0;
+/// How often the window redraws while downloads run, to pick up their progress.
+const PROGRESS_POLL: Duration = Duration::from_millis(100);
+/// How long quitting waits for killed yt-dlp processes to exit.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+/// Pause in typing before the URL field's metadata is fetched for the size estimate.
+const PREVIEW_DELAY: Duration = Duration::from_millis(500);
+/// How long a card added with the Download button stays highlighted.
+const NEW_TASK_HIGHLIGHT: Duration = Duration::from_millis(2000);
+/// Scrolling the downloads list within this long stops new cards from being scrolled to.
+const USER_SCROLL_GRACE: Duration = Duration::from_millis(1000);
+/// Countdown before the "When all downloads finish" action, giving time to cancel it.
+const AFTER_QUEUE_COUNTDOWN: Duration = Duration::from_secs(60);
+/// Widest a recent folder gets in the folder menu before its middle is cut out.
+const RECENT_FOLDER_WIDTH: f32 = 360.0;
+/// Where the setup wizard sends users missing yt-dlp or ffmpeg.
+const YTDLP_INSTALL_URL: &str = "https://github.com/yt-dlp/yt-dlp#installation";
+const FFMPEG_DOWNLOAD_URL: &str = "https://ffmpeg.org/download.html";
+/// How long a removal can be undone.
+const UNDO_WINDOW: Duration = Duration::from_secs(8);
+/// Queue files with more tasks than this are only imported after asking.
+const LARGE_QUEUE_IMPORT: usize = 500;
+/// How long "Copied!" stays on a card after copying its URL or path.
+const COPIED_NOTICE: Duration = Duration::from_millis(1500);
 
 fn main() -> Result<(), eframe::Error> {
-    let rt = Arc::new(Runtime::new().unwrap());
-    RUNTIME.set(rt).unwrap();
-
-    let options = eframe::NativeOptions::default();
-    eframe::run_native(
-        "YouTube Downloader",
-        options,
-        Box::new(|cc| {
-
-            let visuals = Visuals::dark();
-            
+    // Launched for a ytdlgui:// link: let a running instance queue it if there is one
+    let launch_link = protocol::link_from_args(std::env::args().skip(1));
+    if let Some(link) = &launch_link
+        && protocol::forward(link)
+    {
+        return Ok(());
+    }
 
-            cc.egui_ctx.set_visuals(visuals);
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    paths::init(&mut args);
+    let mut overrides = match Overrides::parse(&mut args) {
+        Ok(overrides) => overrides,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, t!("cli.usage"));
+            std::process::exit(cli::USAGE_ERROR);
+        }
+    };
+    if let Some(path) = overrides.config.clone() {
+        settings::use_file(path);
+    }
+    let runtime = Runtime::new().unwrap();
 
-            Box::new(MyApp::default())
+    let first_run = !Settings::saved_before();
+    let mut settings = Settings::load();
+    overrides.apply(&mut settings);
+    let log_guard = app_log::init(settings.log_file);
+    i18n::set_language(settings.language.as_deref());
+    if cli::requested(&args) {
+        let code = cli::run(&args, &settings, &runtime);
+        drop(log_guard);
+        std::process::exit(code);
+    }
+    let mut viewport = egui::ViewportBuilder::default();
+    if let Some(w) = settings.window {
+        viewport = viewport
+            .with_position([w.x, w.y])
+            .with_inner_size([w.width, w.height])
+            .with_maximized(w.maximized);
+    }
+    let options = eframe::NativeOptions {
+        viewport,
+        // Keeps integration_info.system_theme current for the "Follow system" theme
+        follow_system_theme: true,
+        ..Default::default()
+    };
+    let handle = runtime.handle().clone();
+    let result = eframe::run_native(
+        &t!("app.title"),
+        options,
+        Box::new(move |cc| {
+            fonts::install(&cc.egui_ctx);
+            let mut app = MyApp::new(settings, overrides, handle);
+            if first_run {
+                app.wizard = Some(WizardStep::Folder);
+            }
+            match launch_link.as_deref().map(protocol::video_url) {
+                Some(Some(url)) => app.received_links.locked().push(url),
+                Some(None) => app.toasts.error(t!("toast.bad_link")),
+                None => {}
+            }
+            protocol::listen(&cc.egui_ctx, Arc::clone(&app.received_links));
+            app.probe_versions(&cc.egui_ctx);
+            app.apply_theme(&cc.egui_ctx, cc.integration_info.system_theme);
+            Box::new(app)
         }),
-    )
+    );
+    // Don't wait for blocking work such as an import still reading its file
+    runtime.shutdown_background();
+    drop(log_guard);
+    result
 }
 
 struct MyApp {
+    /// Where background work is spawned; owned by `main`.
+    runtime: Handle,
     url_input: String,
-    download_folder: String,
-    selected_quality: String,
-    quality_options: Vec<String>,
+    settings: Settings,
+    /// Zoom factor last given to egui, to tell keyboard zooming apart from the slider.
+    applied_zoom: f32,
+    /// Language setting the string catalog was loaded for.
+    language: Option<String>,
+    /// Last state written to disk, compared each frame to detect changes.
+    saved_settings: Settings,
+    /// Settings given on the command line or in the environment for this session.
+    overrides: Overrides,
+    settings_dirty_since: Option<Instant>,
     downloads: Vec<DownloadTask>,
+    playlists: Vec<Playlist>,
+    next_playlist_id: PlaylistId,
+    playlist_results: PlaylistResults,
+    /// Playlists yt-dlp is still listing, with the form's choices for their tasks.
+    loading_playlists: HashMap<PlaylistId, PlaylistRequest>,
     thumbnails: HashMap<String, egui::TextureHandle>,
-    thumbnail_results: Arc<Mutex<Vec<(String, ColorImage)>>>,
-    progress_rxs: HashMap<String, UnboundedReceiver<f32>>,
+    placeholder: Option<egui::TextureHandle>,
+    thumbnail_retries: HashMap<String, ThumbnailRetry>,
+    /// Video ids whose cards were on screen (plus lookahead) last frame.
+    visible_tasks: HashSet<String>,
+    /// Last measured height of each downloads list row, so rows off screen can be
+    /// skipped with a gap of the right size instead of being built.
+    row_heights: HashMap<egui::Id, f32>,
+    metadata_results: MetadataResults,
+    /// Tasks whose metadata is being fetched.
+    metadata_pending: HashSet<TaskId>,
+    next_task_id: TaskId,
+    /// Latest progress of each running yt-dlp process.
+    progress_rxs: HashMap<TaskId, watch::Receiver<Progress>>,
+    /// Output, warnings, thumbnails and exits of all background jobs, in order.
+    events: UnboundedReceiver<(TaskId, DownloadEvent)>,
+    /// Cloned into every job that reports to `events`.
+    events_tx: EventSender,
+    /// Kill switches for running yt-dlp processes.
+    cancel_txs: HashMap<TaskId, oneshot::Sender<()>>,
+    history: History,
+    session: SessionStats,
+    history_query: String,
+    search: SearchState,
+    search_results: SearchResults,
+    search_thumbnails: SearchThumbnails,
+    downloads_query: String,
+    status_filter: StatusFilter,
+    /// Task the arrow keys move from and Shift+click selects up to.
+    selected: Option<TaskId>,
+    /// Tasks picked with click, Ctrl+click and Shift+click, for the bulk actions.
+    selection: HashSet<TaskId>,
+    /// Set when the selection moved by keyboard, so the list scrolls to it.
+    scroll_to_selected: bool,
+    /// Active task Delete was pressed on, waiting for confirmation.
+    confirm_delete: Option<TaskId>,
+    /// Downloaded file "Delete file" was chosen for, waiting for confirmation.
+    confirm_delete_file: Option<FileToDelete>,
+    /// Task just added with the Download button, highlighted for a moment.
+    new_task: Option<(TaskId, Instant)>,
+    /// Set until the list has scrolled to `new_task`.
+    reveal_new_task: bool,
+    /// When the user last scrolled the downloads list.
+    list_scrolled_at: Option<Instant>,
+    view: View,
+    toasts: Toasts,
+    /// Removed tasks that can still be brought back with Undo.
+    trash: Vec<Trashed>,
+    clear_failed_too: bool,
+    confirm_clear: bool,
+    show_debug: bool,
+    /// Smoothed CPU time per frame in milliseconds, for the debug overlay.
+    frame_time: f32,
+    /// Downloads list rows built last frame, for the debug overlay.
+    rows_built: usize,
+    /// Frames drawn so far; stands still while the window is idle.
+    frames: u64,
+    show_logs: bool,
+    subscriptions: Subscriptions,
+    subscription_results: SubscriptionResults,
+    /// Channel being checked; only one check runs at a time.
+    subscription_check: Option<String>,
+    /// No check starts before this, to space them out and wait while offline.
+    next_subscription_check: Instant,
+    show_subscriptions: bool,
+    subscription_input: String,
+    feed_input: String,
+    feed_results: FeedResults,
+    fetching_feeds: bool,
+    /// Task the Logs window is narrowed to, `None` for everything.
+    log_task: Option<TaskId>,
+    log_query: String,
+    show_about: bool,
+    show_rules: bool,
+    show_encode_profiles: bool,
+    show_presets: bool,
+    /// Preset picked next to the Download button, `None` for the form's own choices.
+    form_preset: Option<String>,
+    /// Smoothed speed behind the time left in the downloads panel's summary line.
+    queue_speed: queue::SpeedAverage,
+    /// Combined speed over the last minutes, drawn in the footer.
+    bandwidth: BandwidthHistory,
+    /// Finished downloads a move rule is moving.
+    moving: HashSet<TaskId>,
+    move_results: MoveResults,
+    /// Filled in once the startup version probes finish.
+    diagnostics: Arc<Mutex<Option<Diagnostics>>>,
+    /// Title last sent to the window, so it is only changed when the text changes.
+    window_title: String,
+    /// Card that last copied something, for its "Copied!" notice.
+    copied: Option<(TaskId, Instant)>,
+    clipboard: ClipboardWatcher,
+    /// Copied link waiting for the user to confirm the download.
+    clipboard_prompt: Option<String>,
+    focus_paste: FocusPaste,
+    /// The URL field was filled from the clipboard; select its text on the next frame.
+    select_url: bool,
+    /// Copied links accepted from a notification while the window was out of sight.
+    clipboard_accepted: Arc<Mutex<Vec<String>>>,
+    /// Links from ytdlgui:// launches, queued on the next frame.
+    received_links: protocol::ReceivedLinks,
+    /// The window was hidden to the tray and hasn't had focus since.
+    in_tray: bool,
+    /// Session as last written to the session file.
+    saved_session: Session,
+    /// Tasks left over from the last run, until the user restores or discards them.
+    restore_prompt: Option<Session>,
+    /// "Pause all" is on: nothing new starts from the queue until "Resume all".
+    queue_paused: bool,
+    tag_editor: Option<TagEditor>,
+    /// Page of the setup wizard while it is open.
+    wizard: Option<WizardStep>,
+    tag_results: TagResults,
+    /// "Split into tracks" in the download form, for audio only videos with chapters.
+    split_tracks: bool,
+    gif_dialog: Option<GifDialog>,
+    gif_jobs: Vec<GifJob>,
+    next_gif_job: u64,
+    gif_results: GifResults,
+    /// "Start at" in the download form.
+    schedule_enabled: bool,
+    schedule_input: String,
+    /// "Download clip" in the download form, with the start and end typed so far.
+    clip_enabled: bool,
+    clip_start: String,
+    clip_end: String,
+    /// Scheduled tasks whose timer fired.
+    due_schedules: Arc<Mutex<Vec<TaskId>>>,
+    /// Scheduled task whose start time is being edited, with the text typed so far.
+    reschedule: Option<(TaskId, String)>,
+    /// Chosen in the form for the current batch, never saved.
+    after_queue: AfterQueue,
+    /// Set when a download starts, cleared once the batch finished and `after_queue` ran.
+    queue_armed: bool,
+    /// When `after_queue` runs, while its countdown dialog is open.
+    after_queue_at: Option<Instant>,
+    /// Number of active downloads shown in the quit confirmation, while it is open.
+    exit_prompt: Option<usize>,
+    exit_remember: bool,
+    /// Set once the user chose to quit, so the next close request goes through.
+    exit_confirmed: bool,
+    /// Recent folder that was picked but no longer exists, waiting for Remove / Keep.
+    missing_folder: Option<String>,
+    /// Whether the restored window placement was checked against the monitor yet.
+    window_checked: bool,
+    import_results: ImportResults,
+    importing: bool,
+    export_results: ExportResults,
+    exporting: bool,
+    import_summary: Option<ImportSummary>,
+    queue_import_results: QueueImportResults,
+    /// Folder being watched for link files, and the watcher's task.
+    folder_watch: Option<(PathBuf, AbortHandle)>,
+    watch_events: Arc<Mutex<Vec<WatchEvent>>>,
+    /// A large queue file waiting for the user to confirm it.
+    pending_queue_import: Option<QueueImport>,
+    queue_import_summary: Option<QueueImportSummary>,
+    /// Tasks whose output file already exists, waiting for Overwrite / Rename / Cancel.
+    conflicts: VecDeque<(TaskId, PathBuf)>,
+    preview: UrlPreview,
+    preview_results: PreviewResults,
+    /// Player found for `settings.player`, `None` if there is none to watch previews with.
+    player: Option<PathBuf>,
+    /// The video being watched without downloading it, if any.
+    playback: Option<player::Playback>,
+    conflict_apply_all: bool,
+    /// Answer from "Apply to all", used until no task is queued any more.
+    batch_conflict_choice: Option<ConflictChoice>,
+    /// Created on the first frame, once the event loop is running.
+    #[cfg(feature = "tray")]
+    tray: Option<tray::Tray>,
+    #[cfg(feature = "tray")]
+    tray_started: bool,
+    /// Created on the first frame, when the window handle is available.
+    #[cfg(windows)]
+    taskbar: Option<taskbar::Taskbar>,
+    #[cfg(windows)]
+    taskbar_started: bool,
+}
+
+/// Metadata for the URL currently in the input field, used for the size estimate.
+enum UrlPreview {
+    Empty,
+    /// Typed at this time, fetched once it stops changing.
+    Waiting(String, Instant),
+    Fetching(String),
+    Ready(String, Result<Box<VideoMetadata>, String>),
+}
+
+/// Answer to the file-exists prompt.
+#[derive(Clone, Copy)]
+enum ConflictChoice {
+    Overwrite,
+    Rename,
+    Cancel,
+}
+
+/// Something done to a task from its card, by a button or the context menu.
+#[derive(Clone, Copy)]
+enum CardAction {
+    CopyUrl,
+    CopyPath,
+    /// Copies the yt-dlp command line of the latest run.
+    CopyCommand,
+    OpenFolder,
+    /// Opens the folder with the file selected.
+    ShowInFolder,
+    OpenFile,
+    DeleteFile,
+    /// Opens the tag editor for a finished audio download.
+    EditTags,
+    /// Queues a scheduled task right away.
+    StartNow,
+    Reschedule,
+    ViewLog,
+    /// Starts again, continuing from the partial file (Retry and Resume).
+    Retry,
+    Redownload,
+    Pause,
+    Cancel,
+    Remove,
+    /// Swaps a queued task with the one queued before it.
+    MoveUp,
+    MoveDown,
+    /// Makes a queued task the next one to start.
+    MoveToTop,
+    /// Turns loudness normalization of an audio download on or off.
+    ToggleNormalize,
+    /// Turns splitting an audio download into chapter tracks on or off.
+    ToggleSplit,
+    /// Opens the "Convert to GIF…" form for a finished video.
+    ConvertGif,
+    /// Stops a GIF conversion, by job id.
+    CancelGif(u64),
+    /// Hides a finished conversion's row.
+    DismissGif(u64),
+}
+
+/// A finished download's file, from a card or a history row.
+#[derive(Clone)]
+struct FileToDelete {
+    title: String,
+    path: PathBuf,
+}
+
+/// Tasks removed together, kept until their Undo toast times out.
+struct Trashed {
+    toast: u64,
+    expires: Instant,
+    /// With the index each had in the list, to put them back in place.
+    tasks: Vec<(usize, DownloadTask)>,
+    /// Playlists that lost their last task with the removal.
+    playlists: Vec<Playlist>,
+}
+
+/// Pages of the setup wizard shown on first launch, in order.
+#[derive(Clone, Copy, PartialEq)]
+enum WizardStep {
+    Folder,
+    Quality,
+    Tools,
+    Integration,
+}
+
+impl WizardStep {
+    const ALL: [WizardStep; 4] = [WizardStep::Folder, WizardStep::Quality, WizardStep::Tools, WizardStep::Integration];
+
+    fn index(self) -> usize {
+        WizardStep::ALL.iter().position(|&s| s == self).unwrap_or_default()
+    }
+}
+
+/// The "Edit tags…" form of a finished audio download.
+struct TagEditor {
+    task: TaskId,
+    tags: Tags,
+    /// ffmpeg is rewriting the file; the form stays open until it reports back.
+    saving: bool,
+}
+
+/// The "Convert to GIF…" form of a finished video.
+struct GifDialog {
+    task: TaskId,
+    /// Start time as typed, seconds or m:ss.
+    start: String,
+    options: GifOptions,
+}
+
+/// A GIF being made from a task's file, shown as a row under its card.
+struct GifJob {
+    id: u64,
+    task: TaskId,
+    progress: watch::Receiver<f32>,
+    abort: AbortHandle,
+    /// The finished GIF, or why there is none; `None` while ffmpeg runs.
+    result: Option<Result<PathBuf, String>>,
+}
+
+/// What a playlist's tasks are created with as it is listed.
+struct PlaylistRequest {
+    quality: Quality,
+    folder: String,
+    start_at: Option<DateTime<Local>>,
+    preset: Option<Preset>,
+}
+
+enum PlaylistUpdate {
+    Entry(PlaylistEntry),
+    /// The listing ended, or why it failed.
+    Done(Result<(), String>),
+}
+
+/// State shared by the cards while the downloads list is drawn.
+struct ListPass {
+    actions: Vec<(TaskId, CardAction)>,
+    /// Video ids of the cards on screen (plus lookahead).
+    visible: HashSet<String>,
+    user_scrolling: bool,
+    compact: bool,
+    /// Card clicked outside its buttons, with the modifiers held.
+    clicked: Option<(TaskId, egui::Modifiers)>,
+    /// Place of each queued task in the queue, from 1.
+    queue: HashMap<TaskId, usize>,
+}
+
+/// A row of the downloads list: a playlist header or a task, indented under its
+/// playlist's header.
+enum ListRow {
+    Header(PlaylistId),
+    Task { index: usize, indented: bool },
+}
+
+/// Outcome of importing a URL list, shown in a dialog.
+struct ImportSummary {
+    queued: usize,
+    duplicates: usize,
+    /// Links to videos that were downloaded before at the same or a better quality.
+    downloaded: Vec<String>,
+    /// Line numbers that didn't hold a usable video URL.
+    invalid: Vec<usize>,
+}
+
+/// Outcome of "Import queue…": tasks queued and, per entry number, why others weren't.
+struct QueueImportSummary {
+    queued: usize,
+    errors: Vec<(usize, String)>,
+}
+
+#[derive(PartialEq)]
+enum View {
+    Downloads,
+    Search,
+    History,
+}
+
+/// The Search tab.
+#[derive(Default)]
+struct SearchState {
+    query: String,
+    /// Query the results are for, kept when the field is edited for "Load more".
+    shown_query: String,
+    results: Vec<SearchResult>,
+    /// The search running, aborted when the query is edited or another one starts.
+    running: Option<AbortHandle>,
+    /// Counts searches, so results of one that was replaced are dropped.
+    generation: u64,
+    error: Option<String>,
+    /// Thumbnails by video id, `None` if the fetch failed.
+    thumbnails: HashMap<String, Option<egui::TextureHandle>>,
+}
+
+impl SearchState {
+    /// Stops the running search, if any, and makes sure its result is ignored.
+    fn cancel(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.abort();
+        }
+        self.generation += 1;
+    }
+}
+
+/// Metadata prefetches, or the error yt-dlp gave.
+type MetadataResults = Arc<Mutex<Vec<(TaskId, Result<VideoMetadata, String>)>>>;
+/// Metadata fetched for the URL field, tagged with the URL it belongs to.
+type PreviewResults = Arc<Mutex<Option<(String, Result<VideoMetadata, String>)>>>;
+/// Hits of a search, tagged with its `SearchState::generation`.
+type SearchResults = Arc<Mutex<Vec<(u64, Result<Vec<SearchResult>, String>)>>>;
+/// Thumbnails of search hits by video id, `None` if the fetch failed.
+type SearchThumbnails = Arc<Mutex<Vec<(String, Option<ColorImage>)>>>;
+/// Finished checks by channel URL: the latest uploads, or `None` if offline.
+type SubscriptionResults = Arc<Mutex<Vec<(String, Option<Result<PlaylistMetadata, String>>)>>>;
+/// Feeds fetched for the inbox by URL, or why one couldn't be read.
+type FeedResults = Arc<Mutex<Vec<(String, Result<feed::Feed, String>)>>>;
+/// Lines read by "Import list…", or the error reading the file.
+type ImportResults = Arc<Mutex<Option<Result<Vec<(usize, String)>, String>>>>;
+/// `Mutex::lock` that carries on if a panic poisoned the lock. The result slots
+/// are only pushed to and drained, so their data is never left half-updated.
+trait LockExt<T> {
+    fn locked(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> LockExt<T> for Mutex<T> {
+    fn locked(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// What a background job that didn't finish hands its caller. A panic becomes
+/// "Internal error" and its message goes to the log.
+fn join_error_text(e: JoinError) -> String {
+    if !e.is_panic() {
+        return e.to_string();
+    }
+    let panic = e.into_panic();
+    let message = panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+    app_log::error(format!("Background job panicked: {}", message));
+    t!("error.internal")
+}
+
+/// Tasks read by "Import queue…", or why the file couldn't be used.
+type QueueImportResults = Arc<Mutex<Option<Result<QueueImport, String>>>>;
+/// File written by "Export history…", or why it couldn't be.
+type ExportResults = Arc<Mutex<Option<Result<PathBuf, String>>>>;
+/// Entries of playlists being listed, as yt-dlp prints them.
+type PlaylistResults = Arc<Mutex<Vec<(PlaylistId, PlaylistUpdate)>>>;
+/// New path of each file a move rule moved, or why it couldn't be moved.
+type MoveResults = Arc<Mutex<Vec<(TaskId, Result<PathBuf, String>)>>>;
+/// Outcome of the tag editor's ffmpeg run, or its error.
+type TagResults = Arc<Mutex<Option<Result<(), String>>>>;
+/// GIFs written by conversion jobs, by job id, or why they failed.
+type GifResults = Arc<Mutex<Vec<(u64, Result<PathBuf, String>)>>>;
+/// Per video_id bookkeeping so failed thumbnails are re-requested without hammering the endpoint.
+struct ThumbnailRetry {
+    in_flight: bool,
+    /// Whether the video is still on screen, read by the fetch job before it starts.
+    wanted: Arc<AtomicBool>,
+    failures: u32,
+    next_attempt: Instant,
 }
 
-impl Default for MyApp {
-    fn default() -> Self {
+impl MyApp {
+    fn new(settings: Settings, overrides: Overrides, runtime: Handle) -> Self {
+        if let Some(cache) = cache::THUMBNAIL_CACHE.as_ref() {
+            cache.set_max_bytes(settings.thumbnail_cache_mb * 1024 * 1024);
+        }
+        let unfinished = session::load();
+        let (events_tx, events) = unbounded_channel();
+        let player = player::find(&settings.player);
         Self {
+            runtime,
             url_input: String::new(),
-            download_folder: "./downloads".to_string(),
-            selected_quality: "720p".to_string(),
-            quality_options: vec![
-                "1080p".to_string(),
-                "720p".to_string(),
-                "480p".to_string(),
-                "360p".to_string(),
-                "Audio Only".to_string(),
-            ],
+            settings: settings.clone(),
+            language: settings.language.clone(),
+            applied_zoom: 1.0,
+            saved_settings: settings,
+            overrides,
+            settings_dirty_since: None,
             downloads: Vec::new(),
+            playlists: Vec::new(),
+            next_playlist_id: 0,
+            playlist_results: Arc::new(Mutex::new(Vec::new())),
+            loading_playlists: HashMap::new(),
             thumbnails: HashMap::new(),
-            thumbnail_results: Arc::new(Mutex::new(Vec::new())),
+            placeholder: None,
+            thumbnail_retries: HashMap::new(),
+            visible_tasks: HashSet::new(),
+            row_heights: HashMap::new(),
+            metadata_results: Arc::new(Mutex::new(Vec::new())),
+            metadata_pending: HashSet::new(),
+            next_task_id: 0,
             progress_rxs: HashMap::new(),
+            events,
+            events_tx,
+            cancel_txs: HashMap::new(),
+            history: History::load(),
+            session: SessionStats::default(),
+            history_query: String::new(),
+            search: SearchState::default(),
+            search_results: Arc::new(Mutex::new(Vec::new())),
+            search_thumbnails: Arc::new(Mutex::new(Vec::new())),
+            downloads_query: String::new(),
+            status_filter: StatusFilter::default(),
+            selected: None,
+            selection: HashSet::new(),
+            scroll_to_selected: false,
+            confirm_delete: None,
+            confirm_delete_file: None,
+            new_task: None,
+            reveal_new_task: false,
+            list_scrolled_at: None,
+            view: View::Downloads,
+            toasts: Toasts::default(),
+            trash: Vec::new(),
+            clear_failed_too: false,
+            confirm_clear: false,
+            show_debug: false,
+            frame_time: 0.0,
+            rows_built: 0,
+            frames: 0,
+            show_logs: false,
+            subscriptions: Subscriptions::load(),
+            subscription_results: Arc::new(Mutex::new(Vec::new())),
+            subscription_check: None,
+            next_subscription_check: Instant::now(),
+            show_subscriptions: false,
+            subscription_input: String::new(),
+            feed_input: String::new(),
+            feed_results: Arc::new(Mutex::new(Vec::new())),
+            fetching_feeds: false,
+            log_task: None,
+            log_query: String::new(),
+            show_about: false,
+            show_rules: false,
+            show_encode_profiles: false,
+            show_presets: false,
+            form_preset: None,
+            queue_speed: queue::SpeedAverage::default(),
+            bandwidth: BandwidthHistory::default(),
+            moving: HashSet::new(),
+            move_results: Arc::new(Mutex::new(Vec::new())),
+            diagnostics: Arc::new(Mutex::new(None)),
+            copied: None,
+            window_title: t!("app.title"),
+            clipboard: ClipboardWatcher::default(),
+            clipboard_prompt: None,
+            focus_paste: FocusPaste::default(),
+            select_url: false,
+            clipboard_accepted: Arc::new(Mutex::new(Vec::new())),
+            in_tray: false,
+            received_links: Arc::new(Mutex::new(Vec::new())),
+            missing_folder: None,
+            restore_prompt: (!unfinished.tasks.is_empty()).then(|| unfinished.clone()),
+            saved_session: unfinished,
+            queue_paused: false,
+            tag_editor: None,
+            wizard: None,
+            tag_results: Arc::new(Mutex::new(None)),
+            split_tracks: false,
+            gif_dialog: None,
+            gif_jobs: Vec::new(),
+            next_gif_job: 0,
+            gif_results: Arc::new(Mutex::new(Vec::new())),
+            schedule_enabled: false,
+            schedule_input: String::new(),
+            clip_enabled: false,
+            clip_start: String::new(),
+            clip_end: String::new(),
+            due_schedules: Arc::new(Mutex::new(Vec::new())),
+            reschedule: None,
+            after_queue: AfterQueue::default(),
+            queue_armed: false,
+            after_queue_at: None,
+            exit_prompt: None,
+            exit_remember: false,
+            exit_confirmed: false,
+            window_checked: false,
+            import_results: Arc::new(Mutex::new(None)),
+            importing: false,
+            export_results: Arc::new(Mutex::new(None)),
+            exporting: false,
+            import_summary: None,
+            queue_import_results: Arc::new(Mutex::new(None)),
+            folder_watch: None,
+            watch_events: Arc::new(Mutex::new(Vec::new())),
+            pending_queue_import: None,
+            queue_import_summary: None,
+            conflicts: VecDeque::new(),
+            preview: UrlPreview::Empty,
+            preview_results: Arc::new(Mutex::new(None)),
+            player,
+            playback: None,
+            conflict_apply_all: false,
+            batch_conflict_choice: None,
+            #[cfg(feature = "tray")]
+            tray: None,
+            #[cfg(feature = "tray")]
+            tray_started: false,
+            #[cfg(windows)]
+            taskbar: None,
+            #[cfg(windows)]
+            taskbar_started: false,
         }
     }
 }
 
-impl App for MyApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
-        for (id, rx) in self.progress_rxs.iter_mut() {
-            while let Ok(prog) = rx.try_recv() {
-                if let Some(task) = self.downloads.iter_mut().find(|t| &t.video_id == id) {
-                    // only increase, never go backwards
-                    if prog > task.progress {
-                        task.progress = prog;
-                        if task.progress >= 1.0 {
-                            task.status = DownloadStatus::Done;
-                        }
+impl MyApp {
+    /// Runs `job` on the runtime and hands its output to `done`, or an error if it
+    /// panicked or was aborted, so whatever waits for the result isn't left hanging.
+    fn spawn_caught<T: Send + 'static>(
+        &self,
+        job: impl Future<Output = T> + Send + 'static,
+        done: impl FnOnce(Result<T, String>) + Send + 'static,
+    ) -> AbortHandle {
+        let handle = self.runtime.spawn(job);
+        let abort = handle.abort_handle();
+        self.runtime.spawn(async move { done(handle.await.map_err(join_error_text)) });
+        abort
+    }
+
+    /// `spawn_caught` for blocking work, run on the blocking pool.
+    fn spawn_blocking_caught<T: Send + 'static>(
+        &self,
+        job: impl FnOnce() -> T + Send + 'static,
+        done: impl FnOnce(Result<T, String>) + Send + 'static,
+    ) {
+        let handle = self.runtime.spawn_blocking(job);
+        self.runtime.spawn(async move { done(handle.await.map_err(join_error_text)) });
+    }
+
+    /// Spawns a background thumbnail fetch for `video_id` unless one is already running.
+    fn request_thumbnail(&mut self, ctx: &egui::Context, video_id: &str) {
+        let Some(task) = self.downloads.iter().find(|t| t.video_id == video_id) else { return };
+        let (task_id, url) = (task.id, task.thumbnail_url.clone());
+        let retry = self.thumbnail_retries.entry(video_id.to_string()).or_insert(ThumbnailRetry {
+            in_flight: false,
+            wanted: Arc::new(AtomicBool::new(true)),
+            failures: 0,
+            next_attempt: Instant::now(),
+        });
+        if retry.in_flight {
+            return;
+        }
+        retry.in_flight = true;
+        retry.wanted.store(true, Ordering::Relaxed);
+        let wanted = Arc::clone(&retry.wanted);
+        self.set_thumbnail_state(video_id, ThumbnailState::Pending);
+
+        let id_c = video_id.to_string();
+        let events = self.events_tx.clone();
+        let ctx_c = ctx.clone();
+        let span = tracing::info_span!("thumbnail", task_id, video_id);
+        let fetch_id = id_c.clone();
+        self.spawn_blocking_caught(
+            move || {
+                let _span = span.enter();
+                // The row may have been scrolled far away while this job sat in the pool queue
+                if wanted.load(Ordering::Relaxed) {
+                    thumbnail::fetch_thumbnail_with_retry(&fetch_id, url.as_deref(), thumbnail::THUMBNAIL_SIZE)
+                } else {
+                    Err(ThumbnailError::Skipped)
+                }
+            },
+            move |image| {
+                let image = image.unwrap_or(Err(ThumbnailError::Internal));
+                let _ = events.send((task_id, DownloadEvent::Thumbnail { video_id: id_c, image }));
+                ctx_c.request_repaint();
+            },
+        );
+    }
+
+    /// Turns a fetched thumbnail into a texture, or schedules the next attempt.
+    fn apply_thumbnail(&mut self, ctx: &egui::Context, vid: String, image: Result<ColorImage, ThumbnailError>) {
+        // The task may have been removed while the fetch was running
+        if !self.downloads.iter().any(|t| t.video_id == vid) {
+            return;
+        }
+        match image {
+            Ok(img) => {
+                let tex = ctx.load_texture(&vid, img, TextureOptions::default());
+                self.thumbnails.insert(vid.clone(), tex);
+                self.thumbnail_retries.remove(&vid);
+                self.set_thumbnail_state(&vid, ThumbnailState::Loaded);
+            }
+            Err(ThumbnailError::Skipped) => {
+                self.thumbnail_retries.remove(&vid);
+            }
+            Err(e) => {
+                app_log::warn(format!("Thumbnail for {} failed: {}", vid, e));
+                self.set_thumbnail_state(&vid, ThumbnailState::Failed);
+                if let Some(retry) = self.thumbnail_retries.get_mut(&vid) {
+                    retry.in_flight = false;
+                    if e.is_retryable() {
+                        retry.failures += 1;
+                        retry.next_attempt = Instant::now() + THUMBNAIL_COOLDOWN * 2u32.pow(retry.failures - 1);
+                    } else {
+                        // Missing or broken thumbnails won't fix themselves
+                        retry.failures = THUMBNAIL_MAX_ROUNDS;
                     }
                 }
             }
         }
+    }
 
-        // Process fetched thumbnails
-        {
-            let mut pending = self.thumbnail_results.lock().unwrap();
-            for (vid, img) in pending.drain(..) {
-                let tex = ctx.load_texture(&vid, img, TextureOptions::default());
-                self.thumbnails.insert(vid, tex);
+    /// Creates a task for `url` and launches yt-dlp for it. Returns `None` if the URL isn't a video link.
+    fn start_download(&mut self, ctx: &egui::Context, url: String, quality: Quality, folder: String) -> Option<TaskId> {
+        let id = self.add_task(url.clone(), quality, folder)?;
+        // Started once the metadata tells us the file name to check for conflicts,
+        // reusing what the URL field already fetched when possible
+        match &self.preview {
+            UrlPreview::Ready(preview_url, Ok(meta)) if *preview_url == url => {
+                self.metadata_results.locked().push((id, Ok(meta.as_ref().clone())));
+                ctx.request_repaint();
             }
+            _ => self.request_metadata(ctx, id, &url),
         }
+        Some(id)
+    }
 
-        // Right-side download panel
-        egui::SidePanel::right("downloads_panel").show(ctx, |ui| {
-            ui.heading("Active Downloads");
-            ui.separator();
+    /// Adds a queued task for `url` that waits for its metadata before it starts.
+    fn add_task(&mut self, url: String, quality: Quality, folder: String) -> Option<TaskId> {
+        let video_id = extract_video_id(&url)?;
+        self.settings.remember_folder(&folder);
+        let id = self.next_task_id;
+        self.next_task_id += 1;
 
-            egui::ScrollArea::vertical()
-                .auto_shrink([false; 2])
-                .show(ui, |ui| {
-                    let mut to_remove = vec![];
+        let mut task = DownloadTask::new(id, video_id, url, quality, self.settings.audio_format, folder);
+        task.normalize = self.settings.normalize_audio;
+        if quality != Quality::AudioOnly {
+            task.encode = self.settings.selected_encode_profile().cloned();
+        }
+        if self.thumbnails.contains_key(&task.video_id) {
+            task.thumbnail = ThumbnailState::Loaded;
+        }
+        self.downloads.push(task);
+        Some(id)
+    }
 
-                    for task in &self.downloads {
-                        let status_text = match task.status {
-                            DownloadStatus::Downloading => "⬇️ Downloading",
-                            DownloadStatus::Done => "✅ Done",
-                        };
-                        ui.group(|ui| {
-                            ui.horizontal(|ui| {
-                                if let Some(tex) = self.thumbnails.get(&task.video_id) {
-                                    ui.image(tex);
-                                }
-                                ui.vertical(|ui| {
-                                    ui.label(&task.title);
-                                    ui.label(status_text);
-                                    ui.add(egui::ProgressBar::new(task.progress).show_percentage());
-                                    if matches!(task.status, DownloadStatus::Done) {
-                                        ui.horizontal(|ui| {
-                                            if ui.button("Open Folder").clicked() {
-                                                let folder = self.download_folder.clone();
-                                                std::thread::spawn(move || {
-                                                    #[cfg(target_os = "windows")]
-                                                    {
-                                                        let _ = std::process::Command::new("explorer").arg(folder).spawn();
-                                                    }
-                                                    #[cfg(target_os = "macos")]
-                                                    {
-                                                        let _ = std::process::Command::new("open").arg(folder).spawn();
-                                                    }
-                                                    #[cfg(all(unix, not(target_os = "macos")))]
-                                                    {
-                                                        let _ = std::process::Command::new("xdg-open").arg(folder).spawn();
-                                                    }
-                                                });
-                                            }
+    /// The preset picked next to the Download button, if it still exists.
+    fn form_preset(&self) -> Option<&Preset> {
+        self.form_preset.as_deref().and_then(|name| self.settings.preset(name))
+    }
 
-                                            // Remove Button
-                                            if ui.add(egui::Button::new("❌").fill(egui::Color32::RED)).clicked() {
-                                                to_remove.push(task.video_id.clone());
-                                            }
-                                        });
-                                    }
-                                });
-                            });
-                        });
-                    }
+    /// Gives a task just added the audio format and re-encoding of `preset` in place of
+    /// the form's, and names the preset on its card.
+    fn apply_preset(&mut self, id: TaskId, preset: &Preset) {
+        let encode = preset.encode_profile.as_deref().and_then(|name| self.settings.encode_profiles.iter().find(|p| p.name == name)).cloned();
+        let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { return };
+        task.audio_format = preset.audio_format;
+        task.encode = if task.quality == Quality::AudioOnly { None } else { encode };
+        task.preset = Some(preset.name.clone());
+    }
 
+    /// Starts yt-dlp for an existing task with a fresh progress channel.
+    fn launch_download(&mut self, ctx: &egui::Context, id: TaskId, resume: bool) {
+        let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { return };
+        self.queue_armed = true;
+        task.status = DownloadStatus::Downloading;
+        task.resume = false;
+        task.progress = 0.0;
+        task.attempts += 1;
+        task.file_missing = false;
+        task.transferred = None;
+        task.post_step = None;
+        task.warning = None;
+        task.tracks.clear();
+        let action = if resume { "Resuming" } else { "Starting" };
+        app_log::task_event(id, Level::Info, format!("{} {} ({}, attempt {})", action, task.url, task.quality.label(), task.attempts));
+        let span = tracing::info_span!("download", task_id = id, video_id = %task.video_id);
+        let job = DownloadJob {
+            url: task.url.clone(),
+            quality: task.quality,
+            audio_format: task.audio_format,
+            folder: task.folder.clone(),
+            resume,
+            overwrite: task.overwrite,
+            file_stem: task.file_stem.clone(),
+            clip: task.clip,
+            normalize: task.normalize,
+            keep_original: self.settings.keep_original_audio,
+            split: task.split_tracks.then(|| chapters::Album {
+                title: task.title.clone(),
+                artist: task.uploader.clone(),
+                chapters: task.chapters.clone(),
+            }),
+            encode: task.encode.clone(),
+        };
 
-                    if !to_remove.is_empty() {
-                        self.downloads.retain(|t| !to_remove.contains(&t.video_id));
-                        for id in to_remove {
-                            self.progress_rxs.remove(&id);
-                        }
-                    }
-                });
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        self.cancel_txs.insert(id, cancel_tx);
+        let (progress_tx, progress_rx) = watch::channel(Progress::default());
+        self.progress_rxs.insert(id, progress_rx);
+
+        // Launch yt-dlp download
+        let events = self.events_tx.clone();
+        let ctx_c = ctx.clone();
+        let events_c = events.clone();
+        self.spawn_caught(
+            spawn_download(id, job, progress_tx, events_c, cancel_rx).instrument(span),
+            move |result| {
+                let result = result.and_then(|r| r.map_err(|e| e.to_string()));
+                let _ = events.send((id, DownloadEvent::Finished(result)));
+                ctx_c.request_repaint();
+            },
+        );
+    }
+
+    /// Starts a queued task unless a file with its name already exists, in which case
+    /// the user is asked first (or the batch "Apply to all" answer is used).
+    fn check_and_start(&mut self, ctx: &egui::Context, id: TaskId, title: Option<&str>) {
+        let Some(task) = self.downloads.iter().find(|t| t.id == id) else { return };
+        if !matches!(task.status, DownloadStatus::Queued) {
+            return;
+        }
+        let existing = title.and_then(|title| {
+            let mut stem = downloader::sanitize_title(title);
+            if let Some(clip) = task.clip {
+                stem = format!("{} [{}]", stem, clip.file_suffix());
+            }
+            downloader::existing_output(Path::new(&task.folder), &stem)
         });
+        match (existing, self.batch_conflict_choice) {
+            (None, _) => self.launch_download(ctx, id, false),
+            (Some(_), Some(choice)) => self.resolve_conflict(ctx, id, choice),
+            (Some(path), None) => self.conflicts.push_back((id, path)),
+        }
+    }
 
-        // Main panel
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("YouTube Downloader");
+    fn resolve_conflict(&mut self, ctx: &egui::Context, id: TaskId, choice: ConflictChoice) {
+        let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { return };
+        match choice {
+            ConflictChoice::Overwrite => task.overwrite = true,
+            ConflictChoice::Rename => {
+                let stem = downloader::sanitize_title(&task.title);
+                task.file_stem = Some(downloader::free_stem(Path::new(&task.folder), &stem));
+            }
+            ConflictChoice::Cancel => {
+                self.remove_tasks(&[id]);
+                return;
+            }
+        }
+        self.launch_download(ctx, id, false);
+    }
 
-            ui.label("Paste YouTube video URL:");
-            ui.text_edit_singleline(&mut self.url_input);
+    /// Stops a running download; it turns Paused once yt-dlp has exited.
+    fn pause_task(&mut self, id: TaskId) {
+        if let Some(cancel) = self.cancel_txs.remove(&id) {
+            let _ = cancel.send(());
+        }
+    }
 
-            ui.horizontal(|ui| {
-                ui.label("Download folder:");
-                ui.text_edit_singleline(&mut self.download_folder);
-                if ui.button("Browse…").clicked() {
-                    if let Some(folder) =
-                        FileDialog::new().set_directory(&self.download_folder).pick_folder()
-                    {
-                        self.download_folder = folder.display().to_string();
+    /// Stops a download if it is running and drops its card.
+    fn cancel_task(&mut self, id: TaskId) {
+        self.pause_task(id);
+        self.trash_tasks(&[id]);
+    }
+
+    fn pause_all(&mut self) {
+        let running: Vec<TaskId> = self.cancel_txs.keys().copied().collect();
+        for id in running {
+            self.pause_task(id);
+        }
+    }
+
+    /// "Pause all": stops the running downloads and holds the queue. Single tasks can
+    /// still be resumed from their cards meanwhile.
+    fn hold_queue(&mut self) {
+        self.queue_paused = true;
+        self.pause_all();
+    }
+
+    /// "Resume all": releases the queue and puts every paused task back into it, so
+    /// they continue in list order within the parallel limit.
+    fn resume_all(&mut self) {
+        self.queue_paused = false;
+        for task in self.downloads.iter_mut().filter(|t| matches!(t.status, DownloadStatus::Paused)) {
+            task.status = DownloadStatus::Queued;
+            task.resume = true;
+        }
+    }
+
+    /// Handles tray menu commands, keeps the tooltip current and turns closing the
+    /// window into hiding it when close-to-tray is on.
+    #[cfg(feature = "tray")]
+    fn update_tray(&mut self, ctx: &egui::Context) {
+        if !self.tray_started {
+            self.tray_started = true;
+            self.tray = tray::Tray::new(ctx);
+        }
+        let Some(tray) = self.tray.as_mut() else { return };
+        tray.set_paused(self.queue_paused);
+        tray.set_downloading(self.cancel_txs.len());
+        let mut commands = Vec::new();
+        while let Some(cmd) = tray.poll() {
+            commands.push(cmd);
+        }
+        for cmd in commands {
+            match cmd {
+                tray::TrayCommand::PauseAll => self.hold_queue(),
+                tray::TrayCommand::ResumeAll => self.resume_all(),
+            }
+        }
+    }
+
+    #[cfg(feature = "tray")]
+    fn tray_available(&self) -> bool {
+        self.tray.is_some()
+    }
+
+    #[cfg(not(feature = "tray"))]
+    fn tray_available(&self) -> bool {
+        false
+    }
+
+    /// Decides what closing the window does: hiding it to the tray when close-to-tray
+    /// is on, quitting, or asking first while downloads are still active.
+    fn handle_close_request(&mut self, ctx: &egui::Context) {
+        if self.exit_confirmed || !ctx.input(|i| i.viewport().close_requested()) {
+            return;
+        }
+        #[cfg(feature = "tray")]
+        if self.settings.close_to_tray && self.tray.as_ref().is_some_and(|t| !t.quit_requested()) {
+            self.hide_to_tray(ctx);
+            return;
+        }
+        let active = self
+            .downloads
+            .iter()
+            .filter(|t| t.status.is_running() || matches!(t.status, DownloadStatus::Queued))
+            .count();
+        match self.settings.exit_with_active {
+            _ if active == 0 => {}
+            ExitAction::Quit => {}
+            ExitAction::KeepRunning if self.tray_available() => self.hide_to_tray(ctx),
+            ExitAction::Ask | ExitAction::KeepRunning => {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                // Quitting from the tray menu can happen while the window is hidden
+                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                self.exit_prompt = Some(active);
+            }
+        }
+    }
+
+    /// Keeps the app running with the window hidden; only used when there is a tray icon to bring it back.
+    fn hide_to_tray(&mut self, ctx: &egui::Context) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        self.in_tray = true;
+    }
+
+    /// Kills all yt-dlp processes before quitting, waiting briefly for them to exit.
+    fn shutdown_downloads(&mut self) {
+        let mut running = self.cancel_txs.len();
+        self.pause_all();
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while running > 0 && Instant::now() < deadline {
+            match self.events.try_recv() {
+                Ok((_, DownloadEvent::Finished(_))) => running -= 1,
+                Ok(_) => {}
+                Err(_) => std::thread::sleep(Duration::from_millis(20)),
+            }
+        }
+    }
+
+    /// Starts a download for the URL field with the current quality and folder, then clears it.
+    fn submit_url(&mut self, ctx: &egui::Context) {
+        let url = self.url_input.trim().to_string();
+        if self.queue_url(ctx, url) {
+            self.url_input.clear();
+        }
+    }
+
+    /// Length in whole seconds of the video `url` as the URL field's preview reports it.
+    fn preview_duration(&self, url: &str) -> Option<u64> {
+        match &self.preview {
+            UrlPreview::Ready(preview_url, Ok(meta)) if preview_url == url => meta.duration.map(|d| d.round() as u64),
+            _ => None,
+        }
+    }
+
+    /// The clip the download form asks for when downloading `url`: `None` without one,
+    /// `Some(None)` if the times typed aren't a part of the video.
+    fn form_clip(&self, url: &str) -> Option<Option<Clip>> {
+        if !self.clip_enabled {
+            return None;
+        }
+        let duration = self.preview_duration(url)?;
+        Some(Clip::parse(&self.clip_start, &self.clip_end, duration))
+    }
+
+    /// Queues a video or playlist link with the current quality, folder, start time and
+    /// clip, as if it was entered in the URL field. The picked preset, if any, replaces
+    /// the quality, format and folder. `false` if the start time or clip is invalid.
+    fn queue_url(&mut self, ctx: &egui::Context, url: String) -> bool {
+        let preset = self.form_preset().cloned();
+        let (quality, folder) = match &preset {
+            Some(preset) => (preset.quality, preset.folder.clone()),
+            None => (self.settings.quality, self.settings.download_folder.clone()),
+        };
+        let start_at = match self.schedule_enabled.then(|| schedule::parse_start_time(&self.schedule_input, Local::now())) {
+            None => None,
+            Some(Some(at)) => Some(at),
+            // Keep the URL so only the time needs fixing
+            Some(None) => {
+                self.toasts.error(t!("schedule.invalid"));
+                return false;
+            }
+        };
+        let clip = match self.form_clip(&url) {
+            None => None,
+            Some(Some(clip)) => Some(clip),
+            Some(None) => {
+                self.toasts.error(t!("clip.invalid"));
+                return false;
+            }
+        };
+        if extract_video_id(&url).is_none() && links::playlist_id(&url).is_some() {
+            self.request_playlist(ctx, url, PlaylistRequest { quality, folder, start_at, preset });
+        } else if !url.is_empty() {
+            match self.start_download(ctx, url, quality, folder) {
+                Some(id) => {
+                    if let Some(preset) = &preset {
+                        self.apply_preset(id, preset);
+                    }
+                    if let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) {
+                        task.split_tracks = self.split_tracks && quality == Quality::AudioOnly;
+                        task.clip = clip;
+                    }
+                    self.new_task = Some((id, Instant::now()));
+                    self.reveal_new_task = true;
+                    if let Some(at) = start_at {
+                        self.schedule_task(ctx, id, at);
                     }
                 }
+                None => self.toasts.error(t!("toast.invalid_url")),
+            }
+        }
+        true
+    }
+
+    /// Lists a playlist's videos in the background; `apply_playlist` queues them.
+    fn request_playlist(&mut self, ctx: &egui::Context, url: String, request: PlaylistRequest) {
+        self.toasts.info(t!("playlist.loading"));
+        let playlist = self.next_playlist_id;
+        self.next_playlist_id += 1;
+        self.loading_playlists.insert(playlist, request);
+        let results = Arc::clone(&self.playlist_results);
+        let entries = Arc::clone(&results);
+        let ctx = ctx.clone();
+        let ctx_c = ctx.clone();
+        self.spawn_caught(
+            async move {
+                metadata::stream_playlist(&url, |entry| {
+                    entries.locked().push((playlist, PlaylistUpdate::Entry(entry)));
+                    ctx_c.request_repaint();
+                })
+                .await
+                .map_err(|e| e.to_string())
+            },
+            move |listed| {
+                results.locked().push((playlist, PlaylistUpdate::Done(listed.and_then(|r| r))));
+                ctx.request_repaint();
+            },
+        );
+    }
+
+    /// Queues a video of a playlist as soon as the listing names it, grouped under the
+    /// playlist's title. Its full metadata is only fetched once its turn is near.
+    fn add_playlist_entry(&mut self, ctx: &egui::Context, playlist: PlaylistId, entry: PlaylistEntry) {
+        let Some(request) = self.loading_playlists.get(&playlist) else { return };
+        let (quality, folder, start_at, preset) = (request.quality, request.folder.clone(), request.start_at, request.preset.clone());
+        let Some(video_id) = entry.id else { return };
+        let Some(id) = self.add_task(watch_url(&video_id), quality, folder) else { return };
+        if let Some(preset) = &preset {
+            self.apply_preset(id, preset);
+        }
+        if let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) {
+            task.playlist = Some(playlist);
+            if let Some(title) = entry.title {
+                task.title = title;
+            }
+            task.duration = entry.duration.map(|d| d.round() as u64);
+            task.uploader = entry.uploader.or(entry.channel);
+        }
+        if !self.playlists.iter().any(|p| p.id == playlist) {
+            let title = entry.playlist_title.unwrap_or_else(|| t!("playlist.untitled"));
+            self.playlists.push(Playlist { id: playlist, title });
+        }
+        if let Some(at) = start_at {
+            self.schedule_task(ctx, id, at);
+        }
+    }
+
+    /// Holds a task back until `at`. A time that has already passed (say, restored from
+    /// a session) lets the task start right away instead.
+    fn schedule_task(&mut self, ctx: &egui::Context, id: TaskId, at: DateTime<Local>) {
+        let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { return };
+        if at <= Local::now() {
+            self.toasts.info(t!("schedule.passed", title = task.title));
+            self.start_scheduled(id);
+            return;
+        }
+        task.status = DownloadStatus::Scheduled(at);
+        app_log::task_event(id, Level::Info, format!("Scheduled for {}", at.format(schedule::DATE_TIME_FORMAT)));
+        let due = Arc::clone(&self.due_schedules);
+        let ctx = ctx.clone();
+        self.runtime.spawn(async move {
+            schedule::wait_until(at).await;
+            due.locked().push(id);
+            ctx.request_repaint();
+        });
+    }
+
+    /// Moves a scheduled task into the normal queue, where `start_queued` picks it up.
+    fn start_scheduled(&mut self, id: TaskId) {
+        let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { return };
+        if !matches!(task.status, DownloadStatus::Scheduled(_)) {
+            return;
+        }
+        task.status = DownloadStatus::Queued;
+    }
+
+    /// Starts queued tasks whose metadata is in, in queue order, while fewer downloads
+    /// than the parallel limit run. Tasks waiting on a file conflict answer are skipped.
+    fn start_queued(&mut self, ctx: &egui::Context) {
+        if self.queue_paused {
+            return;
+        }
+        let limit = self.settings.max_parallel as usize;
+        // Playlist entries get their formats and sizes looked up when they're next in line
+        let fetch: Vec<(TaskId, String)> = queue::awaiting_metadata(&self.downloads)
+            .take(METADATA_AHEAD.max(limit))
+            .filter(|t| !self.metadata_pending.contains(&t.id))
+            .map(|t| (t.id, t.url.clone()))
+            .collect();
+        for (id, url) in fetch {
+            self.request_metadata(ctx, id, &url);
+        }
+        let mut running = queue::running(&self.downloads);
+        let waiting: Vec<(TaskId, String, bool)> = queue::ready(&self.downloads)
+            .filter(|t| !self.conflicts.iter().any(|(id, _)| *id == t.id))
+            .map(|t| (t.id, t.title.clone(), t.resume))
+            .collect();
+        for (id, title, resume) in waiting {
+            if !queue::has_slot(running, limit) {
+                break;
+            }
+            // A partial file of its own is no conflict
+            if resume {
+                self.launch_download(ctx, id, true);
+            } else {
+                self.check_and_start(ctx, id, Some(&title));
+            }
+            if self.downloads.iter().any(|t| t.id == id && t.status.is_running()) {
+                running += 1;
+            }
+        }
+    }
+
+    /// "3 downloading, 5 queued — about 18 min left" for the top of the downloads panel,
+    /// `None` while nothing is running or queued. The time left goes by the averaged
+    /// speed, so it settles instead of changing with every progress line.
+    fn queue_summary(&mut self) -> Option<String> {
+        let downloading = queue::running(&self.downloads);
+        let queued = self.downloads.iter().filter(|t| matches!(t.status, DownloadStatus::Queued)).count();
+        if downloading == 0 {
+            self.queue_speed.reset();
+        }
+        if downloading + queued == 0 {
+            return None;
+        }
+        let counts = t!("queue.summary", downloading = downloading, queued = queued);
+        let speed = self.downloads.iter().filter(|t| t.status.is_running()).filter_map(|t| t.speed).reduce(|a, b| a + b);
+        let speed = self.queue_speed.sample(speed, Instant::now());
+        let left = match queue::estimate(queue::bytes_left(&self.downloads), speed) {
+            None => return Some(counts),
+            Some(estimate) if estimate.at_least => t!("queue.at_least", time = format_minutes(estimate.seconds)),
+            Some(estimate) if estimate.seconds < 60 => t!("queue.under_a_minute"),
+            Some(estimate) => t!("queue.about", time = format_minutes(estimate.seconds)),
+        };
+        Some(format!("{} — {}", counts, left))
+    }
+
+    /// Starts the countdown for the "When all downloads finish" action once every task
+    /// that ran is done or failed, and runs the action when it ends.
+    fn check_queue_finished(&mut self, ctx: &egui::Context) {
+        let finished = !self.downloads.is_empty()
+            && self.downloads.iter().all(|t| matches!(t.status, DownloadStatus::Done | DownloadStatus::Failed(_)));
+        if !finished || self.after_queue == AfterQueue::Nothing {
+            // New work (or "do nothing") called it off
+            self.after_queue_at = None;
+            return;
+        }
+        if self.queue_armed && self.after_queue_at.is_none() {
+            self.queue_armed = false;
+            self.after_queue_at = Some(Instant::now() + AFTER_QUEUE_COUNTDOWN);
+            // Bring the countdown up where it can be cancelled
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        let Some(at) = self.after_queue_at else { return };
+        let left = at.saturating_duration_since(Instant::now());
+        let mut run = left.is_zero();
+        egui::Window::new(t!("after.title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(t!("after.countdown", action = self.after_queue.label(), seconds = left.as_secs() + 1));
+                ui.horizontal(|ui| {
+                    if ui.button(t!("after.now")).clicked() {
+                        run = true;
+                    }
+                    if ui.button(t!("button.cancel")).clicked() {
+                        self.after_queue_at = None;
+                    }
+                });
             });
+        if !run || self.after_queue_at.is_none() {
+            return;
+        }
+        self.after_queue_at = None;
+        let action = self.after_queue;
+        app_log::info(format!("All downloads finished, running \"{}\"", action.label()));
+        let result = match action {
+            AfterQueue::Nothing | AfterQueue::Quit => Ok(()),
+            AfterQueue::Sleep => power::sleep(),
+            AfterQueue::Shutdown => power::shutdown(),
+        };
+        match result {
+            Err(e) => {
+                app_log::error(format!("\"{}\" failed: {}", action.label(), e));
+                self.toasts.error(t!("after.failed", error = e));
+            }
+            // Quitting saves the session and settings before the system goes down
+            Ok(()) if matches!(action, AfterQueue::Quit | AfterQueue::Shutdown) => {
+                self.exit_confirmed = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            Ok(()) => {}
+        }
+    }
 
-            ui.label("Select Video Quality:");
-            egui::ComboBox::from_label("")
-                .selected_text(&self.selected_quality)
-                .show_ui(ui, |ui| {
-                    for q in &self.quality_options {
-                        ui.selectable_value(&mut self.selected_quality, q.clone(), q);
+    fn reschedule_window(&mut self, ctx: &egui::Context) {
+        let Some((id, text)) = self.reschedule.as_mut() else { return };
+        let id = *id;
+        let parsed = schedule::parse_start_time(text, Local::now());
+        let mut answer = None;
+        egui::Window::new(t!("schedule.title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(t!("schedule.prompt"));
+                ui.text_edit_singleline(text);
+                if parsed.is_none() {
+                    ui.colored_label(ui.visuals().error_fg_color, t!("schedule.invalid"));
+                }
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(parsed.is_some(), egui::Button::new(t!("button.ok"))).clicked() {
+                        answer = Some(parsed);
+                    }
+                    if ui.button(t!("button.cancel")).clicked() {
+                        answer = Some(None);
                     }
                 });
+            });
+        if let Some(parsed) = answer {
+            self.reschedule = None;
+            if let Some(at) = parsed {
+                self.schedule_task(ctx, id, at);
+            }
+        }
+    }
+
+    /// Opens the video in the input field in the external player, closing any open one.
+    fn play_url(&mut self) {
+        self.playback = None;
+        let Some(player) = self.player.clone() else { return };
+        let url = self.url_input.trim().to_string();
+        let started = ytdlp_path().map_err(|e| e.to_string()).and_then(|ytdlp| {
+            player::Playback::start(&player, &ytdlp, &url).map_err(|e| e.to_string())
+        });
+        match started {
+            Ok(playback) => {
+                app_log::info(format!("Previewing {} in {}", url, player.display()));
+                self.playback = Some(playback);
+            }
+            Err(e) => self.toasts.error(t!("play.failed", error = e)),
+        }
+    }
 
-            if ui.button("Download").clicked() {
-                let url = self.url_input.trim().to_string();
-                let quality = self.selected_quality.clone();
-                let folder = self.download_folder.clone();
+    /// Forgets the playback once its player was closed.
+    fn update_playback(&mut self, ctx: &egui::Context) {
+        let Some(playback) = &mut self.playback else { return };
+        if playback.running() {
+            // Nothing else repaints when the player is closed
+            ctx.request_repaint_after(Duration::from_secs(1));
+        } else {
+            self.playback = None;
+        }
+    }
 
-                if let Some(video_id) = extract_video_id(&url) {
-                    let title = format!("Video ID: {}", video_id);
+    /// Fetches metadata for the URL field once typing has paused, for the size estimate.
+    fn update_preview(&mut self, ctx: &egui::Context) {
+        if let Some((url, meta)) = self.preview_results.locked().take()
+            && matches!(&self.preview, UrlPreview::Fetching(u) if *u == url)
+        {
+            self.preview = UrlPreview::Ready(url, meta.map(Box::new));
+        }
 
-                    self.downloads.push(DownloadTask {
-                        title: title.clone(),
-                        video_id: video_id.clone(),
-                        status: DownloadStatus::Downloading,
-                        progress: 0.0,
-                    });
+        let url = self.url_input.trim();
+        let current = match &self.preview {
+            UrlPreview::Empty => None,
+            UrlPreview::Waiting(u, _) | UrlPreview::Fetching(u) | UrlPreview::Ready(u, _) => Some(u.as_str()),
+        };
+        if current != Some(url) {
+            self.preview = match extract_video_id(url) {
+                Some(_) => UrlPreview::Waiting(url.to_string(), Instant::now()),
+                None => UrlPreview::Empty,
+            };
+        }
 
-                    // Spawn thumbnail fetcher
-                    {
-                        let id_c = video_id.clone();
-                        let results = Arc::clone(&self.thumbnail_results);
-                        let ctx_c = ctx.clone();
-                        RUNTIME
-                            .get()
-                            .unwrap()
-                            .spawn_blocking(move || {
-                                if let Some(img) = thumbnail::fetch_thumbnail(&id_c) {
-                                    results.lock().unwrap().push((id_c.clone(), img));
-                                    ctx_c.request_repaint();
-                                }
-                            });
-                    }
+        if let UrlPreview::Waiting(url, since) = &self.preview {
+            let waited = since.elapsed();
+            if waited < PREVIEW_DELAY {
+                ctx.request_repaint_after(PREVIEW_DELAY - waited);
+                return;
+            }
+            let url = url.clone();
+            self.preview = UrlPreview::Fetching(url.clone());
+            let results = Arc::clone(&self.preview_results);
+            let ctx_c = ctx.clone();
+            let fetch_url = url.clone();
+            self.spawn_caught(
+                async move { metadata::fetch_metadata(&fetch_url).await.map_err(|e| e.to_string()) },
+                move |meta| {
+                    *results.locked() = Some((url, meta.and_then(|m| m)));
+                    ctx_c.request_repaint();
+                },
+            );
+        }
+    }
 
-                    //Create a new progress channel per video_id
-                    let (tx, rx) = unbounded_channel();
-                    self.progress_rxs.insert(video_id.clone(), rx);
+    /// Reads a list of URLs in the background; `apply_import` queues them once it arrives.
+    fn import_list(&mut self, ctx: &egui::Context) {
+        let Some(path) = FileDialog::new().add_filter(&t!("import.file_filter"), &["txt"]).pick_file() else { return };
+        self.importing = true;
+        let results = Arc::clone(&self.import_results);
+        let ctx_c = ctx.clone();
+        self.spawn_blocking_caught(
+            move || {
+                std::fs::read_to_string(&path)
+                    .map(|text| links::numbered_urls(&text))
+                    .map_err(|e| t!("import.read_error", path = path.display(), error = e))
+            },
+            move |lines| {
+                *results.locked() = Some(lines.and_then(|l| l));
+                ctx_c.request_repaint();
+            },
+        );
+    }
 
-                    // Launch yt-dlp download
-                    RUNTIME
-                        .get()
-                        .unwrap()
-                        .spawn(spawn_download(
-                            url.clone(),
-                            quality.clone(),
-                            folder.clone(),
-                            tx,
-                        ));
-                }
+    /// Reads a queue file in the background; `apply_queue_import` queues its tasks.
+    fn import_queue(&mut self, ctx: &egui::Context) {
+        let Some(path) = FileDialog::new().add_filter("JSON", &["json"]).pick_file() else { return };
+        self.importing = true;
+        let results = Arc::clone(&self.queue_import_results);
+        let ctx_c = ctx.clone();
+        self.spawn_blocking_caught(
+            move || {
+                std::fs::read_to_string(&path)
+                    .map_err(|e| t!("import.read_error", path = path.display(), error = e))
+                    .and_then(|text| session::import_queue(&text))
+            },
+            move |import| {
+                *results.locked() = Some(import.and_then(|i| i));
+                ctx_c.request_repaint();
+            },
+        );
+    }
 
-                self.url_input.clear();
+    fn apply_queue_import(&mut self, ctx: &egui::Context, import: QueueImport) {
+        let mut summary = QueueImportSummary { queued: 0, errors: import.errors };
+        for (entry, imported) in import.tasks {
+            let quality = imported.quality.unwrap_or(self.settings.quality);
+            let folder = imported.folder.unwrap_or_else(|| self.settings.download_folder.clone());
+            let Some(id) = self.start_download(ctx, imported.url.clone(), quality, folder) else {
+                summary.errors.push((entry, t!("queue_import.bad_url", url = imported.url)));
+                continue;
+            };
+            let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { continue };
+            if let Some(title) = imported.title {
+                task.title = title;
             }
-        });
+            if let Some(format) = imported.audio_format {
+                task.audio_format = format;
+            }
+            task.overwrite = imported.overwrite;
+            task.file_stem = imported.file_stem;
+            summary.queued += 1;
+        }
+        summary.errors.sort_by_key(|&(entry, _)| entry);
+        self.queue_import_summary = Some(summary);
+    }
+
+    /// Writes the history to a CSV or JSON file in the background, then shows a toast.
+    fn export_history(&mut self, ctx: &egui::Context) {
+        let Some(path) = FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .add_filter("JSON", &["json"])
+            .set_file_name("history.csv")
+            .save_file()
+        else {
+            return;
+        };
+        self.exporting = true;
+        let entries = self.history.entries.clone();
+        let results = Arc::clone(&self.export_results);
+        let ctx_c = ctx.clone();
+        self.spawn_blocking_caught(
+            move || {
+                history::export(&entries, &path)
+                    .map(|()| path.clone())
+                    .map_err(|e| t!("export.failed", path = path.display(), error = e))
+            },
+            move |result| {
+                *results.locked() = Some(result.and_then(|r| r));
+                ctx_c.request_repaint();
+            },
+        );
+    }
 
-        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    fn apply_import(&mut self, ctx: &egui::Context, lines: Vec<(usize, String)>) {
+        let mut known: HashSet<String> = self.downloads.iter().map(|t| t.video_id.clone()).collect();
+        let mut summary = ImportSummary { queued: 0, duplicates: 0, downloaded: Vec::new(), invalid: Vec::new() };
+        for (line, url) in lines {
+            let Some(video_id) = extract_video_id(&url) else {
+                summary.invalid.push(line);
+                continue;
+            };
+            let quality = self.settings.quality;
+            if self.history.last_done(&video_id).is_some_and(|e| e.quality.covers(quality)) {
+                summary.downloaded.push(url);
+                continue;
+            }
+            if !known.insert(video_id) {
+                summary.duplicates += 1;
+                continue;
+            }
+            let folder = self.settings.download_folder.clone();
+            if self.start_download(ctx, url, quality, folder).is_some() {
+                summary.queued += 1;
+            } else {
+                summary.invalid.push(line);
+            }
+        }
+        self.import_summary = Some(summary);
     }
+
+    /// Queues every link found in files dropped onto the window.
+    ///
+    /// egui 0.27 only reports dropped files, so a link dragged straight from the
+    /// browser arrives as the `.url` shortcut the browser creates for it.
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let (hovering, dropped) = ctx.input(|i| (!i.raw.hovered_files.is_empty(), i.raw.dropped_files.clone()));
+        if hovering {
+            let rect = ctx.screen_rect();
+            let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_overlay")));
+            painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(180));
+            painter.rect_stroke(rect.shrink(8.0), 8.0, egui::Stroke::new(2.0, ctx.style().visuals.selection.bg_fill));
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                t!("drop.overlay"),
+                egui::FontId::proportional(24.0),
+                egui::Color32::WHITE,
+            );
+        }
+        if dropped.is_empty() {
+            return;
+        }
+
+        let mut urls = Vec::new();
+        let mut rejected = 0;
+        for file in dropped {
+            match file.path.as_deref().map(links::urls_from_file) {
+                Some(Ok(found)) => urls.extend(found),
+                _ => rejected += 1,
+            }
+        }
+        let mut queued = 0;
+        for url in urls {
+            let quality = self.settings.quality;
+            let folder = self.settings.download_folder.clone();
+            if self.start_download(ctx, url, quality, folder).is_some() {
+                queued += 1;
+            } else {
+                rejected += 1;
+            }
+        }
+        let summary = t!("drop.summary", queued = queued, rejected = rejected);
+        if queued == 0 {
+            self.toasts.error(summary);
+        } else {
+            self.toasts.info(summary);
+        }
+    }
+
+    /// Puts a copied YouTube link into the URL field when the window gains focus, if the
+    /// field is empty and the video isn't in the list already. Nothing starts by itself.
+    fn fill_url_on_focus(&mut self, ctx: &egui::Context) {
+        if !self.settings.fill_url_on_focus {
+            return;
+        }
+        let focused = ctx.input(|i| i.viewport().focused == Some(true));
+        let empty = self.url_input.trim().is_empty();
+        let downloads = &self.downloads;
+        let link = self.focus_paste.on_focus(focused, |url| {
+            let video_id = extract_video_id(url);
+            empty && !downloads.iter().any(|t| t.url == url || video_id.as_ref() == Some(&t.video_id))
+        });
+        if let Some(url) = link {
+            self.url_input = url;
+            self.select_url = true;
+        }
+    }
+
+    fn watch_clipboard(&mut self, ctx: &egui::Context) {
+        if !self.settings.watch_clipboard {
+            self.clipboard.stop();
+            self.clipboard_prompt = None;
+            return;
+        }
+        ctx.request_repaint_after(clipboard_watch::POLL_INTERVAL);
+        let accepted = std::mem::take(&mut *self.clipboard_accepted.locked());
+        for url in accepted {
+            if self.clipboard_prompt.as_ref() == Some(&url) {
+                self.clipboard_prompt = None;
+            }
+            let quality = self.settings.quality;
+            let folder = self.settings.download_folder.clone();
+            self.start_download(ctx, url, quality, folder);
+        }
+        if ctx.input(|i| i.viewport().focused == Some(true)) {
+            self.in_tray = false;
+        }
+
+        let Some(url) = self.clipboard.poll() else { return };
+        match self.settings.clipboard_action {
+            ClipboardAction::Prompt => {
+                // Nobody would see the prompt, so ask through the desktop instead. The
+                // prompt still waits in the window for when it comes back.
+                let out_of_sight = self.in_tray || ctx.input(|i| i.viewport().minimized == Some(true));
+                if out_of_sight && self.settings.notifications {
+                    if notifications::HAS_ACTIONS {
+                        let accepted = Arc::clone(&self.clipboard_accepted);
+                        let link = url.clone();
+                        notifications::ask(ctx, t!("notify.clipboard"), url.clone(), t!("button.download"), move || {
+                            accepted.locked().push(link);
+                        });
+                    } else {
+                        notifications::notify(ctx, t!("notify.clipboard"), t!("notify.clipboard_open", url = url));
+                    }
+                }
+                self.clipboard_prompt = Some(url);
+            }
+            ClipboardAction::AutoQueue => {
+                let quality = self.settings.quality;
+                let folder = self.settings.download_folder.clone();
+                if self.start_download(ctx, url, quality, folder).is_some() {
+                    self.toasts.info(t!("toast.clipboard_queued"));
+                }
+            }
+        }
+    }
+
+    /// Queues links that came in through the URL scheme and brings the window up to show them.
+    fn receive_links(&mut self, ctx: &egui::Context) {
+        let links = std::mem::take(&mut *self.received_links.locked());
+        if links.is_empty() {
+            return;
+        }
+        for url in links {
+            let quality = self.settings.quality;
+            let folder = self.settings.download_folder.clone();
+            if self.start_download(ctx, url, quality, folder).is_some() {
+                self.toasts.info(t!("toast.link_received"));
+            }
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+    }
+
+    /// Starts, moves or stops the folder watcher to follow the saved settings, so it
+    /// isn't restarted on every key typed into the folder field, and queues the links
+    /// of the files it picked up.
+    fn update_folder_watch(&mut self, ctx: &egui::Context) {
+        let folder = self.saved_settings.watch_folder.trim();
+        let wanted = (self.saved_settings.watch_folder_enabled && !folder.is_empty()).then(|| PathBuf::from(folder));
+        if self.folder_watch.as_ref().map(|(folder, _)| folder) != wanted.as_ref() {
+            if let Some((_, watch)) = self.folder_watch.take() {
+                watch.abort();
+            }
+            if let Some(folder) = wanted {
+                let events = Arc::clone(&self.watch_events);
+                let ctx = ctx.clone();
+                let watch = watch_folder::spawn(&self.runtime, folder.clone(), move |found| {
+                    events.locked().extend(found);
+                    ctx.request_repaint();
+                });
+                self.folder_watch = Some((folder, watch));
+            }
+        }
+        let events = std::mem::take(&mut *self.watch_events.locked());
+        for event in events {
+            match event {
+                WatchEvent::File { name, urls } => self.queue_watched(ctx, &name, urls),
+                WatchEvent::Error(e) => {
+                    app_log::error(format!("Watch folder: {}", e));
+                    self.toasts.error(e);
+                }
+            }
+        }
+    }
+
+    /// Queues the links of a file from the watch folder with the watch preset, or the
+    /// form's quality and folder without one, and reports how many were taken.
+    fn queue_watched(&mut self, ctx: &egui::Context, name: &str, urls: Vec<String>) {
+        let preset = self.settings.watch_preset.as_deref().and_then(|name| self.settings.preset(name)).cloned();
+        let (quality, folder) = match &preset {
+            Some(preset) => (preset.quality, preset.folder.clone()),
+            None => (self.settings.quality, self.settings.download_folder.clone()),
+        };
+        let (mut queued, mut rejected) = (0, 0);
+        for url in urls {
+            if extract_video_id(&url).is_none() && links::playlist_id(&url).is_some() {
+                let request = PlaylistRequest { quality, folder: folder.clone(), start_at: None, preset: preset.clone() };
+                self.request_playlist(ctx, url, request);
+                queued += 1;
+            } else if let Some(id) = self.start_download(ctx, url.clone(), quality, folder.clone()) {
+                if let Some(preset) = &preset {
+                    self.apply_preset(id, preset);
+                }
+                queued += 1;
+            } else {
+                app_log::warn(format!("Watch folder: {} in {} is not a YouTube link", url, name));
+                rejected += 1;
+            }
+        }
+        app_log::info(format!("Watch folder: queued {} and rejected {} links from {}", queued, rejected, name));
+        let summary = t!("watch.summary", file = name, queued = queued, rejected = rejected);
+        if queued == 0 || rejected > 0 {
+            self.toasts.error(summary);
+        } else {
+            self.toasts.info(summary);
+        }
+    }
+
+    /// Registers or unregisters the URL scheme after the checkbox was toggled, undoing
+    /// the toggle if the OS refused.
+    fn toggle_protocol_handler(&mut self) {
+        let result = if self.settings.protocol_handler { protocol::register() } else { protocol::unregister() };
+        if let Err(e) = result {
+            app_log::error(format!("Protocol handler: {}", e));
+            self.toasts.error(t!("toast.protocol_failed", error = e));
+            self.settings.protocol_handler = !self.settings.protocol_handler;
+        }
+    }
+
+    /// Opens a finished task's file with the default player, flagging the task if the file is gone.
+    fn play_file(&mut self, id: TaskId) {
+        let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { return };
+        match &task.file_path {
+            Some(path) if path.exists() => os_open::open_in_os(path),
+            _ => {
+                task.file_missing = true;
+                self.toasts.error(t!("toast.file_missing", title = task.title));
+            }
+        }
+    }
+
+    fn run_card_action(&mut self, ctx: &egui::Context, id: TaskId, action: CardAction) {
+        let Some(task) = self.downloads.iter().find(|t| t.id == id) else { return };
+        match action {
+            // The canonical link rather than whatever was pasted (youtu.be, tracking params, …)
+            CardAction::CopyUrl => {
+                ctx.output_mut(|o| o.copied_text = watch_url(&task.video_id));
+                self.copied = Some((id, Instant::now()));
+                ctx.request_repaint_after(COPIED_NOTICE);
+            }
+            CardAction::CopyPath => {
+                if let Some(path) = &task.file_path {
+                    ctx.output_mut(|o| o.copied_text = path.display().to_string());
+                    self.copied = Some((id, Instant::now()));
+                    ctx.request_repaint_after(COPIED_NOTICE);
+                }
+            }
+            CardAction::CopyCommand => {
+                if let Some(argv) = &task.command {
+                    ctx.output_mut(|o| o.copied_text = downloader::command_line(argv));
+                    self.copied = Some((id, Instant::now()));
+                    ctx.request_repaint_after(COPIED_NOTICE);
+                }
+            }
+            CardAction::OpenFolder => os_open::open_in_os(task.moved_to.as_ref().unwrap_or(&task.folder)),
+            CardAction::ShowInFolder => {
+                if let Some(path) = &task.file_path {
+                    os_open::reveal_in_os(path);
+                }
+            }
+            CardAction::OpenFile => self.play_file(id),
+            CardAction::DeleteFile => {
+                if let Some(path) = task.file_path.clone() {
+                    self.confirm_delete_file = Some(FileToDelete { title: task.title.clone(), path });
+                }
+            }
+            CardAction::ViewLog => {
+                self.show_logs = true;
+                self.log_task = Some(id);
+            }
+            CardAction::Retry => self.launch_download(ctx, id, true),
+            CardAction::Redownload => self.launch_download(ctx, id, false),
+            CardAction::Pause => self.pause_task(id),
+            CardAction::Cancel => self.cancel_task(id),
+            CardAction::Remove => self.trash_tasks(&[id]),
+            CardAction::StartNow => self.start_scheduled(id),
+            // The queue is the order of `downloads`, which the session file keeps too
+            CardAction::MoveUp => _ = queue::move_queued(&mut self.downloads, id, Move::Up),
+            CardAction::MoveDown => _ = queue::move_queued(&mut self.downloads, id, Move::Down),
+            CardAction::MoveToTop => _ = queue::move_queued(&mut self.downloads, id, Move::ToTop),
+            CardAction::Reschedule => {
+                if let DownloadStatus::Scheduled(at) = task.status {
+                    self.reschedule = Some((id, at.format(schedule::DATE_TIME_FORMAT).to_string()));
+                }
+            }
+            CardAction::EditTags => {
+                let tags = task.suggested_tags.clone().unwrap_or_else(|| {
+                    Tags::from_title(&task.title, task.uploader.as_deref(), task.upload_date.as_deref())
+                });
+                self.tag_editor = Some(TagEditor { task: id, tags, saving: false });
+            }
+            CardAction::ToggleNormalize => {
+                if let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) {
+                    task.normalize = !task.normalize;
+                }
+            }
+            CardAction::ToggleSplit => {
+                if let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) {
+                    task.split_tracks = !task.split_tracks;
+                }
+            }
+            CardAction::ConvertGif => {
+                // The whole video if it's shorter than the default clip
+                let mut options = GifOptions::default();
+                if let Some(duration) = task.duration {
+                    options.duration = options.duration.min(duration as f64);
+                }
+                self.gif_dialog = Some(GifDialog { task: id, start: "0:00".to_string(), options });
+            }
+            CardAction::CancelGif(job) | CardAction::DismissGif(job) => {
+                if let Some(at) = self.gif_jobs.iter().position(|j| j.id == job) {
+                    self.gif_jobs.remove(at).abort.abort();
+                }
+            }
+        }
+    }
+
+    /// Runs ffmpeg for the tag editor's form in the background.
+    fn save_tags(&mut self, ctx: &egui::Context) {
+        let Some(editor) = self.tag_editor.as_mut() else { return };
+        let Some(path) = self.downloads.iter().find(|t| t.id == editor.task).and_then(|t| t.file_path.clone()) else {
+            return;
+        };
+        editor.saving = true;
+        let tags = editor.tags.clone();
+        let results = Arc::clone(&self.tag_results);
+        let ctx = ctx.clone();
+        self.spawn_caught(
+            async move { tags::write_tags(&path, &tags).await.map_err(|e| e.to_string()) },
+            move |result| {
+                *results.locked() = Some(result.and_then(|r| r));
+                ctx.request_repaint();
+            },
+        );
+    }
+
+    fn tag_editor_window(&mut self, ctx: &egui::Context) {
+        if let Some(result) = self.tag_results.locked().take() {
+            match result {
+                Ok(()) => {
+                    self.tag_editor = None;
+                    self.toasts.info(t!("tags.saved"));
+                }
+                Err(e) => {
+                    app_log::error(format!("Writing tags failed: {}", e));
+                    self.toasts.error(t!("tags.failed", error = e));
+                    if let Some(editor) = self.tag_editor.as_mut() {
+                        editor.saving = false;
+                    }
+                }
+            }
+        }
+        let Some(editor) = self.tag_editor.as_mut() else { return };
+        let mut save = false;
+        let mut close = false;
+        egui::Window::new(t!("tags.title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(!editor.saving, |ui| {
+                    egui::Grid::new("tag_fields").num_columns(2).show(ui, |ui| {
+                        for (label, value) in [
+                            (t!("tags.field_title"), &mut editor.tags.title),
+                            (t!("tags.artist"), &mut editor.tags.artist),
+                            (t!("tags.album"), &mut editor.tags.album),
+                            (t!("tags.year"), &mut editor.tags.year),
+                        ] {
+                            ui.label(label);
+                            ui.text_edit_singleline(value);
+                            ui.end_row();
+                        }
+                    });
+                });
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!editor.saving, egui::Button::new(t!("button.save"))).clicked() {
+                        save = true;
+                    }
+                    if ui.add_enabled(!editor.saving, egui::Button::new(t!("button.cancel"))).clicked() {
+                        close = true;
+                    }
+                    if editor.saving {
+                        ui.spinner();
+                    }
+                });
+            });
+        if save {
+            self.save_tags(ctx);
+        } else if close {
+            self.tag_editor = None;
+        }
+    }
+
+    /// Starts converting a task's file to a GIF on the runtime, shown as a row under its card.
+    fn start_gif(&mut self, ctx: &egui::Context, id: TaskId, options: GifOptions) {
+        let Some(source) = self.downloads.iter().find(|t| t.id == id).and_then(|t| t.file_path.clone()) else {
+            return;
+        };
+        let job = self.next_gif_job;
+        self.next_gif_job += 1;
+        app_log::task_event(id, Level::Info, format!("Converting to GIF from {:.1} s for {:.1} s", options.start, options.duration));
+        let (progress_tx, progress) = watch::channel(0.0);
+        let results = Arc::clone(&self.gif_results);
+        let ctx = ctx.clone();
+        let abort = self.spawn_caught(
+            async move { gif::convert(&source, options, progress_tx).await.map_err(|e| e.to_string()) },
+            move |result| {
+                results.locked().push((job, result.and_then(|r| r)));
+                ctx.request_repaint();
+            },
+        );
+        self.gif_jobs.push(GifJob { id: job, task: id, progress, abort, result: None });
+    }
+
+    /// Applies the outcome of conversions started by `start_gif`. Cancelled jobs are
+    /// gone from `gif_jobs` already, so whatever they report is dropped.
+    fn receive_gifs(&mut self) {
+        let finished: Vec<_> = self.gif_results.locked().drain(..).collect();
+        for (id, result) in finished {
+            let Some(job) = self.gif_jobs.iter_mut().find(|j| j.id == id) else { continue };
+            match &result {
+                Ok(path) => app_log::task_event(job.task, Level::Info, format!("GIF written to {}", path.display())),
+                Err(e) => {
+                    app_log::task_event(job.task, Level::Error, format!("GIF conversion failed: {}", e));
+                    self.toasts.error(t!("gif.failed", error = e));
+                }
+            }
+            job.result = Some(result);
+        }
+    }
+
+    fn gif_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = self.gif_dialog.as_mut() else { return };
+        let Some(task) = self.downloads.iter().find(|t| t.id == dialog.task) else {
+            self.gif_dialog = None;
+            return;
+        };
+        let start = parse_timestamp(&dialog.start);
+        // A start past the end would make an empty GIF
+        let start_valid = start.is_some_and(|s| task.duration.is_none_or(|d| s < d as f64));
+        let mut convert = false;
+        let mut close = false;
+        egui::Window::new(t!("gif.title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.add(egui::Label::new(&task.title).truncate(true));
+                ui.add_space(4.0);
+                egui::Grid::new("gif_fields").num_columns(2).show(ui, |ui| {
+                    ui.label(t!("gif.start"));
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut dialog.start).desired_width(80.0).hint_text("1:05"));
+                        if !start_valid {
+                            ui.colored_label(ui.visuals().error_fg_color, t!("gif.bad_start"));
+                        }
+                    });
+                    ui.end_row();
+                    ui.label(t!("gif.duration"));
+                    ui.add(egui::DragValue::new(&mut dialog.options.duration).clamp_range(0.5..=60.0).speed(0.1).suffix(" s"));
+                    ui.end_row();
+                    ui.label(t!("gif.width"));
+                    ui.add(egui::DragValue::new(&mut dialog.options.width).clamp_range(64..=1920).speed(4).suffix(" px"));
+                    ui.end_row();
+                    ui.label(t!("gif.fps"));
+                    ui.add(egui::DragValue::new(&mut dialog.options.fps).clamp_range(1..=30));
+                    ui.end_row();
+                });
+                ui.weak(t!("gif.hint"));
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(start_valid, egui::Button::new(t!("gif.convert"))).clicked() {
+                        convert = true;
+                    }
+                    if ui.button(t!("button.cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+        if convert && let Some(start) = start {
+            let id = dialog.task;
+            let options = GifOptions { start, ..dialog.options };
+            self.gif_dialog = None;
+            self.start_gif(ctx, id, options);
+        } else if close {
+            self.gif_dialog = None;
+        }
+    }
+
+    /// Whether the startup probe found no ffmpeg; false while it is still running.
+    fn ffmpeg_missing(&self) -> bool {
+        self.diagnostics.locked().as_ref().is_some_and(|d| d.ffmpeg_version.is_none())
+    }
+
+    /// Walks through the download folder, quality, the tools downloads need and the
+    /// desktop integration. Finishing or skipping writes the settings file, so it only
+    /// shows up by itself on the first launch.
+    fn setup_wizard(&mut self, ctx: &egui::Context) {
+        let Some(step) = self.wizard else { return };
+        let mut next_step = Some(step);
+        let mut done = false;
+        egui::Window::new(t!("wizard.title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.weak(t!("wizard.step", step = step.index() + 1, total = WizardStep::ALL.len()));
+                ui.add_space(4.0);
+                match step {
+                    WizardStep::Folder => {
+                        ui.label(t!("wizard.folder"));
+                        ui.horizontal(|ui| overrides::locked_ui(ui, self.overrides.folder.as_ref(), |ui| {
+                            ui.text_edit_singleline(&mut self.settings.download_folder);
+                            if ui.button(t!("button.browse")).clicked()
+                                && let Some(folder) =
+                                    FileDialog::new().set_directory(&self.settings.download_folder).pick_folder()
+                            {
+                                self.settings.download_folder = folder.display().to_string();
+                            }
+                        }));
+                    }
+                    WizardStep::Quality => {
+                        ui.label(t!("wizard.quality"));
+                        overrides::locked_ui(ui, self.overrides.quality.as_ref(), |ui| {
+                            egui::ComboBox::from_id_source("wizard_quality")
+                                .selected_text(self.settings.quality.display_name())
+                                .show_ui(ui, |ui| {
+                                    for q in Quality::ALL {
+                                        ui.selectable_value(&mut self.settings.quality, q, q.display_name());
+                                    }
+                                });
+                        });
+                        if self.settings.quality == Quality::AudioOnly {
+                            egui::ComboBox::from_id_source("wizard_audio_format")
+                                .selected_text(self.settings.audio_format.describe())
+                                .show_ui(ui, |ui| {
+                                    for f in AudioFormat::ALL {
+                                        ui.selectable_value(&mut self.settings.audio_format, f, f.describe());
+                                    }
+                                });
+                        }
+                    }
+                    WizardStep::Tools => {
+                        ui.label(t!("wizard.tools"));
+                        let diagnostics = self.diagnostics.locked().clone();
+                        match diagnostics {
+                            None => {
+                                ui.horizontal(|ui| {
+                                    ui.spinner();
+                                    ui.label(t!("about.probing"));
+                                });
+                            }
+                            Some(diagnostics) => {
+                                egui::Grid::new("wizard_tools").num_columns(2).show(ui, |ui| {
+                                    for (name, version, url) in [
+                                        ("yt-dlp", &diagnostics.ytdlp_version, YTDLP_INSTALL_URL),
+                                        ("ffmpeg", &diagnostics.ffmpeg_version, FFMPEG_DOWNLOAD_URL),
+                                    ] {
+                                        ui.label(name);
+                                        ui.horizontal(|ui| match version {
+                                            Some(version) => {
+                                                ui.label(format!("✅ {}", version));
+                                            }
+                                            None => {
+                                                ui.colored_label(ui.visuals().warn_fg_color, t!("about.not_found"));
+                                                ui.hyperlink_to(t!("wizard.get_tool"), url);
+                                            }
+                                        });
+                                        ui.end_row();
+                                    }
+                                });
+                                if diagnostics.ffmpeg_version.is_none() {
+                                    ui.label(t!("wizard.ffmpeg_needed"));
+                                }
+                                if ui.button(t!("wizard.check_again")).clicked() {
+                                    *self.diagnostics.locked() = None;
+                                    self.probe_versions(ctx);
+                                }
+                            }
+                        }
+                    }
+                    WizardStep::Integration => {
+                        ui.checkbox(&mut self.settings.watch_clipboard, t!("settings.watch_clipboard"));
+                        ui.checkbox(&mut self.settings.notifications, t!("settings.notify"));
+                    }
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(t!("wizard.skip")).clicked() {
+                        done = true;
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let index = step.index();
+                        match WizardStep::ALL.get(index + 1) {
+                            Some(&next) => {
+                                if ui.button(t!("wizard.next")).clicked() {
+                                    next_step = Some(next);
+                                }
+                            }
+                            None => {
+                                if ui.button(t!("wizard.finish")).clicked() {
+                                    done = true;
+                                }
+                            }
+                        }
+                        if index > 0 && ui.button(t!("wizard.back")).clicked() {
+                            next_step = Some(WizardStep::ALL[index - 1]);
+                        }
+                    });
+                });
+            });
+        self.wizard = next_step;
+        if done {
+            self.wizard = None;
+            // Written even when nothing changed, which is what marks the wizard as seen
+            if let Err(e) = self.overrides.saved_form(&self.settings).save() {
+                app_log::error(format!("Failed to save settings: {}", e));
+            }
+            self.saved_settings = self.settings.clone();
+        }
+    }
+
+    /// Looks up the yt-dlp and ffmpeg versions in the background for the About dialog.
+    fn probe_versions(&self, ctx: &egui::Context) {
+        let results = Arc::clone(&self.diagnostics);
+        let ctx = ctx.clone();
+        self.runtime.spawn(async move {
+            let diagnostics = Diagnostics::probe().await;
+            *results.locked() = Some(diagnostics);
+            ctx.request_repaint();
+        });
+    }
+
+    /// Starts the next subscription check that is due, and handles finished ones.
+    fn update_subscriptions(&mut self, ctx: &egui::Context) {
+        let finished: Vec<_> = self.subscription_results.locked().drain(..).collect();
+        for (url, listing) in finished {
+            self.subscription_check = None;
+            let Some(listing) = listing else {
+                app_log::info("Offline, checking subscriptions again later");
+                self.next_subscription_check = Instant::now() + OFFLINE_RETRY;
+                continue;
+            };
+            if let Err(e) = &listing {
+                app_log::warn(format!("Checking {} for new uploads failed: {}", url, e));
+            }
+            let new = self.subscriptions.checked(&url, listing, history::now_unix());
+            self.receive_uploads(ctx, &url, new);
+            self.subscriptions.save();
+        }
+        let feeds: Vec<_> = self.feed_results.locked().drain(..).collect();
+        if !feeds.is_empty() {
+            self.fetching_feeds = false;
+            for (url, feed) in feeds {
+                match feed {
+                    Ok(feed) => self.receive_feed(&url, feed),
+                    Err(e) => {
+                        app_log::warn(format!("Reading feed {} failed: {}", url, e));
+                        self.toasts.error(t!("feed.failed", url = url, error = e));
+                    }
+                }
+            }
+            self.subscriptions.save();
+        }
+
+        if self.subscriptions.channels.is_empty() {
+            return;
+        }
+        let interval = Duration::from_secs(self.settings.subscription_interval_min as u64 * 60);
+        if self.subscription_check.is_none()
+            && Instant::now() >= self.next_subscription_check
+            && let Some(index) = self.subscriptions.due(interval, history::now_unix())
+        {
+            self.next_subscription_check = Instant::now() + SUBSCRIPTION_SPACING;
+            self.check_subscription(ctx, self.subscriptions.channels[index].url.clone());
+        }
+        // Nothing else repaints an idle window
+        ctx.request_repaint_after(SUBSCRIPTION_SPACING);
+    }
+
+    /// Lists the latest uploads of the channel at `url`, unless the machine is offline.
+    fn check_subscription(&mut self, ctx: &egui::Context, url: String) {
+        self.subscription_check = Some(url.clone());
+        let results = Arc::clone(&self.subscription_results);
+        let ctx = ctx.clone();
+        let span = tracing::info_span!("subscription", url = %url);
+        let channel = url.clone();
+        self.spawn_caught(
+            async move {
+                if !tokio::task::spawn_blocking(subscriptions::online).await.unwrap_or(false) {
+                    return None;
+                }
+                Some(metadata::fetch_playlist_head(&channel, SUBSCRIPTION_WINDOW).await.map_err(|e| e.to_string()))
+            }
+            .instrument(span),
+            move |listing| {
+                // A panic counts as a failed check
+                results.locked().push((url, listing.unwrap_or_else(|e| Some(Err(e)))));
+                ctx.request_repaint();
+            },
+        );
+    }
+
+    /// Queues the new uploads of a channel set to auto-download, or puts them in the inbox.
+    fn receive_uploads(&mut self, ctx: &egui::Context, url: &str, new: Vec<NewVideo>) {
+        let Some(sub) = self.subscriptions.channels.iter().find(|s| s.url == url) else { return };
+        if new.is_empty() {
+            return;
+        }
+        let (channel, count) = (sub.title.clone(), new.len());
+        if sub.auto_download {
+            for video in &new {
+                self.download_new_video(ctx, video);
+            }
+            self.toasts.info(t!("subscriptions.queued", count = count, channel = channel));
+        } else {
+            self.subscriptions.inbox.extend(new);
+            self.toasts.info(t!("subscriptions.new_videos", count = count, channel = channel));
+        }
+    }
+
+    /// Fetches the feeds at `urls` in the background, one after the other, for the inbox.
+    fn fetch_feeds(&mut self, ctx: &egui::Context, urls: Vec<String>) {
+        self.fetching_feeds = true;
+        let results = Arc::clone(&self.feed_results);
+        let ctx = ctx.clone();
+        self.spawn_blocking_caught(
+            move || {
+                urls.into_iter()
+                    .map(|url| {
+                        let feed = feed::fetch_feed(&url).map_err(|e| e.to_string());
+                        (url, feed)
+                    })
+                    .collect::<Vec<_>>()
+            },
+            move |fetched| {
+                let fetched = fetched.unwrap_or_else(|e| vec![(String::new(), Err(e))]);
+                results.locked().extend(fetched);
+                ctx.request_repaint();
+            },
+        );
+    }
+
+    /// Asks for an OPML file and fetches every feed in it.
+    fn import_opml(&mut self, ctx: &egui::Context) {
+        let Some(path) = FileDialog::new().add_filter("OPML", &["opml", "xml"]).pick_file() else { return };
+        let urls = std::fs::read_to_string(&path)
+            .map_err(|e| t!("import.read_error", path = path.display(), error = e))
+            .and_then(|text| feed::parse_opml(&text).map_err(|e| e.to_string()));
+        match urls {
+            Ok(urls) if urls.is_empty() => self.toasts.info(t!("feed.opml_empty")),
+            Ok(urls) => self.fetch_feeds(ctx, urls),
+            Err(e) => self.toasts.error(e),
+        }
+    }
+
+    /// Adds the entries of a fetched feed to the inbox, leaving out ones already there.
+    fn receive_feed(&mut self, url: &str, feed: feed::Feed) {
+        let inbox = &mut self.subscriptions.inbox;
+        let before = inbox.len();
+        // Oldest first, as checks add them
+        for entry in feed.entries.into_iter().rev() {
+            if !inbox.iter().any(|v| v.video_id == entry.video_id) {
+                inbox.push(NewVideo { video_id: entry.video_id, title: entry.title, channel_url: url.to_string() });
+            }
+        }
+        let title = feed.title.unwrap_or_else(|| url.to_string());
+        self.toasts.info(t!("feed.added", count = inbox.len() - before, feed = title));
+    }
+
+    /// Queues an upload with its subscription's quality and folder, or the current ones.
+    fn download_new_video(&mut self, ctx: &egui::Context, video: &NewVideo) {
+        let sub = self.subscriptions.channels.iter().find(|s| s.url == video.channel_url);
+        let quality = sub.and_then(|s| s.quality).unwrap_or(self.settings.quality);
+        let folder = sub.and_then(|s| s.folder.clone()).unwrap_or_else(|| self.settings.download_folder.clone());
+        self.start_download(ctx, watch_url(&video.video_id), quality, folder);
+    }
+
+    fn subscriptions_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_subscriptions;
+        let mut changed = false;
+        let mut remove = None;
+        let mut download = Vec::new();
+        let mut dismiss = Vec::new();
+        egui::Window::new(t!("subscriptions.title")).open(&mut open).default_size([520.0, 420.0]).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let field = ui.add(
+                    egui::TextEdit::singleline(&mut self.subscription_input).hint_text("https://www.youtube.com/@channel"),
+                );
+                let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button(t!("subscriptions.add")).clicked() || submitted {
+                    match links::channel_uploads_url(&self.subscription_input) {
+                        Some(url) if self.subscriptions.channels.iter().any(|s| s.url == url) => {
+                            self.toasts.info(t!("subscriptions.already"));
+                        }
+                        Some(url) => {
+                            self.subscriptions.channels.push(Subscription::new(url));
+                            self.subscription_input.clear();
+                            changed = true;
+                        }
+                        None => self.toasts.error(t!("subscriptions.bad_url")),
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                let field = ui.add(
+                    egui::TextEdit::singleline(&mut self.feed_input)
+                        .hint_text("https://www.youtube.com/feeds/videos.xml?channel_id=…"),
+                );
+                let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                ui.add_enabled_ui(!self.fetching_feeds, |ui| {
+                    if ui.button(t!("feed.load")).on_hover_text(t!("feed.load_hint")).clicked() || submitted {
+                        let url = self.feed_input.trim().to_string();
+                        if feed::is_feed_url(&url) {
+                            self.feed_input.clear();
+                            self.fetch_feeds(ctx, vec![url]);
+                        } else {
+                            self.toasts.error(t!("feed.bad_url"));
+                        }
+                    }
+                    if ui.button(t!("feed.import_opml")).clicked() {
+                        self.import_opml(ctx);
+                    }
+                });
+                if self.fetching_feeds {
+                    ui.spinner();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("subscriptions.interval"));
+                ui.add(
+                    egui::DragValue::new(&mut self.settings.subscription_interval_min)
+                        .clamp_range(SUBSCRIPTION_INTERVAL_RANGE)
+                        .suffix(" min"),
+                );
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().id_source("subscriptions").max_height(220.0).show(ui, |ui| {
+                if self.subscriptions.channels.is_empty() {
+                    ui.weak(t!("subscriptions.none"));
+                }
+                for (i, sub) in self.subscriptions.channels.iter_mut().enumerate() {
+                    ui.group(|ui| {
+                        elide::label(ui, &sub.title, egui::TextStyle::Body, Elide::End);
+                        let checked = match sub.last_checked.and_then(|at| DateTime::from_timestamp(at, 0)) {
+                            Some(at) => at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+                            None => t!("subscriptions.never"),
+                        };
+                        let checking = self.subscription_check.as_deref() == Some(sub.url.as_str());
+                        ui.horizontal(|ui| {
+                            ui.small(t!("subscriptions.last_checked", time = checked));
+                            if checking {
+                                ui.spinner();
+                            }
+                        });
+                        if let Some(e) = &sub.last_error {
+                            ui.colored_label(ui.visuals().error_fg_color, e);
+                        }
+                        ui.horizontal(|ui| {
+                            changed |= ui.checkbox(&mut sub.auto_download, t!("subscriptions.auto")).changed();
+                            let default = t!("subscriptions.default");
+                            egui::ComboBox::from_id_source(("subscription_quality", i))
+                                .selected_text(sub.quality.map_or(default.clone(), |q| q.display_name()))
+                                .show_ui(ui, |ui| {
+                                    changed |= ui.selectable_value(&mut sub.quality, None, default).changed();
+                                    for q in Quality::ALL {
+                                        changed |= ui.selectable_value(&mut sub.quality, Some(q), q.display_name()).changed();
+                                    }
+                                });
+                            let mut folder = sub.folder.clone().unwrap_or_default();
+                            let edit = ui.add(
+                                egui::TextEdit::singleline(&mut folder)
+                                    .desired_width(140.0)
+                                    .hint_text(t!("subscriptions.default_folder")),
+                            );
+                            if edit.changed() {
+                                sub.folder = (!folder.trim().is_empty()).then_some(folder);
+                                changed = true;
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(!checking, egui::Button::new(t!("subscriptions.check_now"))).clicked() {
+                                sub.last_checked = None;
+                                self.next_subscription_check = Instant::now();
+                            }
+                            if ui.button(t!("subscriptions.remove")).clicked() {
+                                remove = Some(i);
+                            }
+                        });
+                    });
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.strong(t!("subscriptions.inbox"));
+                if !self.subscriptions.inbox.is_empty() {
+                    if ui.small_button(t!("subscriptions.download_all")).clicked() {
+                        download.extend(0..self.subscriptions.inbox.len());
+                    }
+                    if ui.small_button(t!("subscriptions.clear")).clicked() {
+                        dismiss.extend(0..self.subscriptions.inbox.len());
+                    }
+                }
+            });
+            egui::ScrollArea::vertical().id_source("inbox").show(ui, |ui| {
+                if self.subscriptions.inbox.is_empty() {
+                    ui.weak(t!("subscriptions.inbox_empty"));
+                }
+                for (i, video) in self.subscriptions.inbox.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button(t!("button.download")).clicked() {
+                            download.push(i);
+                        }
+                        if ui.small_button("✖").on_hover_text(t!("button.dismiss")).clicked() {
+                            dismiss.push(i);
+                        }
+                        if self.history.last_done(&video.video_id).is_some() {
+                            ui.weak("✔").on_hover_text(t!("feed.in_history"));
+                        }
+                        elide::label(ui, &video.title, egui::TextStyle::Body, Elide::End);
+                    });
+                }
+            });
+        });
+        self.show_subscriptions = open;
+
+        for &i in &download {
+            let video = self.subscriptions.inbox[i].clone();
+            self.download_new_video(ctx, &video);
+        }
+        if !download.is_empty() || !dismiss.is_empty() {
+            let mut index = 0;
+            self.subscriptions.inbox.retain(|_| {
+                let keep = !download.contains(&index) && !dismiss.contains(&index);
+                index += 1;
+                keep
+            });
+            changed = true;
+        }
+        if let Some(i) = remove {
+            let url = self.subscriptions.channels.remove(i).url;
+            self.subscriptions.inbox.retain(|v| v.channel_url != url);
+            changed = true;
+        }
+        if changed {
+            self.subscriptions.save();
+        }
+    }
+
+    /// Lists the move rules for editing. Changes are saved like any other setting.
+    fn rules_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_rules;
+        let mut swap = None;
+        let mut remove = None;
+        egui::Window::new(t!("rules.title")).open(&mut open).default_size([560.0, 300.0]).show(ctx, |ui| {
+            ui.label(t!("rules.hint"));
+            let count = self.settings.move_rules.len();
+            for (i, rule) in self.settings.move_rules.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let text = match &rule.condition {
+                        Condition::Quality(_) => String::new(),
+                        Condition::UploaderContains(t) | Condition::TitleContains(t) => t.clone(),
+                    };
+                    let kinds = [
+                        (t!("rules.if_quality"), Condition::Quality(Quality::AudioOnly)),
+                        (t!("rules.if_uploader"), Condition::UploaderContains(text.clone())),
+                        (t!("rules.if_title"), Condition::TitleContains(text)),
+                    ];
+                    let current = std::mem::discriminant(&rule.condition);
+                    let selected = kinds.iter().find(|(_, c)| std::mem::discriminant(c) == current).map(|(label, _)| label.clone());
+                    egui::ComboBox::from_id_source(("rule_kind", i)).selected_text(selected.unwrap_or_default()).show_ui(ui, |ui| {
+                        for (label, condition) in kinds {
+                            let same = std::mem::discriminant(&condition) == current;
+                            if ui.selectable_label(same, label).clicked() && !same {
+                                rule.condition = condition;
+                            }
+                        }
+                    });
+                    match &mut rule.condition {
+                        Condition::Quality(quality) => {
+                            egui::ComboBox::from_id_source(("rule_quality", i))
+                                .selected_text(quality.display_name())
+                                .show_ui(ui, |ui| {
+                                    for q in Quality::ALL {
+                                        ui.selectable_value(quality, q, q.display_name());
+                                    }
+                                });
+                        }
+                        Condition::UploaderContains(text) | Condition::TitleContains(text) => {
+                            ui.add(egui::TextEdit::singleline(text).desired_width(120.0));
+                        }
+                    }
+                    ui.label(t!("rules.move_to"));
+                    ui.add(egui::TextEdit::singleline(&mut rule.folder).desired_width(160.0));
+                    if ui.button("…").on_hover_text(t!("button.browse")).clicked()
+                        && let Some(folder) = FileDialog::new().pick_folder()
+                    {
+                        rule.folder = folder.display().to_string();
+                    }
+                    if ui.add_enabled(i > 0, egui::Button::new("⏶")).clicked() {
+                        swap = Some(i - 1);
+                    }
+                    if ui.add_enabled(i + 1 < count, egui::Button::new("⏷")).clicked() {
+                        swap = Some(i);
+                    }
+                    if ui.button("✖").on_hover_text(t!("rules.remove")).clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if ui.button(t!("rules.add")).clicked() {
+                self.settings.move_rules.push(MoveRule { condition: Condition::Quality(Quality::AudioOnly), folder: String::new() });
+            }
+        });
+        if let Some(i) = swap {
+            self.settings.move_rules.swap(i, i + 1);
+        }
+        if let Some(i) = remove {
+            self.settings.move_rules.remove(i);
+        }
+        self.show_rules = open;
+    }
+
+    /// Lists the re-encoding profiles for editing. Changes are saved like any other setting.
+    fn encode_profiles_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_encode_profiles;
+        let mut remove = None;
+        egui::Window::new(t!("encode.profiles")).open(&mut open).default_size([560.0, 240.0]).show(ctx, |ui| {
+            ui.label(t!("encode.profiles_hint"));
+            for (i, profile) in self.settings.encode_profiles.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let selected = self.settings.encode_profile.as_ref() == Some(&profile.name);
+                    let renamed = ui.add(egui::TextEdit::singleline(&mut profile.name).desired_width(120.0));
+                    egui::ComboBox::from_id_source(("encode_codec", i)).selected_text(profile.codec.label()).show_ui(ui, |ui| {
+                        for codec in VideoCodec::ALL {
+                            ui.selectable_value(&mut profile.codec, codec, codec.label());
+                        }
+                    });
+                    egui::ComboBox::from_id_source(("encode_container", i))
+                        .selected_text(profile.container.extension())
+                        .show_ui(ui, |ui| {
+                            for container in Container::ALL.into_iter().filter(|c| c.supports(profile.codec)) {
+                                ui.selectable_value(&mut profile.container, container, container.extension());
+                            }
+                        });
+                    // Picking VP9 or AV1 leaves a WebM choice alone, the others move it to Matroska
+                    if !profile.container.supports(profile.codec) {
+                        profile.container = Container::Mkv;
+                    }
+                    let crf = match profile.rate {
+                        RateControl::Crf(crf) => Some(crf),
+                        RateControl::Bitrate(_) => None,
+                    };
+                    egui::ComboBox::from_id_source(("encode_rate", i))
+                        .selected_text(if crf.is_some() { t!("encode.crf") } else { t!("encode.bitrate") })
+                        .show_ui(ui, |ui| {
+                            if ui.selectable_label(crf.is_some(), t!("encode.crf")).clicked() && crf.is_none() {
+                                profile.rate = RateControl::Crf(23);
+                            }
+                            if ui.selectable_label(crf.is_none(), t!("encode.bitrate")).clicked() && crf.is_some() {
+                                profile.rate = RateControl::Bitrate(4000);
+                            }
+                        });
+                    match &mut profile.rate {
+                        RateControl::Crf(crf) => {
+                            *crf = (*crf).min(profile.codec.max_crf());
+                            ui.add(egui::DragValue::new(crf).clamp_range(0..=profile.codec.max_crf()))
+                                .on_hover_text(t!("encode.crf_hint"));
+                        }
+                        RateControl::Bitrate(kbps) => {
+                            ui.add(egui::DragValue::new(kbps).clamp_range(100..=100_000).suffix(" kbit/s"));
+                        }
+                    }
+                    if ui.button("✖").on_hover_text(t!("encode.remove")).clicked() {
+                        remove = Some(i);
+                    }
+                    // The form's choice follows the profile it picked when that is renamed
+                    if renamed.changed() && selected {
+                        self.settings.encode_profile = Some(profile.name.clone());
+                    }
+                });
+            }
+            if ui.button(t!("encode.add")).clicked() {
+                let name = t!("encode.new_name", number = self.settings.encode_profiles.len() + 1);
+                self.settings.encode_profiles.push(EncodeProfile {
+                    name,
+                    codec: VideoCodec::H264,
+                    container: Container::Mp4,
+                    rate: RateControl::Crf(23),
+                });
+            }
+        });
+        if let Some(i) = remove {
+            self.settings.encode_profiles.remove(i);
+        }
+        self.show_encode_profiles = open;
+    }
+
+    /// Lists the category presets for editing. Changes are saved like any other setting.
+    fn presets_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_presets;
+        let mut remove = None;
+        egui::Window::new(t!("preset.title")).open(&mut open).default_size([620.0, 240.0]).show(ctx, |ui| {
+            ui.label(t!("preset.editor_hint"));
+            for (i, preset) in self.settings.presets.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let selected = self.form_preset.as_ref() == Some(&preset.name);
+                    let watched = self.settings.watch_preset.as_ref() == Some(&preset.name);
+                    let renamed = ui.add(egui::TextEdit::singleline(&mut preset.name).desired_width(100.0));
+                    egui::ComboBox::from_id_source(("preset_quality", i))
+                        .selected_text(preset.quality.display_name())
+                        .show_ui(ui, |ui| {
+                            for q in Quality::ALL {
+                                ui.selectable_value(&mut preset.quality, q, q.display_name());
+                            }
+                        });
+                    if preset.quality == Quality::AudioOnly {
+                        egui::ComboBox::from_id_source(("preset_audio", i))
+                            .selected_text(preset.audio_format.describe())
+                            .show_ui(ui, |ui| {
+                                for f in AudioFormat::ALL {
+                                    ui.selectable_value(&mut preset.audio_format, f, f.describe());
+                                }
+                            });
+                    } else {
+                        let selected = preset.encode_profile.clone().unwrap_or_else(|| t!("encode.none"));
+                        egui::ComboBox::from_id_source(("preset_encode", i)).selected_text(selected).show_ui(ui, |ui| {
+                            ui.selectable_value(&mut preset.encode_profile, None, t!("encode.none"));
+                            for profile in &self.settings.encode_profiles {
+                                ui.selectable_value(&mut preset.encode_profile, Some(profile.name.clone()), &profile.name);
+                            }
+                        });
+                    }
+                    ui.add(egui::TextEdit::singleline(&mut preset.folder).desired_width(160.0));
+                    if ui.button("…").on_hover_text(t!("button.browse")).clicked()
+                        && let Some(folder) = FileDialog::new().pick_folder()
+                    {
+                        preset.folder = folder.display().to_string();
+                    }
+                    if ui.button("✖").on_hover_text(t!("preset.remove")).clicked() {
+                        remove = Some(i);
+                    }
+                    // The form and the watch folder follow the preset they picked when that is renamed
+                    if renamed.changed() && selected {
+                        self.form_preset = Some(preset.name.clone());
+                    }
+                    if renamed.changed() && watched {
+                        self.settings.watch_preset = Some(preset.name.clone());
+                    }
+                });
+            }
+            if ui.button(t!("preset.add")).clicked() {
+                let name = t!("preset.new_name", number = self.settings.presets.len() + 1);
+                self.settings.presets.push(Preset {
+                    name,
+                    quality: self.settings.quality,
+                    audio_format: self.settings.audio_format,
+                    encode_profile: self.settings.encode_profile.clone(),
+                    folder: self.settings.download_folder.clone(),
+                });
+            }
+        });
+        if let Some(i) = remove {
+            self.settings.presets.remove(i);
+        }
+        self.show_presets = open;
+    }
+
+    fn about_window(&mut self, ctx: &egui::Context) {
+        let diagnostics = self.diagnostics.locked().clone();
+        egui::Window::new(t!("about.title"))
+            .open(&mut self.show_about)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.heading(t!("app.title"));
+                ui.label(t!("about.version", version = env!("CARGO_PKG_VERSION")));
+                ui.separator();
+                let Some(diagnostics) = diagnostics else {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(t!("about.probing"));
+                    });
+                    return;
+                };
+                let missing = t!("about.not_found");
+                egui::Grid::new("about_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("yt-dlp");
+                    ui.label(diagnostics.ytdlp_version.clone().unwrap_or_else(|| missing.clone()));
+                    ui.end_row();
+                    ui.label(t!("about.ytdlp_path"));
+                    match &diagnostics.ytdlp_path {
+                        Ok(path) => elide::label(ui, &path.display().to_string(), egui::TextStyle::Body, Elide::Middle),
+                        Err(e) => ui.colored_label(ui.visuals().error_fg_color, e),
+                    };
+                    ui.end_row();
+                    ui.label("ffmpeg");
+                    ui.label(diagnostics.ffmpeg_version.clone().unwrap_or_else(|| missing.clone()));
+                    ui.end_row();
+                    ui.label(t!("about.os"));
+                    ui.label(&diagnostics.os);
+                    ui.end_row();
+                    if let Some(root) = paths::portable_root() {
+                        ui.label(t!("about.portable"));
+                        elide::label(ui, &root.display().to_string(), egui::TextStyle::Body, Elide::Middle);
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                if ui.button(t!("about.copy")).clicked() {
+                    ui.output_mut(|o| o.copied_text = diagnostics.to_text());
+                    self.toasts.info(t!("about.copied"));
+                }
+            });
+    }
+
+    fn logs_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_logs;
+        egui::Window::new(t!("logs.title")).open(&mut open).default_size([640.0, 360.0]).show(ctx, |ui| {
+            let task_name = |id: TaskId| {
+                self.downloads.iter().find(|t| t.id == id).map_or_else(|| format!("#{}", id), |t| t.title.clone())
+            };
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("log_task")
+                    .selected_text(self.log_task.map_or_else(|| t!("logs.all_tasks"), task_name))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.log_task, None, t!("logs.all_tasks"));
+                        for task in &self.downloads {
+                            ui.selectable_value(&mut self.log_task, Some(task.id), &task.title);
+                        }
+                    });
+                ui.label(t!("panel.filter"));
+                ui.text_edit_singleline(&mut self.log_query);
+            });
+
+            let query = self.log_query.trim().to_lowercase();
+            let entries = app_log::snapshot(|e| {
+                self.log_task.is_none_or(|id| e.task == Some(id))
+                    && (query.is_empty() || e.text.to_lowercase().contains(&query))
+            });
+            ui.horizontal(|ui| {
+                if ui.button(t!("logs.copy_all")).clicked() {
+                    let text: Vec<String> = entries.iter().map(|e| e.to_line()).collect();
+                    ui.output_mut(|o| o.copied_text = text.join("\n"));
+                }
+                if ui.button(t!("button.clear")).clicked() {
+                    app_log::clear();
+                }
+                let folder = app_log::log_dir().map(|d| d.display().to_string()).unwrap_or_default();
+                ui.checkbox(&mut self.settings.log_file, t!("logs.write_file"))
+                    .on_hover_text(t!("logs.write_file_hint", folder = folder));
+            });
+            ui.separator();
+
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            egui::ScrollArea::both().stick_to_bottom(true).auto_shrink([false; 2]).show_rows(
+                ui,
+                row_height,
+                entries.len(),
+                |ui, rows| {
+                    for entry in &entries[rows] {
+                        let color = match entry.level {
+                            Level::Info => ui.visuals().text_color(),
+                            Level::Warning => ui.visuals().warn_fg_color,
+                            Level::Error => ui.visuals().error_fg_color,
+                        };
+                        ui.label(egui::RichText::new(entry.to_line()).monospace().color(color));
+                    }
+                },
+            );
+        });
+        self.show_logs = open;
+    }
+
+    /// Indices of the tasks the downloads list shows, filtered and in display order.
+    fn shown_tasks(&self) -> Vec<usize> {
+        let query = self.downloads_query.trim();
+        let mut shown: Vec<usize> = (0..self.downloads.len())
+            .filter(|&i| {
+                let t = &self.downloads[i];
+                self.status_filter.allows(&t.status) && t.matches(query)
+            })
+            .collect();
+        let order = self.settings.sort_order;
+        shown.sort_by(|&a, &b| order.compare(&self.downloads[a], &self.downloads[b]));
+        // Playlist items stay together, where the first of them sorts
+        let mut group_start = HashMap::new();
+        for (position, &i) in shown.iter().enumerate() {
+            if let Some(playlist) = self.downloads[i].playlist {
+                group_start.entry(playlist).or_insert(position);
+            }
+        }
+        let mut keyed: Vec<(usize, usize)> = shown
+            .iter()
+            .enumerate()
+            .map(|(position, &i)| (self.downloads[i].playlist.map_or(position, |p| group_start[&p]), i))
+            .collect();
+        keyed.sort_by_key(|&(key, _)| key);
+        keyed.into_iter().map(|(_, i)| i).collect()
+    }
+
+    /// Whether one of the centered dialogs is waiting for an answer.
+    fn dialog_open(&self) -> bool {
+        self.confirm_clear
+            || self.confirm_delete.is_some()
+            || self.confirm_delete_file.is_some()
+            || !self.conflicts.is_empty()
+            || self.import_summary.is_some()
+            || self.pending_queue_import.is_some()
+            || self.queue_import_summary.is_some()
+            || self.restore_prompt.is_some()
+            || self.exit_prompt.is_some()
+            || self.missing_folder.is_some()
+            || self.tag_editor.is_some()
+            || self.gif_dialog.is_some()
+            || self.wizard.is_some()
+            || self.reschedule.is_some()
+            || self.after_queue_at.is_some()
+    }
+
+    /// Tasks of the downloads list in the order shown, without those in collapsed playlists.
+    fn list_order(&self, ctx: &egui::Context) -> Vec<TaskId> {
+        self.shown_tasks()
+            .into_iter()
+            .map(|i| &self.downloads[i])
+            .filter(|t| t.playlist.is_none_or(|p| playlist_open(ctx, p)))
+            .map(|t| t.id)
+            .collect()
+    }
+
+    /// A click on a card selects only it, Ctrl+click adds or removes it and Shift+click
+    /// selects everything from the last clicked card to it.
+    fn click_task(&mut self, ctx: &egui::Context, id: TaskId, modifiers: egui::Modifiers) {
+        if modifiers.shift
+            && let Some(anchor) = self.selected
+        {
+            let order = self.list_order(ctx);
+            let position = |id| order.iter().position(|&o| o == id);
+            if let (Some(a), Some(b)) = (position(anchor), position(id)) {
+                if !modifiers.command {
+                    self.selection.clear();
+                }
+                self.selection.extend(&order[a.min(b)..=a.max(b)]);
+                return;
+            }
+        }
+        if !modifiers.command {
+            self.selection.clear();
+        }
+        if !self.selection.remove(&id) {
+            self.selection.insert(id);
+        }
+        self.selected = Some(id);
+    }
+
+    /// Selected tasks a bulk action applies to, leaving out those in the wrong state
+    /// for it (running ones can't be removed, finished ones not cancelled, …).
+    fn selection_targets(&self, action: CardAction) -> Vec<TaskId> {
+        self.downloads
+            .iter()
+            .filter(|t| self.selection.contains(&t.id))
+            .filter(|t| match action {
+                CardAction::Pause => t.status.is_running(),
+                CardAction::Retry => matches!(t.status, DownloadStatus::Failed(_) | DownloadStatus::Paused),
+                CardAction::Cancel => !matches!(t.status, DownloadStatus::Done | DownloadStatus::Failed(_)),
+                CardAction::Remove => !t.status.is_running(),
+                _ => false,
+            })
+            .map(|t| t.id)
+            .collect()
+    }
+
+    /// Shown above the downloads list while tasks are selected.
+    fn selection_bar(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            ui.label(t!("selection.count", count = self.selection.len()));
+            for (text, bulk) in [
+                (t!("button.pause"), CardAction::Pause),
+                (t!("button.retry"), CardAction::Retry),
+                (t!("button.cancel"), CardAction::Cancel),
+                (t!("menu.remove"), CardAction::Remove),
+            ] {
+                if ui.add_enabled(!self.selection_targets(bulk).is_empty(), egui::Button::new(text)).clicked() {
+                    action = Some(bulk);
+                }
+            }
+            if ui.small_button("✖").on_hover_text(t!("selection.clear")).clicked() {
+                self.selection.clear();
+            }
+        });
+        if let Some(action) = action {
+            self.run_bulk_action(ctx, action);
+        }
+    }
+
+    fn run_bulk_action(&mut self, ctx: &egui::Context, action: CardAction) {
+        let actions = self.selection_targets(action).into_iter().map(|id| (id, action)).collect();
+        self.run_card_actions(ctx, actions);
+    }
+
+    /// Runs several card actions, removing the tasks to remove in one go so a single
+    /// Undo brings them all back.
+    fn run_card_actions(&mut self, ctx: &egui::Context, actions: Vec<(TaskId, CardAction)>) {
+        let mut removed = Vec::new();
+        for (id, action) in actions {
+            match action {
+                CardAction::Cancel => {
+                    self.pause_task(id);
+                    removed.push(id);
+                }
+                CardAction::Remove => removed.push(id),
+                _ => self.run_card_action(ctx, id, action),
+            }
+        }
+        if !removed.is_empty() {
+            self.trash_tasks(&removed);
+        }
+    }
+
+    /// Up/Down move the selection through the downloads list, Enter opens the selected
+    /// file and Delete removes the task, asking first if it is still active. With several
+    /// tasks selected Delete removes those that aren't running and Escape deselects them.
+    /// Keys go to text fields and dialogs instead while those have them.
+    fn handle_list_keys(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() || self.dialog_open() {
+            return;
+        }
+        let (up, down, enter, delete, escape) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::ArrowUp),
+                i.key_pressed(egui::Key::ArrowDown),
+                i.key_pressed(egui::Key::Enter),
+                i.key_pressed(egui::Key::Delete),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if self.selection.len() > 1 {
+            if escape {
+                self.selection.clear();
+                return;
+            }
+            if delete {
+                self.run_bulk_action(ctx, CardAction::Remove);
+                return;
+            }
+        }
+        if !(up || down || enter || delete) {
+            return;
+        }
+        let order = self.list_order(ctx);
+        let Some(last) = order.len().checked_sub(1) else { return };
+        let position = self.selected.and_then(|id| order.iter().position(|&o| o == id));
+        if up || down {
+            let next = match position {
+                None if up => last,
+                None => 0,
+                Some(p) if up => p.saturating_sub(1),
+                Some(p) => (p + 1).min(last),
+            };
+            self.selected = Some(order[next]);
+            self.selection = HashSet::from([order[next]]);
+            self.scroll_to_selected = true;
+            return;
+        }
+        let Some(position) = position else { return };
+        let id = order[position];
+        let Some(task) = self.downloads.iter().find(|t| t.id == id) else { return };
+        if enter && matches!(task.status, DownloadStatus::Done) {
+            self.play_file(id);
+        } else if delete {
+            if task.status.is_running() || matches!(task.status, DownloadStatus::Queued) {
+                self.confirm_delete = Some(id);
+            } else {
+                // Keep a selection so Delete can be pressed repeatedly
+                self.selected = order.get(position + 1).or(position.checked_sub(1).map(|p| &order[p])).copied();
+                self.selection = self.selected.into_iter().collect();
+                self.trash_tasks(&[id]);
+            }
+        }
+    }
+
+    /// Logs how a run ended once `events::reduce` has updated the task, and records
+    /// finished and failed ones in the history.
+    fn finish_task(&mut self, ctx: &egui::Context, id: TaskId) {
+        let Some(task) = self.downloads.iter().find(|t| t.id == id) else { return };
+        match &task.status {
+            DownloadStatus::Done => {
+                let saved = task.file_path.as_ref().map_or("unknown path".to_string(), |p| p.display().to_string());
+                app_log::task_event(id, Level::Info, format!("Finished, saved to {}", saved));
+                if let Some(file) = task.file_path.clone()
+                    && let Some(rule) = rules::first_match(&self.settings.move_rules, task)
+                {
+                    let folder = rule.folder.clone();
+                    self.move_finished(ctx, id, file, folder);
+                    return;
+                }
+            }
+            DownloadStatus::Failed(e) => app_log::task_event(id, Level::Error, format!("Failed: {}", e)),
+            _ => {
+                app_log::task_event(id, Level::Info, "Paused");
+                return;
+            }
+        }
+        self.record_finished(ctx, id);
+    }
+
+    /// Moves a finished download's file where a move rule says, in the background;
+    /// the task goes into the history once it's there.
+    fn move_finished(&mut self, ctx: &egui::Context, id: TaskId, file: PathBuf, folder: String) {
+        app_log::task_event(id, Level::Info, format!("Moving to {} by rule", folder));
+        self.moving.insert(id);
+        let results = Arc::clone(&self.move_results);
+        let ctx_c = ctx.clone();
+        self.spawn_blocking_caught(
+            move || rules::move_file(&file, Path::new(&folder)).map_err(|e| e.to_string()),
+            move |moved| {
+                results.locked().push((id, moved.and_then(|m| m)));
+                ctx_c.request_repaint();
+            },
+        );
+    }
+
+    /// Adds a finished or failed task to the history and notifies about it.
+    fn record_finished(&mut self, ctx: &egui::Context, id: TaskId) {
+        let Some(task) = self.downloads.iter().find(|t| t.id == id) else { return };
+        let status = match task.status {
+            DownloadStatus::Done => HistoryStatus::Done,
+            _ => HistoryStatus::Failed,
+        };
+        let entry = HistoryEntry {
+            title: task.title.clone(),
+            video_id: task.video_id.clone(),
+            url: task.url.clone(),
+            quality: task.quality,
+            folder: task.moved_to.clone().unwrap_or_else(|| task.folder.clone()),
+            file_path: task.file_path.clone(),
+            status,
+            finished_at: history::now_unix(),
+            size: task.file_path.as_ref().and_then(|p| std::fs::metadata(p).ok()).map(|m| m.len()),
+            duration: task.duration,
+            file_deleted: false,
+        };
+
+        let focused = ctx.input(|i| i.viewport().focused.unwrap_or(false));
+        if self.settings.notifications && !(self.settings.notify_only_unfocused && focused) {
+            let (summary, body) = match &task.status {
+                DownloadStatus::Failed(e) => (t!("notify.failed", title = task.title), e.clone()),
+                _ => (t!("notify.done", title = task.title), entry.folder.clone()),
+            };
+            notifications::notify(ctx, summary, body);
+        }
+        self.history.append(entry);
+        #[cfg(feature = "sound")]
+        self.play_completion_sound(id);
+    }
+
+    /// Plays the chime for a task that just ended if the settings ask for it: for each
+    /// finished download, or once nothing is downloading or waiting any more.
+    #[cfg(feature = "sound")]
+    fn play_completion_sound(&self, id: TaskId) {
+        if !self.settings.completion_sound {
+            return;
+        }
+        let play = match self.settings.sound_when {
+            SoundWhen::EachDownload => self.downloads.iter().any(|t| t.id == id && matches!(t.status, DownloadStatus::Done)),
+            // Scheduled tasks may be hours away, so they don't hold the chime back
+            SoundWhen::QueueDone => {
+                self.moving.is_empty()
+                    && !self.downloads.iter().any(|t| {
+                        matches!(t.status, DownloadStatus::Queued | DownloadStatus::Downloading | DownloadStatus::PostProcessing)
+                    })
+            }
+        };
+        if play {
+            sound::play_done(self.settings.sound_volume);
+        }
+    }
+
+    /// Applies the outcome of moves started by `move_finished`.
+    fn receive_moves(&mut self, ctx: &egui::Context) {
+        let moved: Vec<_> = self.move_results.locked().drain(..).collect();
+        for (id, moved) in moved {
+            self.moving.remove(&id);
+            let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { continue };
+            match moved {
+                Ok(path) => {
+                    app_log::task_event(id, Level::Info, format!("Moved to {}", path.display()));
+                    task.moved_to = path.parent().map(|dir| dir.display().to_string());
+                    task.file_path = Some(path);
+                }
+                Err(e) => {
+                    app_log::task_event(id, Level::Warning, format!("Move rule failed: {}", e));
+                    self.toasts.error(t!("rules.move_failed", title = task.title, error = e));
+                }
+            }
+            self.record_finished(ctx, id);
+        }
+    }
+
+    /// One task of the downloads list, as a full card or a compact row.
+    fn task_card(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, i: usize, pass: &mut ListPass) {
+        let task = &self.downloads[i];
+        let (icon, status_text, color) = status_style(&task.status, ui.visuals(), self.settings.status_palette);
+        let status_text = match task.post_step {
+            Some(step) if matches!(task.status, DownloadStatus::PostProcessing) => step.label(),
+            _ => status_text,
+        };
+        let copied = self.copied.is_some_and(|(id, at)| id == task.id && at.elapsed() < COPIED_NOTICE);
+        // Registered before the card's buttons so they still get their clicks;
+        // uses last frame's card rect since this one's isn't known yet
+        let background_id = egui::Id::new(("card_background", task.id));
+        let background_rect = ctx.data(|d| d.get_temp(background_id)).unwrap_or(egui::Rect::NOTHING);
+        let background = ui.interact(background_rect, background_id, egui::Sense::click());
+        if background.clicked() {
+            pass.clicked = Some((task.id, ui.input(|i| i.modifiers)));
+        } else if background.secondary_clicked() && !self.selection.contains(&task.id) {
+            // Right-clicking inside the selection keeps it, like in file managers
+            pass.clicked = Some((task.id, egui::Modifiers::NONE));
+        }
+        let selected = self.selection.contains(&task.id);
+        let mut frame = if pass.compact {
+            egui::Frame::none()
+                .inner_margin(egui::Margin::symmetric(4.0, 1.0))
+                .rounding(ui.visuals().widgets.noninteractive.rounding)
+        } else {
+            egui::Frame::group(ui.style())
+        };
+        if selected {
+            frame = frame.stroke(ui.visuals().selection.stroke);
+        }
+        let is_new = self.new_task.is_some_and(|(id, _)| id == task.id);
+        if let Some((_, added)) = self.new_task
+            && is_new
+            && added.elapsed() < NEW_TASK_HIGHLIGHT
+        {
+            let fade = 1.0 - added.elapsed().as_secs_f32() / NEW_TASK_HIGHLIGHT.as_secs_f32();
+            frame = frame.fill(ui.visuals().selection.bg_fill.gamma_multiply(fade));
+            ctx.request_repaint();
+        }
+        let card = frame.show(ui, |ui| {
+            if pass.compact {
+                compact_row(ui, task, icon, color, copied);
+                return;
+            }
+            ui.horizontal(|ui| {
+                let thumb = thumbnail_slot(
+                    ui,
+                    task.thumbnail,
+                    self.thumbnails.get(&task.video_id),
+                    self.placeholder.as_ref(),
+                );
+                if let Some(badge) = task.duration_badge() {
+                    paint_duration_badge(ui, thumb.rect, &badge, task.is_live);
+                }
+                if thumb
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text(t!("card.open_in_browser"))
+                    .clicked()
+                {
+                    os_open::open_in_os(watch_url(&task.video_id));
+                }
+                ui.vertical(|ui| {
+                    elide::label(ui, &task.title, egui::TextStyle::Body, Elide::End);
+                    if let Some(details) = task.details_line() {
+                        // Truncate so a long channel name can't widen the panel
+                        ui.add(egui::Label::new(egui::RichText::new(details).small().weak()).truncate(true));
+                    }
+                    let mut status_text = if task.attempts > 1 {
+                        t!("card.attempt", status = status_text, attempt = task.attempts)
+                    } else {
+                        status_text
+                    };
+                    if let Some(position) = pass.queue.get(&task.id) {
+                        status_text = t!("card.queue_position", status = status_text, position = position);
+                    }
+                    if let Some(clip) = task.clip {
+                        status_text = format!("{} · {}", status_text, t!("card.clip", range = clip.range()));
+                    }
+                    if task.quality == Quality::AudioOnly {
+                        status_text = format!("{} · {}", status_text, t!("card.audio", format = task.audio_format.describe()));
+                    } else if let Some(res) = &task.resolution {
+                        status_text = format!("{} · {}", status_text, res);
+                    }
+                    if let Some(profile) = &task.encode {
+                        status_text = format!("{} · {}", status_text, t!("card.encode", profile = profile.name));
+                    }
+                    if let Some(preset) = &task.preset {
+                        status_text = format!("{} · {}", status_text, t!("card.preset", name = preset));
+                    }
+                    // Icon and label differ per status too, so color is never the only cue
+                    let status = ui
+                        .horizontal(|ui| {
+                            if task.status.is_running() {
+                                ui.add(egui::Spinner::new().size(12.0).color(color));
+                            } else {
+                                ui.colored_label(color, icon);
+                            }
+                            let label = ui.colored_label(color, status_text);
+                            if copied {
+                                ui.small(t!("card.copied"));
+                            }
+                            label
+                        })
+                        .inner;
+                    if let DownloadStatus::Failed(e) = &task.status {
+                        status.on_hover_text(e);
+                    }
+                    ui.add(egui::ProgressBar::new(task.progress).show_percentage().fill(color));
+                    // Where a move rule takes the file, or took it
+                    let destination = match &task.moved_to {
+                        Some(folder) => Some(t!("rules.moved_to", folder = folder)),
+                        None if !matches!(task.status, DownloadStatus::Done | DownloadStatus::Failed(_))
+                            || self.moving.contains(&task.id) =>
+                        {
+                            rules::first_match(&self.settings.move_rules, task)
+                                .map(|rule| t!("rules.will_move", folder = rule.folder))
+                        }
+                        None => None,
+                    };
+                    if let Some(destination) = destination {
+                        ui.add(egui::Label::new(egui::RichText::new(destination).small().weak()).truncate(true));
+                    }
+                    if !task.tracks.is_empty() {
+                        egui::CollapsingHeader::new(egui::RichText::new(t!("chapters.tracks", count = task.tracks.len())).small())
+                            .id_source(("tracks", task.id))
+                            .show(ui, |ui| {
+                                for track in &task.tracks {
+                                    let name = track.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                                    if ui.small_button(name).on_hover_text(t!("chapters.play")).clicked() {
+                                        os_open::open_in_os(track);
+                                    }
+                                }
+                            });
+                    }
+                    if let Some(warning) = &task.warning {
+                        let warning = egui::RichText::new(format!("⚠ {}", warning)).small().color(ui.visuals().warn_fg_color);
+                        ui.add(egui::Label::new(warning).truncate(true));
+                    }
+                    if task.status.is_running() && ui.button(t!("button.pause")).clicked() {
+                        pass.actions.push((task.id, CardAction::Pause));
+                    }
+                    if !task.status.is_running() {
+                        ui.horizontal(|ui| {
+                            if matches!(task.status, DownloadStatus::Done) {
+                                let play = ui
+                                    .add_enabled(!task.file_deleted, egui::Button::new(t!("button.play")))
+                                    .on_disabled_hover_text(t!("card.file_deleted"));
+                                if play.clicked() {
+                                    pass.actions.push((task.id, CardAction::OpenFile));
+                                }
+                            }
+                            if task.taggable() && ui.button(t!("button.edit_tags")).clicked() {
+                                pass.actions.push((task.id, CardAction::EditTags));
+                            }
+                            if matches!(task.status, DownloadStatus::Failed(_))
+                                && ui.button(t!("button.retry")).clicked()
+                            {
+                                pass.actions.push((task.id, CardAction::Retry));
+                            }
+                            if matches!(task.status, DownloadStatus::Paused)
+                                && ui.button(t!("button.resume")).clicked()
+                            {
+                                pass.actions.push((task.id, CardAction::Retry));
+                            }
+                            if let Some(&position) = pass.queue.get(&task.id) {
+                                let last = pass.queue.len();
+                                for (enabled, text, hint, action) in [
+                                    (position > 1, "⏫", t!("queue.move_top"), CardAction::MoveToTop),
+                                    (position > 1, "⏶", t!("queue.move_up"), CardAction::MoveUp),
+                                    (position < last, "⏷", t!("queue.move_down"), CardAction::MoveDown),
+                                ] {
+                                    if ui.add_enabled(enabled, egui::Button::new(text)).on_hover_text(hint).clicked() {
+                                        pass.actions.push((task.id, action));
+                                    }
+                                }
+                            }
+                            if matches!(task.status, DownloadStatus::Scheduled(_)) {
+                                if ui.button(t!("schedule.start_now")).clicked() {
+                                    pass.actions.push((task.id, CardAction::StartNow));
+                                }
+                                if ui.button(t!("schedule.edit")).clicked() {
+                                    pass.actions.push((task.id, CardAction::Reschedule));
+                                }
+                            }
+
+                            // Remove Button
+                            let remove = egui::RichText::new("❌").color(egui::Color32::WHITE);
+                            let fill = themed(ui.visuals(), (200, 40, 40), (190, 20, 20));
+                            if ui.add(egui::Button::new(remove).fill(fill)).clicked() {
+                                pass.actions.push((task.id, CardAction::Remove));
+                            }
+                        });
+                    }
+                    if task.file_deleted {
+                        ui.horizontal(|ui| {
+                            ui.weak(t!("card.file_deleted"));
+                            if ui.button(t!("button.redownload")).clicked() {
+                                pass.actions.push((task.id, CardAction::Redownload));
+                            }
+                        });
+                    } else if task.file_missing {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(ui.visuals().error_fg_color, t!("card.file_not_found"));
+                            if ui.button(t!("button.redownload")).clicked() {
+                                pass.actions.push((task.id, CardAction::Redownload));
+                            }
+                        });
+                    }
+                });
+            });
+        });
+        ctx.data_mut(|d| d.insert_temp(background_id, card.response.rect));
+        for job in self.gif_jobs.iter().filter(|j| j.task == task.id) {
+            gif_row(ui, job, &mut pass.actions);
+        }
+        if selected && self.scroll_to_selected {
+            card.response.scroll_to_me(None);
+        }
+        if is_new && self.reveal_new_task && !pass.user_scrolling {
+            card.response.scroll_to_me(None);
+        }
+        let queue = pass.queue.get(&task.id).map(|&position| (position, pass.queue.len()));
+        let gif_blocked = if self.ffmpeg_missing() {
+            Some(t!("gif.needs_ffmpeg"))
+        } else if self.gif_jobs.iter().any(|j| j.task == task.id && j.result.is_none()) {
+            Some(t!("gif.busy"))
+        } else {
+            None
+        };
+        background.context_menu(|ui| card_menu(ui, task, queue, gif_blocked, &mut pass.actions));
+        // Compact rows have no thumbnails to fetch
+        if !pass.compact && ui.is_rect_visible(card.response.rect.expand2(egui::vec2(0.0, THUMBNAIL_LOOKAHEAD))) {
+            pass.visible.insert(task.video_id.clone());
+        }
+    }
+
+    /// Header of the tasks expanded from one playlist, with their combined progress,
+    /// actions for the whole group and a toggle to collapse them.
+    fn playlist_header(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, playlist: PlaylistId, pass: &mut ListPass) {
+        let title = self.playlists.iter().find(|p| p.id == playlist).map(|p| p.title.clone()).unwrap_or_default();
+        // Counted over the whole playlist, also the tasks the filter hides
+        let tasks: Vec<&DownloadTask> = self.downloads.iter().filter(|t| t.playlist == Some(playlist)).collect();
+        let done: Vec<TaskId> =
+            tasks.iter().filter(|t| matches!(t.status, DownloadStatus::Done)).map(|t| t.id).collect();
+        let unfinished: Vec<TaskId> = tasks
+            .iter()
+            .filter(|t| !matches!(t.status, DownloadStatus::Done | DownloadStatus::Failed(_)))
+            .map(|t| t.id)
+            .collect();
+        let progress = tasks.iter().map(|t| t.progress).sum::<f32>() / tasks.len().max(1) as f32;
+        let total = tasks.len();
+
+        // The members are rows of their own, so only those on screen get built
+        let mut state =
+            egui::collapsing_header::CollapsingState::load_with_default_open(ctx, playlist_header_id(playlist), true);
+        ui.horizontal(|ui| {
+            state.show_toggle_button(ui, egui::collapsing_header::paint_default_icon);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.menu_button("⋯", |ui| {
+                    if ui.add_enabled(!unfinished.is_empty(), egui::Button::new(t!("playlist.cancel_all"))).clicked() {
+                        pass.actions.extend(unfinished.iter().map(|&id| (id, CardAction::Cancel)));
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!done.is_empty(), egui::Button::new(t!("playlist.remove_finished"))).clicked() {
+                        pass.actions.extend(done.iter().map(|&id| (id, CardAction::Remove)));
+                        ui.close_menu();
+                    }
+                });
+                ui.label(t!("playlist.progress", done = done.len(), total = total));
+                ui.add(egui::ProgressBar::new(progress).desired_width(60.0));
+                ui.add(egui::Label::new(egui::RichText::new(title).strong()).truncate(true));
+            });
+        });
+        state.store(ctx);
+    }
+
+    fn search_view(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            let field = ui.add(egui::TextEdit::singleline(&mut self.search.query).hint_text(t!("search.hint")));
+            if field.changed() {
+                self.search.cancel();
+            }
+            let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button(t!("search.button")).clicked() || submitted {
+                self.start_search(ctx, false);
+            }
+            if self.search.running.is_some() {
+                ui.spinner();
+            }
+        });
+        if let Some(e) = &self.search.error {
+            ui.colored_label(ui.visuals().error_fg_color, e);
+        }
+        ui.separator();
+
+        let mut download = None;
+        let mut load_more = false;
+        egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+            for result in &self.search.results {
+                ui.horizontal(|ui| {
+                    let (state, texture) = match self.search.thumbnails.get(&result.id) {
+                        Some(Some(texture)) => (ThumbnailState::Loaded, Some(texture)),
+                        Some(None) => (ThumbnailState::Failed, None),
+                        None => (ThumbnailState::Pending, None),
+                    };
+                    let thumb = thumbnail_slot(ui, state, texture, self.placeholder.as_ref());
+                    let duration = result.duration.map(|d| format_duration(d as u64));
+                    if let Some(badge) = &duration {
+                        paint_duration_badge(ui, thumb.rect, badge, false);
+                    }
+                    if thumb
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .on_hover_text(t!("card.open_in_browser"))
+                        .clicked()
+                    {
+                        os_open::open_in_os(watch_url(&result.id));
+                    }
+                    ui.vertical(|ui| {
+                        let title = result.title.as_deref().unwrap_or(&result.id);
+                        elide::label(ui, title, egui::TextStyle::Body, Elide::End);
+                        let details: Vec<&str> = [result.channel(), duration.as_deref()].into_iter().flatten().collect();
+                        if !details.is_empty() {
+                            ui.add(egui::Label::new(egui::RichText::new(details.join(" · ")).small().weak()).truncate(true));
+                        }
+                        if ui.button(t!("button.download")).clicked() {
+                            download = Some((result.id.clone(), title.to_string()));
+                        }
+                    });
+                });
+                ui.separator();
+            }
+            if self.search.running.is_none() && !self.search.shown_query.is_empty() && self.search.error.is_none() {
+                if self.search.results.is_empty() {
+                    ui.weak(t!("search.no_results"));
+                } else if ui.button(t!("search.more", count = SEARCH_PAGE)).clicked() {
+                    load_more = true;
+                }
+            }
+        });
+
+        if let Some((video_id, title)) = download
+            && self.queue_url(ctx, watch_url(&video_id))
+        {
+            self.toasts.info(t!("search.queued", title = title));
+        }
+        if load_more {
+            self.start_search(ctx, true);
+        }
+    }
+
+    /// Runs the search in the query field, or with `more` the next page of the one shown.
+    fn start_search(&mut self, ctx: &egui::Context, more: bool) {
+        self.search.cancel();
+        let query = if more { self.search.shown_query.clone() } else { self.search.query.trim().to_string() };
+        if query.is_empty() {
+            return;
+        }
+        if !more {
+            self.search.results.clear();
+            self.search.thumbnails.clear();
+            self.search.shown_query = query.clone();
+        }
+        self.search.error = None;
+        let generation = self.search.generation;
+        let start = self.search.results.len() + 1;
+        let results = Arc::clone(&self.search_results);
+        let ctx = ctx.clone();
+        let span = tracing::info_span!("search", query = %query, start);
+        self.search.running = Some(self.spawn_caught(
+            async move { metadata::search(&query, start, SEARCH_PAGE).await.map_err(|e| e.to_string()) }.instrument(span),
+            move |found| {
+                results.locked().push((generation, found.and_then(|f| f)));
+                ctx.request_repaint();
+            },
+        ));
+    }
+
+    /// Takes in finished searches and thumbnails of their hits.
+    fn update_search(&mut self, ctx: &egui::Context) {
+        let finished: Vec<_> = self.search_results.locked().drain(..).collect();
+        // Anything older was aborted or replaced
+        for (_, found) in finished.into_iter().filter(|(g, _)| *g == self.search.generation) {
+            self.search.running = None;
+            match found {
+                Ok(found) => {
+                    for result in &found {
+                        self.request_search_thumbnail(ctx, result.id.clone());
+                    }
+                    self.search.results.extend(found);
+                }
+                Err(e) => self.search.error = Some(t!("search.failed", error = e)),
+            }
+        }
+
+        let thumbnails: Vec<_> = self.search_thumbnails.locked().drain(..).collect();
+        for (video_id, image) in thumbnails {
+            if self.search.results.iter().any(|r| r.id == video_id) {
+                let texture = image.map(|img| ctx.load_texture(&video_id, img, TextureOptions::default()));
+                self.search.thumbnails.insert(video_id, texture);
+            }
+        }
+    }
+
+    fn request_search_thumbnail(&self, ctx: &egui::Context, video_id: String) {
+        let results = Arc::clone(&self.search_thumbnails);
+        let ctx = ctx.clone();
+        let fetch_id = video_id.clone();
+        self.spawn_blocking_caught(
+            move || thumbnail::fetch_thumbnail_with_retry(&fetch_id, None, thumbnail::THUMBNAIL_SIZE),
+            move |image| {
+                results.locked().push((video_id, image.ok().and_then(Result::ok)));
+                ctx.request_repaint();
+            },
+        );
+    }
+
+    fn history_view(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.horizontal(|ui| {
+            ui.label(t!("history.search"));
+            ui.text_edit_singleline(&mut self.history_query);
+        });
+        ui.separator();
+
+        let mut redownload = None;
+        let mut delete = None;
+        egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+            let query = self.history_query.trim();
+            for entry in self.history.entries.iter().rev().filter(|e| e.matches(query)) {
+                ui.group(|ui| {
+                    elide::label(ui, &entry.title, egui::TextStyle::Body, Elide::End);
+                    let status = match entry.status {
+                        HistoryStatus::Done => DownloadStatus::Done,
+                        HistoryStatus::Failed => DownloadStatus::Failed(String::new()),
+                    };
+                    let (icon, label, color) = status_style(&status, ui.visuals(), self.settings.status_palette);
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} · {} ·", entry.date_string(), entry.quality.display_name()));
+                        ui.colored_label(color, format!("{} {}", icon, label));
+                    });
+                    if let Some(path) = &entry.file_path {
+                        elide::label(ui, &path.display().to_string(), egui::TextStyle::Small, Elide::Middle);
+                    }
+                    if entry.file_deleted {
+                        ui.weak(t!("card.file_deleted"));
+                    }
+                    ui.horizontal(|ui| {
+                        if let Some(path) = &entry.file_path {
+                            let kept = !entry.file_deleted;
+                            if ui.add_enabled(kept, egui::Button::new(t!("button.open_file"))).clicked() {
+                                os_open::open_in_os(path);
+                            }
+                            if kept
+                                && entry.status == HistoryStatus::Done
+                                && ui.button(t!("button.delete_file")).clicked()
+                            {
+                                delete = Some(FileToDelete { title: entry.title.clone(), path: path.clone() });
+                            }
+                        }
+                        if ui.button(t!("button.open_folder")).clicked() {
+                            os_open::open_in_os(&entry.folder);
+                        }
+                        if let Some(path) = &entry.file_path
+                            && !entry.file_deleted
+                            && ui.button(t!("button.show_in_folder")).clicked()
+                        {
+                            os_open::reveal_in_os(path);
+                        }
+                        if ui.button(t!("button.copy_url")).clicked() {
+                            ui.output_mut(|o| o.copied_text = watch_url(&entry.video_id));
+                            self.toasts.info(t!("toast.url_copied"));
+                        }
+                        if ui.button(t!("button.redownload")).clicked() {
+                            redownload = Some(entry.clone());
+                        }
+                    });
+                });
+            }
+        });
+
+        if let Some(entry) = redownload {
+            self.start_download(ctx, entry.url, entry.quality, entry.folder);
+            self.view = View::Downloads;
+        }
+        if delete.is_some() {
+            self.confirm_delete_file = delete;
+        }
+    }
+
+    /// Deletes a downloaded file the user confirmed, then marks the tasks and history
+    /// entries that point at it.
+    fn delete_file(&mut self, file: FileToDelete) {
+        if let Err(e) = os_open::delete_file(&file.path, self.settings.delete_permanently) {
+            self.toasts.error(t!("delete_file.failed", title = file.title, error = e));
+            return;
+        }
+        app_log::info(format!("Deleted {}", file.path.display()));
+        for task in self.downloads.iter_mut().filter(|t| t.file_path.as_ref() == Some(&file.path)) {
+            task.file_deleted = true;
+            task.file_missing = false;
+        }
+        self.history.mark_deleted(&file.path);
+        self.toasts.info(t!("delete_file.done", title = file.title));
+    }
+
+    /// Runs the yt-dlp metadata prefetch for a queued task.
+    fn request_metadata(&mut self, ctx: &egui::Context, id: TaskId, url: &str) {
+        self.metadata_pending.insert(id);
+        let url = url.to_string();
+        let results = Arc::clone(&self.metadata_results);
+        let ctx_c = ctx.clone();
+        let span = tracing::info_span!("metadata", task_id = id);
+        self.spawn_caught(
+            async move { metadata::fetch_metadata(&url).await.map_err(|e| e.to_string()) }.instrument(span),
+            move |meta| {
+                results.locked().push((id, meta.and_then(|m| m)));
+                ctx_c.request_repaint();
+            },
+        );
+    }
+
+    /// Applies prefetched metadata and switches to its thumbnail if ours hasn't loaded yet.
+    fn apply_metadata(&mut self, id: TaskId, meta: VideoMetadata) {
+        let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { return };
+        task.suggested_tags = Some(Tags::guess(&meta));
+        task.estimated_size = meta.estimate_size(task.quality).map(|size| match (task.clip, meta.duration) {
+            (Some(clip), Some(duration)) => clip.scale_size(size, duration.round() as u64),
+            _ => size,
+        });
+        if let Some(title) = meta.title {
+            task.title = title;
+        }
+        task.duration = meta.duration.map(|d| d.round() as u64);
+        task.is_live = meta.is_live.unwrap_or(false);
+        task.uploader = meta.uploader.or(meta.channel);
+        task.upload_date = meta.upload_date.filter(|d| d.len() == 8 && d.bytes().all(|b| b.is_ascii_digit())).map(|d| {
+            format!("{}-{}-{}", &d[0..4], &d[4..6], &d[6..8])
+        });
+        task.view_count = meta.view_count;
+        task.chapters = meta.chapters.unwrap_or_default();
+        if meta.thumbnail.is_some() && task.thumbnail_url.is_none() {
+            task.thumbnail_url = meta.thumbnail;
+            // Forget earlier failures so the next reconciliation fetches the new URL
+            let in_flight = self.thumbnail_retries.get(&task.video_id).is_some_and(|r| r.in_flight);
+            if task.thumbnail != ThumbnailState::Loaded && !in_flight {
+                self.thumbnail_retries.remove(&task.video_id);
+            }
+        }
+    }
+
+    /// Removes tasks and everything held for them. Every removal path should go
+    /// through here so row heights and textures can't be leaked.
+    fn remove_tasks(&mut self, ids: &[TaskId]) {
+        self.downloads.retain(|t| !ids.contains(&t.id));
+        self.progress_rxs.retain(|id, _| !ids.contains(id));
+        self.selection.retain(|id| !ids.contains(id));
+        self.conflicts.retain(|(id, _)| !ids.contains(id));
+        self.playlists.retain(|p| self.downloads.iter().any(|t| t.playlist == Some(p.id)));
+        for &id in ids {
+            for compact in [false, true] {
+                self.row_heights.remove(&list_row_id(id, compact));
+            }
+        }
+        self.purge_orphaned_thumbnails();
+    }
+
+    /// Removes all Done tasks, and Failed ones too if `include_failed`.
+    /// They are already in the history since that is written when a task finishes.
+    fn clear_finished(&mut self, include_failed: bool) {
+        let ids: Vec<TaskId> = self
+            .downloads
+            .iter()
+            .filter(|t| match t.status {
+                DownloadStatus::Done => true,
+                DownloadStatus::Failed(_) => include_failed,
+                DownloadStatus::Queued
+                | DownloadStatus::Downloading
+                | DownloadStatus::PostProcessing
+                | DownloadStatus::Paused
+                | DownloadStatus::Scheduled(_) => false,
+            })
+            .map(|t| t.id)
+            .collect();
+        self.trash_tasks(&ids);
+    }
+
+    /// Removes tasks the user asked to remove, keeping them aside for Undo. Active
+    /// downloads have been stopped by then and come back paused.
+    fn trash_tasks(&mut self, ids: &[TaskId]) {
+        let mut tasks = Vec::new();
+        let mut kept = Vec::new();
+        for (index, task) in std::mem::take(&mut self.downloads).into_iter().enumerate() {
+            if ids.contains(&task.id) {
+                tasks.push((index, task));
+            } else {
+                kept.push(task);
+            }
+        }
+        self.downloads = kept;
+        if tasks.is_empty() {
+            return;
+        }
+        for (_, task) in &mut tasks {
+            if task.status.is_running() || matches!(task.status, DownloadStatus::Queued) {
+                task.status = DownloadStatus::Paused;
+                task.speed = None;
+            }
+        }
+        let (playlists, emptied): (Vec<Playlist>, Vec<Playlist>) = std::mem::take(&mut self.playlists)
+            .into_iter()
+            .partition(|p| self.downloads.iter().any(|t| t.playlist == Some(p.id)));
+        self.playlists = playlists;
+
+        let text = match tasks.as_slice() {
+            [(_, task)] => t!("undo.removed_one", title = task.title),
+            _ => t!("undo.removed_many", count = tasks.len()),
+        };
+        let toast = self.toasts.with_action(text, t!("undo.undo"), UNDO_WINDOW);
+        self.trash.push(Trashed { toast, expires: Instant::now() + UNDO_WINDOW, tasks, playlists: emptied });
+        self.remove_tasks(ids);
+    }
+
+    /// Puts the tasks of an Undo toast back where they were.
+    fn undo_removal(&mut self, toast: u64) {
+        let Some(position) = self.trash.iter().position(|t| t.toast == toast) else { return };
+        let trashed = self.trash.remove(position);
+        self.playlists.extend(trashed.playlists);
+        // In ascending index order, so each lands where it was among the others
+        for (index, task) in trashed.tasks {
+            let (id, status) = (task.id, task.status.clone());
+            self.downloads.insert(index.min(self.downloads.len()), task);
+            // Its timer went off while it was gone
+            if let DownloadStatus::Scheduled(at) = status
+                && at <= Local::now()
+            {
+                self.start_scheduled(id);
+            }
+        }
+    }
+
+    /// Drops removed tasks whose Undo has timed out, along with their thumbnails.
+    fn empty_trash(&mut self) {
+        let before = self.trash.len();
+        let now = Instant::now();
+        self.trash.retain(|t| t.expires > now);
+        if self.trash.len() < before {
+            self.purge_orphaned_thumbnails();
+        }
+    }
+
+    /// Drops textures and retry state of video ids no task references anymore,
+    /// including the tasks waiting in the trash.
+    fn purge_orphaned_thumbnails(&mut self) {
+        let live: HashSet<&str> = self
+            .downloads
+            .iter()
+            .chain(self.trash.iter().flat_map(|t| t.tasks.iter().map(|(_, task)| task)))
+            .map(|t| t.video_id.as_str())
+            .collect();
+        self.thumbnails.retain(|id, _| live.contains(id.as_str()));
+        self.thumbnail_retries.retain(|id, _| live.contains(id.as_str()));
+    }
+
+    /// Records the window placement for the next launch. On the first frame a placement
+    /// restored onto a monitor that is gone or smaller now is pulled back into view.
+    ///
+    /// egui only knows the current monitor's size, not its position, so this assumes
+    /// the window should be within (0, 0) and that size.
+    fn track_window(&mut self, ctx: &egui::Context) {
+        let (inner, outer, monitor, minimized, maximized) = ctx.input(|i| {
+            let v = i.viewport();
+            (v.inner_rect, v.outer_rect, v.monitor_size, v.minimized, v.maximized)
+        });
+        let (Some(inner), Some(outer)) = (inner, outer) else { return };
+
+        if !self.window_checked {
+            self.window_checked = true;
+            if let Some(monitor) = monitor {
+                let size = inner.size().min(monitor);
+                let pos = outer.min.clamp(egui::Pos2::ZERO, (monitor - size).to_pos2().max(egui::Pos2::ZERO));
+                if size != inner.size() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+                }
+                if pos != outer.min {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
+                }
+                return;
+            }
+        }
+
+        // Keep the last normal placement so un-maximizing next session restores it
+        if minimized == Some(true) {
+            return;
+        }
+        let maximized = maximized == Some(true);
+        let window = match (maximized, self.settings.window) {
+            (true, Some(w)) => settings::WindowGeometry { maximized, ..w },
+            _ => settings::WindowGeometry {
+                x: outer.min.x,
+                y: outer.min.y,
+                width: inner.width(),
+                height: inner.height(),
+                maximized,
+            },
+        };
+        self.settings.window = Some(window);
+    }
+
+    /// Number of tasks whose status passes `include` and their mean progress, `None` if there are none.
+    fn mean_progress(&self, include: impl Fn(&DownloadStatus) -> bool) -> Option<(usize, f32)> {
+        let progress: Vec<f32> = self.downloads.iter().filter(|t| include(&t.status)).map(|t| t.progress).collect();
+        (!progress.is_empty()).then(|| (progress.len(), progress.iter().sum::<f32>() / progress.len() as f32))
+    }
+
+    /// Mirrors the title's aggregate progress on the taskbar button: red while a failed
+    /// task is in the list, yellow once everything left is paused.
+    #[cfg(windows)]
+    fn update_taskbar(&mut self, frame: &Frame) {
+        use taskbar::TaskbarProgress;
+        if !self.taskbar_started {
+            self.taskbar_started = true;
+            self.taskbar = taskbar::Taskbar::new(frame);
+        }
+        let failed = self.downloads.iter().any(|t| matches!(t.status, DownloadStatus::Failed(_)));
+        let state = match self.mean_progress(|s| s.is_running() || matches!(s, DownloadStatus::Queued)) {
+            Some((_, progress)) if failed => TaskbarProgress::Error(progress),
+            Some((_, progress)) => TaskbarProgress::Normal(progress),
+            None => match self.mean_progress(|s| matches!(s, DownloadStatus::Paused)) {
+                Some((_, progress)) => TaskbarProgress::Paused(progress),
+                None => TaskbarProgress::None,
+            },
+        };
+        if let Some(taskbar) = self.taskbar.as_mut() {
+            taskbar.set(state);
+        }
+    }
+
+    /// Shows the number of active downloads and their mean progress in the window title,
+    /// e.g. "YouTube Downloader — 3 active · 62%", or just the app name when idle.
+    fn update_window_title(&mut self, ctx: &egui::Context) {
+        let title = match self.mean_progress(|s| s.is_running() || matches!(s, DownloadStatus::Queued)) {
+            None => t!("app.title"),
+            Some((count, progress)) => {
+                // Whole percents so the title isn't rewritten for every progress line
+                let percent = (progress * 100.0).floor();
+                t!("title.progress", app = t!("app.title"), count = count, percent = percent)
+            }
+        };
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+    }
+
+    /// Switches visuals when the theme setting or, for "Follow system", the OS theme changed.
+    /// eframe also resets the visuals itself when the OS theme changes, which this undoes.
+    fn apply_theme(&self, ctx: &egui::Context, system: Option<eframe::Theme>) {
+        let visuals = self.settings.theme.visuals(system);
+        if ctx.style().visuals.dark_mode != visuals.dark_mode {
+            ctx.set_visuals(visuals);
+        }
+    }
+
+    /// Keeps egui's zoom and the UI scale setting in step. Ctrl+= / Ctrl+- / Ctrl+0 are
+    /// egui's built-in zoom shortcuts, which change the zoom before `update` runs.
+    fn apply_zoom(&mut self, ctx: &egui::Context) {
+        let current = ctx.zoom_factor();
+        if current != self.applied_zoom {
+            self.settings.ui_scale = current.clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end());
+        }
+        if self.settings.ui_scale != current {
+            ctx.set_zoom_factor(self.settings.ui_scale);
+        }
+        self.applied_zoom = self.settings.ui_scale;
+    }
+
+    /// Reloads the string catalog when the language setting changed.
+    fn apply_language(&mut self) {
+        if self.language != self.settings.language {
+            i18n::set_language(self.settings.language.as_deref());
+            self.language = self.settings.language.clone();
+        }
+    }
+
+    /// Writes the settings to disk once they have changed and settled.
+    fn persist_settings(&mut self, force: bool) {
+        if self.settings == self.saved_settings {
+            self.settings_dirty_since = None;
+            return;
+        }
+        let since = *self.settings_dirty_since.get_or_insert_with(Instant::now);
+        if force || since.elapsed() >= SETTINGS_SAVE_DELAY {
+            if let Err(e) = self.overrides.saved_form(&self.settings).save() {
+                app_log::error(format!("Failed to save settings: {}", e));
+            }
+            if self.settings.thumbnail_cache_mb != self.saved_settings.thumbnail_cache_mb
+                && let Some(cache) = cache::THUMBNAIL_CACHE.as_ref()
+            {
+                cache.set_max_bytes(self.settings.thumbnail_cache_mb * 1024 * 1024);
+            }
+            self.saved_settings = self.settings.clone();
+            self.settings_dirty_since = None;
+        }
+    }
+
+    /// Writes the unfinished tasks to the session file when one was added, removed or
+    /// changed state. Progress alone only gets saved along with such a change or on exit.
+    fn persist_session(&mut self, force: bool) {
+        // Don't overwrite the previous run's tasks before the user decided about them
+        if self.restore_prompt.is_some() {
+            return;
+        }
+        let tasks = self
+            .downloads
+            .iter()
+            .filter_map(|t| {
+                let status = match t.status {
+                    DownloadStatus::Queued => SavedStatus::Queued,
+                    DownloadStatus::Downloading | DownloadStatus::PostProcessing => SavedStatus::Running,
+                    DownloadStatus::Paused => SavedStatus::Paused,
+                    DownloadStatus::Scheduled(at) => SavedStatus::Scheduled(at.timestamp()),
+                    DownloadStatus::Done | DownloadStatus::Failed(_) => return None,
+                };
+                Some(SavedTask {
+                    url: t.url.clone(),
+                    title: t.title.clone(),
+                    quality: t.quality,
+                    audio_format: t.audio_format,
+                    folder: t.folder.clone(),
+                    overwrite: t.overwrite,
+                    file_stem: t.file_stem.clone(),
+                    normalize: t.normalize,
+                    split_tracks: t.split_tracks,
+                    clip: t.clip,
+                    encode: t.encode.clone(),
+                    preset: t.preset.clone(),
+                    status,
+                    progress: t.progress,
+                })
+            })
+            .collect();
+        let session = Session { tasks, paused: self.queue_paused };
+        if session.same_state(&self.saved_session) && !force {
+            return;
+        }
+        if let Err(e) = session::save(&session) {
+            app_log::error(format!("Failed to save session: {}", e));
+        }
+        self.saved_session = session;
+    }
+
+    /// Re-creates the tasks of the last run: queued ones wait for their metadata as usual,
+    /// interrupted ones continue from their partial files.
+    fn restore_session(&mut self, ctx: &egui::Context, session: Session) {
+        self.queue_paused = session.paused;
+        for saved in session.tasks {
+            let Some(id) = self.start_download(ctx, saved.url, saved.quality, saved.folder) else { continue };
+            let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) else { continue };
+            task.title = saved.title;
+            task.audio_format = saved.audio_format;
+            task.overwrite = saved.overwrite;
+            task.file_stem = saved.file_stem;
+            task.normalize = saved.normalize;
+            task.split_tracks = saved.split_tracks;
+            task.clip = saved.clip;
+            task.encode = saved.encode;
+            task.preset = saved.preset;
+            task.progress = saved.progress;
+            match saved.status {
+                SavedStatus::Queued => {}
+                SavedStatus::Paused => task.status = DownloadStatus::Paused,
+                // Resumed only by "Resume all" while everything is paused
+                SavedStatus::Running if self.queue_paused => task.status = DownloadStatus::Paused,
+                SavedStatus::Running => self.launch_download(ctx, id, true),
+                SavedStatus::Scheduled(at) => {
+                    if let Some(at) = Local.timestamp_opt(at, 0).single() {
+                        self.schedule_task(ctx, id, at);
+                    }
+                }
+            }
+        }
+    }
+
+    fn set_thumbnail_state(&mut self, video_id: &str, state: ThumbnailState) {
+        for task in self.downloads.iter_mut().filter(|t| t.video_id == video_id) {
+            task.thumbnail = state;
+        }
+    }
+
+    /// Requests thumbnails for visible tasks that lack one: first fetches right away,
+    /// failed ones again once their cooldown has passed.
+    fn reconcile_thumbnails(&mut self, ctx: &egui::Context) {
+        for (video_id, retry) in self.thumbnail_retries.iter().filter(|(_, r)| r.in_flight) {
+            retry.wanted.store(self.visible_tasks.contains(video_id), Ordering::Relaxed);
+        }
+
+        let now = Instant::now();
+        let due: Vec<String> = self
+            .downloads
+            .iter()
+            .filter(|t| self.visible_tasks.contains(&t.video_id))
+            .filter(|t| !self.thumbnails.contains_key(&t.video_id))
+            .filter(|t| match self.thumbnail_retries.get(&t.video_id) {
+                None => true,
+                Some(r) => !r.in_flight && r.failures < THUMBNAIL_MAX_ROUNDS && now >= r.next_attempt,
+            })
+            .map(|t| t.video_id.clone())
+            .collect();
+        for id in due {
+            self.request_thumbnail(ctx, &id);
+        }
+        // Wake up for the next retry of a visible card's thumbnail
+        let next_retry = self
+            .downloads
+            .iter()
+            .filter(|t| self.visible_tasks.contains(&t.video_id) && !self.thumbnails.contains_key(&t.video_id))
+            .filter_map(|t| self.thumbnail_retries.get(&t.video_id))
+            .filter(|r| !r.in_flight && r.failures < THUMBNAIL_MAX_ROUNDS)
+            .map(|r| r.next_attempt)
+            .min();
+        if let Some(at) = next_retry {
+            ctx.request_repaint_after(at.saturating_duration_since(now));
+        }
+    }
+}
+
+impl App for MyApp {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut Frame) {
+        self.apply_theme(ctx, frame.info().system_theme);
+        self.apply_language();
+        self.apply_zoom(ctx);
+        self.track_window(ctx);
+        self.handle_dropped_files(ctx);
+        self.update_preview(ctx);
+        self.update_playback(ctx);
+        self.update_search(ctx);
+        self.update_subscriptions(ctx);
+        #[cfg(feature = "tray")]
+        self.update_tray(ctx);
+        self.handle_close_request(ctx);
+        let imported = self.import_results.locked().take();
+        if let Some(imported) = imported {
+            self.importing = false;
+            match imported {
+                Ok(lines) => self.apply_import(ctx, lines),
+                Err(e) => self.toasts.error(e),
+            }
+        }
+        let queue_import = self.queue_import_results.locked().take();
+        if let Some(import) = queue_import {
+            self.importing = false;
+            match import {
+                Ok(import) if import.tasks.len() > LARGE_QUEUE_IMPORT => self.pending_queue_import = Some(import),
+                Ok(import) => self.apply_queue_import(ctx, import),
+                Err(e) => self.toasts.error(e),
+            }
+        }
+        let exported = self.export_results.locked().take();
+        if let Some(exported) = exported {
+            self.exporting = false;
+            match exported {
+                Ok(path) => self.toasts.info(t!("export.done", path = path.display())),
+                Err(e) => self.toasts.error(e),
+            }
+        }
+        self.watch_clipboard(ctx);
+        self.fill_url_on_focus(ctx);
+        self.receive_links(ctx);
+        self.update_folder_watch(ctx);
+        self.receive_moves(ctx);
+        self.receive_gifs();
+        let playlists: Vec<_> = self.playlist_results.locked().drain(..).collect();
+        for (playlist, update) in playlists {
+            match update {
+                PlaylistUpdate::Entry(entry) => self.add_playlist_entry(ctx, playlist, entry),
+                PlaylistUpdate::Done(listed) => {
+                    self.loading_playlists.remove(&playlist);
+                    let queued = self.playlists.iter().any(|p| p.id == playlist);
+                    match listed {
+                        Err(e) => self.toasts.error(t!("playlist.failed", error = e)),
+                        Ok(()) if !queued => self.toasts.error(t!("playlist.empty")),
+                        Ok(()) => {}
+                    }
+                }
+            }
+        }
+
+        // Progress first, so a run that just finished ends from its last value. A closed
+        // channel still holds it, until the run's Finished event removes the receiver.
+        for (&id, rx) in &mut self.progress_rxs {
+            if !matches!(rx.has_changed(), Ok(false))
+                && let Some(task) = self.downloads.iter_mut().find(|t| t.id == id)
+            {
+                events::apply_progress(task, &mut self.session, *rx.borrow_and_update());
+            }
+        }
+        while let Ok((id, event)) = self.events.try_recv() {
+            if matches!(event, DownloadEvent::Finished(_)) {
+                self.cancel_txs.remove(&id);
+                self.progress_rxs.remove(&id);
+            }
+            match events::reduce(&mut self.downloads, &mut self.session, id, event) {
+                Effect::None => {}
+                Effect::Output(line) => app_log::output(id, line),
+                Effect::Warning(line) => app_log::task_event(id, Level::Warning, line),
+                Effect::Ended => self.finish_task(ctx, id),
+                Effect::Thumbnail { video_id, image } => self.apply_thumbnail(ctx, video_id, image),
+            }
+        }
+        self.update_window_title(ctx);
+        #[cfg(windows)]
+        self.update_taskbar(frame);
+
+        let due: Vec<TaskId> = self.due_schedules.locked().drain(..).collect();
+        for id in due {
+            // A task rescheduled to later has a timer of its own
+            let now = Local::now();
+            if self.downloads.iter().any(|t| t.id == id && matches!(t.status, DownloadStatus::Scheduled(at) if at <= now)) {
+                self.start_scheduled(id);
+            }
+        }
+
+        let fetched_meta: Vec<_> = self.metadata_results.locked().drain(..).collect();
+        for (id, meta) in fetched_meta {
+            self.metadata_pending.remove(&id);
+            if let Some(task) = self.downloads.iter_mut().find(|t| t.id == id) {
+                task.metadata_ready = true;
+            }
+            match meta {
+                Ok(meta) => self.apply_metadata(id, meta),
+                // yt-dlp will report the problem itself when the download runs
+                Err(e) => app_log::task_event(id, Level::Warning, format!("Metadata prefetch failed: {}", e)),
+            }
+        }
+        self.start_queued(ctx);
+        if !self.downloads.iter().any(|t| matches!(t.status, DownloadStatus::Queued)) {
+            self.batch_conflict_choice = None;
+        }
+
+        if self.placeholder.is_none() {
+            self.placeholder = thumbnail::placeholder_image(thumbnail::THUMBNAIL_SIZE)
+                .map(|img| ctx.load_texture("thumbnail_placeholder", img, TextureOptions::default()));
+        }
+
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                ui.menu_button(t!("menu.file"), |ui| {
+                    if ui.add_enabled(!self.importing, egui::Button::new(t!("menu.import_queue"))).clicked() {
+                        ui.close_menu();
+                        self.import_queue(ctx);
+                    }
+                    let can_export = !self.exporting && !self.history.entries.is_empty();
+                    if ui.add_enabled(can_export, egui::Button::new(t!("menu.export_history"))).clicked() {
+                        ui.close_menu();
+                        self.export_history(ctx);
+                    }
+                });
+                ui.menu_button(t!("menu.view"), |ui| {
+                    ui.checkbox(&mut self.show_logs, t!("logs.title"));
+                    let new = self.subscriptions.inbox.len();
+                    let label = if new > 0 { t!("subscriptions.menu_new", count = new) } else { t!("subscriptions.title") };
+                    ui.checkbox(&mut self.show_subscriptions, label);
+                    ui.checkbox(&mut self.show_rules, t!("rules.title"));
+                    ui.checkbox(&mut self.show_encode_profiles, t!("encode.profiles"));
+                    ui.checkbox(&mut self.show_presets, t!("preset.title"));
+                });
+                ui.menu_button(t!("menu.help"), |ui| {
+                    if ui.button(t!("about.title")).clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+            });
+        });
+
+        // Right-side download panel
+        let mut panel = egui::SidePanel::right("downloads_panel");
+        if let Some(width) = self.settings.panel_width {
+            panel = panel.default_width(width);
+        }
+        // Widths are in points, so zooming in shrinks the window in points; keep room for the form
+        panel = panel.max_width(ctx.screen_rect().width() * 0.6);
+        let panel = panel.show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(t!("panel.heading"));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.toggle_value(&mut self.settings.compact_list, t!("panel.compact"))
+                        .on_hover_text(t!("panel.compact_hint"));
+                });
+            });
+            ui.horizontal(|ui| {
+                let running = !self.cancel_txs.is_empty();
+                let paused = self.downloads.iter().any(|t| matches!(t.status, DownloadStatus::Paused));
+                if ui.add_enabled(!self.queue_paused || running, egui::Button::new(t!("panel.pause_all"))).clicked() {
+                    self.hold_queue();
+                }
+                if ui.add_enabled(self.queue_paused || paused, egui::Button::new(t!("panel.resume_all"))).clicked() {
+                    self.resume_all();
+                }
+            });
+            if let Some(summary) = self.queue_summary() {
+                ui.label(summary);
+            }
+            if self.queue_paused {
+                egui::Frame::none()
+                    .fill(ui.visuals().warn_fg_color.gamma_multiply(0.15))
+                    .stroke(egui::Stroke::new(1.0, ui.visuals().warn_fg_color))
+                    .rounding(ui.visuals().widgets.noninteractive.rounding)
+                    .inner_margin(egui::Margin::same(6.0))
+                    .show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        ui.colored_label(ui.visuals().warn_fg_color, t!("panel.paused_banner"));
+                    });
+            }
+            ui.horizontal(|ui| {
+                let finished = self
+                    .downloads
+                    .iter()
+                    .any(|t| matches!(t.status, DownloadStatus::Done | DownloadStatus::Failed(_)));
+                if ui.add_enabled(finished, egui::Button::new(t!("panel.clear_completed"))).clicked() {
+                    let failed = self.downloads.iter().any(|t| matches!(t.status, DownloadStatus::Failed(_)));
+                    if self.clear_failed_too && failed {
+                        self.confirm_clear = true;
+                    } else {
+                        self.clear_finished(self.clear_failed_too);
+                    }
+                }
+                ui.checkbox(&mut self.clear_failed_too, t!("panel.incl_failed"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("panel.filter"));
+                let filter = ui.text_edit_singleline(&mut self.downloads_query).on_hover_text(t!("panel.filter_hint"));
+                // Works from the URL field too, taking the focus over from it
+                if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::F)) {
+                    filter.request_focus();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.toggle_value(&mut self.status_filter.queued, t!("status.queued"));
+                ui.toggle_value(&mut self.status_filter.downloading, t!("status.downloading"));
+                ui.toggle_value(&mut self.status_filter.done, t!("status.done"));
+                ui.toggle_value(&mut self.status_filter.failed, t!("status.failed"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("panel.sort"));
+                egui::ComboBox::from_id_source("sort_order")
+                    .selected_text(self.settings.sort_order.label())
+                    .show_ui(ui, |ui| {
+                        for order in SortOrder::ALL {
+                            ui.selectable_value(&mut self.settings.sort_order, order, order.label());
+                        }
+                    });
+            });
+            self.handle_list_keys(ctx);
+            // Indices rather than references so the list can still be mutated below
+            let shown = self.shown_tasks();
+            ui.label(t!("panel.shown", shown = shown.len(), total = self.downloads.len()));
+            // Bulk actions only reach tasks that can be seen
+            let shown_ids: HashSet<TaskId> = shown.iter().map(|&i| self.downloads[i].id).collect();
+            self.selection.retain(|id| shown_ids.contains(id));
+            if !self.selection.is_empty() {
+                self.selection_bar(ui, ctx);
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false; 2])
+                .show_viewport(ui, |ui, viewport| {
+                    let scrolling = ui.rect_contains_pointer(ui.clip_rect())
+                        && ui.input(|i| i.raw_scroll_delta != egui::Vec2::ZERO || i.pointer.primary_down());
+                    if scrolling {
+                        self.list_scrolled_at = Some(Instant::now());
+                    }
+                    // Don't yank the list away from where the user is looking
+                    let mut pass = ListPass {
+                        actions: Vec::new(),
+                        visible: HashSet::new(),
+                        user_scrolling: self.list_scrolled_at.is_some_and(|at| at.elapsed() < USER_SCROLL_GRACE),
+                        compact: self.settings.compact_list,
+                        clicked: None,
+                        queue: queue::positions(&self.downloads),
+                    };
+
+                    // Runs of tasks from the same playlist (shown_tasks keeps them together)
+                    let groups: Vec<Vec<usize>> = shown
+                        .chunk_by(|&a, &b| {
+                            let playlist = |i: usize| self.downloads[i].playlist;
+                            playlist(a).is_some() && playlist(a) == playlist(b)
+                        })
+                        .map(<[usize]>::to_vec)
+                        .collect();
+                    let mut rows = Vec::new();
+                    for group in groups {
+                        match self.downloads[group[0]].playlist {
+                            Some(playlist) => {
+                                rows.push(ListRow::Header(playlist));
+                                if playlist_open(ctx, playlist) {
+                                    rows.extend(group.into_iter().map(|index| ListRow::Task { index, indented: true }));
+                                }
+                            }
+                            None => rows.push(ListRow::Task { index: group[0], indented: false }),
+                        }
+                    }
+
+                    // Only rows in (or near) the viewport are built; the rest become gaps
+                    // as tall as they were when last shown
+                    let wanted = viewport.expand2(egui::vec2(0.0, THUMBNAIL_LOOKAHEAD));
+                    let card_estimate = if pass.compact { LINE_ROW_GUESS } else { CARD_ROW_GUESS };
+                    let mut y = 0.0;
+                    self.rows_built = 0;
+                    for row in rows {
+                        let (key, estimate) = match row {
+                            ListRow::Header(playlist) => (playlist_header_id(playlist), LINE_ROW_GUESS),
+                            ListRow::Task { index, .. } => {
+                                (list_row_id(self.downloads[index].id, pass.compact), card_estimate)
+                            }
+                        };
+                        let height = self.row_heights.get(&key).copied().unwrap_or(estimate);
+                        // Cards about to be scrolled to are built wherever they are
+                        let target = match row {
+                            ListRow::Task { index, .. } => {
+                                let id = self.downloads[index].id;
+                                (self.scroll_to_selected && self.selected == Some(id))
+                                    || (self.reveal_new_task && self.new_task.is_some_and(|(new, _)| new == id))
+                            }
+                            ListRow::Header(_) => false,
+                        };
+                        if !target && (y + height < wanted.min.y || y > wanted.max.y) {
+                            ui.add_space(height);
+                            y += height;
+                            continue;
+                        }
+                        let start = ui.cursor().top();
+                        match row {
+                            ListRow::Header(playlist) => self.playlist_header(ui, ctx, playlist, &mut pass),
+                            ListRow::Task { index, indented: true } => {
+                                ui.indent(key, |ui| self.task_card(ui, ctx, index, &mut pass));
+                            }
+                            ListRow::Task { index, indented: false } => self.task_card(ui, ctx, index, &mut pass),
+                        }
+                        let built = ui.cursor().top() - start;
+                        self.row_heights.insert(key, built);
+                        self.rows_built += 1;
+                        y += built;
+                    }
+                    self.visible_tasks = pass.visible;
+                    if let Some((id, modifiers)) = pass.clicked {
+                        self.click_task(ctx, id, modifiers);
+                    }
+                    self.scroll_to_selected = false;
+                    // Also when the filter hides the new card, so it isn't revealed later by surprise
+                    self.reveal_new_task = false;
+
+                    self.run_card_actions(ctx, pass.actions);
+                });
+        });
+        self.settings.panel_width = Some(panel.response.rect.width().round());
+        self.reconcile_thumbnails(ctx);
+
+        let speed: f64 = self.downloads.iter().filter(|t| t.status.is_running()).filter_map(|t| t.speed).sum();
+        if self.downloads.iter().any(|t| matches!(t.status, DownloadStatus::Downloading)) {
+            self.bandwidth.sample(speed, Instant::now());
+        } else {
+            self.bandwidth.pause();
+        }
+        egui::TopBottomPanel::bottom("session_stats").show(ctx, |ui| {
+            if self.bandwidth.samples().len() > 0 {
+                bandwidth_graph(ui, &self.bandwidth);
+            }
+            let shown = egui::CollapsingHeader::new(t!("stats.heading")).show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(t!("stats.completed", count = self.session.completed));
+                    ui.label(t!("stats.failed", count = self.session.failed));
+                    ui.label(t!("stats.downloaded", size = format_size(self.session.bytes)));
+                    ui.label(t!("stats.speed", speed = format_size(speed as u64)));
+                    ui.label(t!("stats.elapsed", time = format_duration(self.session.started.elapsed().as_secs())));
+                });
+            });
+            // Keep the elapsed time ticking while the numbers are visible
+            if shown.body_returned.is_some() {
+                ctx.request_repaint_after(Duration::from_secs(1));
+            }
+        });
+
+        // Main panel
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(t!("app.title"));
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.view, View::Downloads, t!("tab.download"));
+                ui.selectable_value(&mut self.view, View::Search, t!("tab.search"));
+                ui.selectable_value(&mut self.view, View::History, t!("tab.history"));
+            });
+            ui.separator();
+            match self.view {
+                View::Downloads => {}
+                View::Search => return self.search_view(ui, ctx),
+                View::History => return self.history_view(ui, ctx),
+            }
+
+            ui.label(t!("main.url_label"));
+            let url_field = ui.add(egui::TextEdit::singleline(&mut self.url_input).id(egui::Id::new("url_input")));
+            // Selected, so Enter starts it and typing replaces it
+            if std::mem::take(&mut self.select_url) {
+                let mut state = egui::text_edit::TextEditState::load(ctx, url_field.id).unwrap_or_default();
+                let all = egui::text::CCursorRange::two(
+                    egui::text::CCursor::new(0),
+                    egui::text::CCursor::new(self.url_input.chars().count()),
+                );
+                state.cursor.set_char_range(Some(all));
+                state.store(ctx, url_field.id);
+                url_field.request_focus();
+            }
+            // Enter makes a single-line edit lose focus, so this only fires for this field
+            let submitted = url_field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            ui.horizontal(|ui| overrides::locked_ui(ui, self.overrides.folder.as_ref(), |ui| {
+                ui.label(t!("main.folder_label"));
+                ui.text_edit_singleline(&mut self.settings.download_folder)
+                    .on_hover_text(&self.settings.download_folder);
+                ui.add_enabled_ui(!self.settings.recent_folders.is_empty(), |ui| {
+                    ui.menu_button("⏷", |ui| {
+                        let font = egui::TextStyle::Button.resolve(ui.style());
+                        for folder in self.settings.recent_folders.clone() {
+                            let shown = elide::elide(ui, &folder, &font, RECENT_FOLDER_WIDTH, Elide::Middle);
+                            let button = ui.button(shown.as_deref().unwrap_or(&folder));
+                            let button = if shown.is_some() { button.on_hover_text(&folder) } else { button };
+                            if button.clicked() {
+                                if Path::new(&folder).is_dir() {
+                                    self.settings.download_folder = folder;
+                                } else {
+                                    self.missing_folder = Some(folder);
+                                }
+                                ui.close_menu();
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text(t!("folder.recent"));
+                });
+                if ui.button(t!("button.browse")).clicked()
+                    && let Some(folder) =
+                        FileDialog::new().set_directory(&self.settings.download_folder).pick_folder()
+                {
+                    self.settings.download_folder = folder.display().to_string();
+                }
+            }));
+
+            ui.label(t!("main.quality_label"));
+            overrides::locked_ui(ui, self.overrides.quality.as_ref(), |ui| {
+                egui::ComboBox::from_label("")
+                    .selected_text(self.settings.quality.display_name())
+                    .show_ui(ui, |ui| {
+                        for q in Quality::ALL {
+                            ui.selectable_value(&mut self.settings.quality, q, q.display_name());
+                        }
+                    });
+            });
+            if self.settings.quality == Quality::AudioOnly {
+                ui.horizontal(|ui| {
+                    ui.label(t!("main.audio_format_label"));
+                    let name = |f: AudioFormat| {
+                        if f.reencodes() { t!("audio.reencodes", format = f.describe()) } else { f.describe() }
+                    };
+                    egui::ComboBox::from_id_source("audio_format")
+                        .selected_text(name(self.settings.audio_format))
+                        .show_ui(ui, |ui| {
+                            for f in AudioFormat::ALL {
+                                ui.selectable_value(&mut self.settings.audio_format, f, name(f));
+                            }
+                        });
+                });
+                ui.checkbox(&mut self.settings.normalize_audio, t!("loudnorm.normalize"))
+                    .on_hover_text(t!("loudnorm.normalize_hint"));
+                let chapters = match &self.preview {
+                    UrlPreview::Ready(_, Ok(meta)) => meta.chapters.as_ref().map_or(0, Vec::len),
+                    _ => 0,
+                };
+                ui.add_enabled(chapters > 0, egui::Checkbox::new(&mut self.split_tracks, t!("chapters.split")))
+                    .on_hover_text(t!("chapters.split_hint", count = chapters))
+                    .on_disabled_hover_text(t!("chapters.none"));
+                // Extracting audio runs ffmpeg even when nothing is re-encoded
+                if self.ffmpeg_missing() {
+                    ui.colored_label(ui.visuals().warn_fg_color, t!("audio.no_ffmpeg"));
+                }
+            } else {
+                ui.horizontal(|ui| {
+                    ui.label(t!("encode.label"));
+                    let selected = self.settings.selected_encode_profile().map_or_else(|| t!("encode.none"), |p| p.name.clone());
+                    egui::ComboBox::from_id_source("encode_profile").selected_text(selected).show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.settings.encode_profile, None, t!("encode.none"));
+                        for profile in &self.settings.encode_profiles {
+                            ui.selectable_value(&mut self.settings.encode_profile, Some(profile.name.clone()), &profile.name);
+                        }
+                    })
+                    .response
+                    .on_hover_text(t!("encode.hint"));
+                });
+                if self.settings.selected_encode_profile().is_some() && self.ffmpeg_missing() {
+                    ui.colored_label(ui.visuals().warn_fg_color, t!("encode.no_ffmpeg"));
+                }
+            }
+            let quality = self.form_preset().map_or(self.settings.quality, |p| p.quality);
+            let clip = self.form_clip(self.url_input.trim()).flatten();
+            let estimate = match &self.preview {
+                UrlPreview::Empty => None,
+                UrlPreview::Waiting(..) | UrlPreview::Fetching(_) => Some(t!("size.estimating")),
+                UrlPreview::Ready(_, Ok(meta)) => Some(match (meta.estimate_size(quality), clip.zip(meta.duration)) {
+                    (Some(bytes), Some((clip, duration))) => {
+                        let bytes = clip.scale_size(bytes, duration.round() as u64);
+                        t!("size.estimate_clip", size = format_size(bytes), quality = quality.display_name())
+                    }
+                    (Some(bytes), None) => t!("size.estimate", size = format_size(bytes), quality = quality.display_name()),
+                    (None, _) => t!("size.unknown"),
+                }),
+                UrlPreview::Ready(_, Err(_)) => Some(t!("size.unknown")),
+            };
+            if let Some(estimate) = estimate {
+                ui.small(estimate);
+            }
+            // Downloading a video again takes a click on the note, unless the file we
+            // have is of a lower quality than asked for
+            let mut held = false;
+            let previous = extract_video_id(self.url_input.trim()).and_then(|id| self.history.last_done(&id).cloned());
+            if let Some(entry) = previous {
+                held = entry.quality.covers(quality);
+                let (note, again) = if held {
+                    (t!("history.already", date = entry.date_string(), folder = entry.folder), t!("history.download_again"))
+                } else {
+                    (
+                        t!("history.already_lower", date = entry.date_string(), quality = entry.quality.display_name(), folder = entry.folder),
+                        t!("history.download_in", quality = quality.display_name()),
+                    )
+                };
+                let file = entry.file_path.clone().filter(|p| p.exists());
+                ui.colored_label(ui.visuals().warn_fg_color, note);
+                ui.horizontal(|ui| {
+                    if ui.button(again).clicked() {
+                        self.submit_url(ctx);
+                    }
+                    if ui.add_enabled(file.is_some(), egui::Button::new(t!("history.open_existing")))
+                        .on_disabled_hover_text(t!("history.file_gone"))
+                        .clicked()
+                        && let Some(file) = file
+                    {
+                        os_open::open_in_os(file);
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.settings.notifications, t!("settings.notify"));
+                ui.add_enabled(
+                    self.settings.notifications,
+                    egui::Checkbox::new(&mut self.settings.notify_only_unfocused, t!("settings.notify_unfocused")),
+                );
+            });
+            #[cfg(feature = "sound")]
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.settings.completion_sound, t!("sound.play"));
+                ui.add_enabled_ui(self.settings.completion_sound, |ui| {
+                    for when in SoundWhen::ALL {
+                        ui.radio_value(&mut self.settings.sound_when, when, when.label());
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut self.settings.sound_volume, 0.0..=1.0)
+                            .custom_formatter(|v, _| format!("{:.0}%", v * 100.0)),
+                    )
+                    .on_hover_text(t!("sound.volume"));
+                    if ui.button("🔊").on_hover_text(t!("sound.test")).clicked() {
+                        sound::play_done(self.settings.sound_volume);
+                    }
+                });
+            });
+            #[cfg(feature = "tray")]
+            ui.checkbox(&mut self.settings.close_to_tray, t!("settings.close_to_tray"));
+            ui.horizontal(|ui| {
+                ui.label(t!("after.label"));
+                egui::ComboBox::from_id_source("after_queue")
+                    .selected_text(self.after_queue.label())
+                    .show_ui(ui, |ui| {
+                        for action in AfterQueue::ALL {
+                            ui.selectable_value(&mut self.after_queue, action, action.label());
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("settings.exit_with_active"));
+                egui::ComboBox::from_id_source("exit_with_active")
+                    .selected_text(self.settings.exit_with_active.label())
+                    .show_ui(ui, |ui| {
+                        for action in ExitAction::ALL {
+                            if action != ExitAction::KeepRunning || self.tray_available() {
+                                ui.selectable_value(&mut self.settings.exit_with_active, action, action.label());
+                            }
+                        }
+                    });
+            });
+            ui.checkbox(&mut self.settings.fill_url_on_focus, t!("settings.fill_url_on_focus"))
+                .on_hover_text(t!("settings.fill_url_on_focus_hint"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.settings.watch_clipboard, t!("settings.watch_clipboard"));
+                ui.add_enabled_ui(self.settings.watch_clipboard, |ui| {
+                    ui.radio_value(&mut self.settings.clipboard_action, ClipboardAction::Prompt, t!("settings.clipboard_ask"));
+                    ui.radio_value(&mut self.settings.clipboard_action, ClipboardAction::AutoQueue, t!("settings.clipboard_auto"));
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.settings.watch_folder_enabled, t!("watch.enable"))
+                    .on_hover_text(t!("watch.hint", processed = watch_folder::PROCESSED));
+                ui.add_enabled_ui(self.settings.watch_folder_enabled, |ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.settings.watch_folder).desired_width(140.0));
+                    if ui.button("…").on_hover_text(t!("button.browse")).clicked()
+                        && let Some(folder) = FileDialog::new().pick_folder()
+                    {
+                        self.settings.watch_folder = folder.display().to_string();
+                    }
+                    let selected = self.settings.watch_preset.clone().unwrap_or_else(|| t!("preset.none"));
+                    egui::ComboBox::from_id_source("watch_preset").selected_text(selected).show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.settings.watch_preset, None, t!("preset.none"));
+                        for preset in &self.settings.presets {
+                            ui.selectable_value(&mut self.settings.watch_preset, Some(preset.name.clone()), &preset.name);
+                        }
+                    })
+                    .response
+                    .on_hover_text(t!("watch.preset_hint"));
+                });
+            });
+            let handler = ui
+                .checkbox(&mut self.settings.protocol_handler, t!("settings.protocol_handler", scheme = protocol::SCHEME))
+                .on_hover_text(t!("settings.protocol_hint", scheme = protocol::SCHEME));
+            if handler.changed() {
+                self.toggle_protocol_handler();
+            }
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.schedule_enabled, t!("schedule.start_at"));
+                ui.add_enabled(
+                    self.schedule_enabled,
+                    egui::TextEdit::singleline(&mut self.schedule_input).desired_width(120.0).hint_text("23:30"),
+                )
+                .on_hover_text(t!("schedule.hint"));
+            });
+            // Needs the length from the preview to check the times against
+            let duration = self.preview_duration(self.url_input.trim());
+            ui.horizontal(|ui| {
+                ui.add_enabled(duration.is_some(), egui::Checkbox::new(&mut self.clip_enabled, t!("clip.label")))
+                    .on_disabled_hover_text(t!("clip.needs_duration"));
+                ui.add_enabled_ui(self.clip_enabled && duration.is_some(), |ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.clip_start).desired_width(60.0).hint_text("0:00"));
+                    ui.label("–");
+                    let end_hint = duration.map(format_duration).unwrap_or_default();
+                    ui.add(egui::TextEdit::singleline(&mut self.clip_end).desired_width(60.0).hint_text(end_hint));
+                });
+            });
+            if let Some(duration) = duration
+                && self.form_clip(self.url_input.trim()) == Some(None)
+            {
+                ui.colored_label(ui.visuals().error_fg_color, t!("clip.out_of_range", duration = format_duration(duration)));
+            }
+            ui.horizontal(|ui| {
+                let download = ui
+                    .add_enabled(!held, egui::Button::new(t!("button.download")))
+                    .on_disabled_hover_text(t!("history.already_hint"));
+                if download.clicked() || (submitted && !held) {
+                    self.submit_url(ctx);
+                }
+                let selected = self.form_preset().map_or_else(|| t!("preset.none"), |p| p.name.clone());
+                egui::ComboBox::from_id_source("form_preset").selected_text(selected).show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.form_preset, None, t!("preset.none"));
+                    for preset in &self.settings.presets {
+                        ui.selectable_value(&mut self.form_preset, Some(preset.name.clone()), &preset.name)
+                            .on_hover_text(preset.summary());
+                    }
+                })
+                .response
+                .on_hover_text(t!("preset.hint"));
+                if ui.add_enabled(!self.importing, egui::Button::new(t!("button.import_list"))).clicked() {
+                    self.import_list(ctx);
+                }
+                if self.playback.is_some() {
+                    if ui.button(t!("play.stop")).clicked() {
+                        self.playback = None;
+                    }
+                } else {
+                    let playable = self.player.is_some() && extract_video_id(self.url_input.trim()).is_some();
+                    let button = ui
+                        .add_enabled(playable, egui::Button::new(t!("play.button")))
+                        .on_hover_text(t!("play.hint"))
+                        .on_disabled_hover_text(if self.player.is_some() { t!("play.no_url") } else { t!("play.no_player") });
+                    if button.clicked() {
+                        self.play_url();
+                    }
+                }
+            });
+            if let Some(preset) = self.form_preset() {
+                ui.weak(t!("preset.active", summary = preset.summary()));
+            }
+
+            ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
+                ui.horizontal(|ui| {
+                    if ui.small_button(t!("button.reset_defaults")).clicked() {
+                        self.settings = Settings::default();
+                        self.overrides.apply(&mut self.settings);
+                        self.player = player::find(&self.settings.player);
+                    }
+                    if ui.small_button(t!("wizard.open")).clicked() {
+                        self.wizard = Some(WizardStep::Folder);
+                    }
+                });
+                ui.horizontal(|ui| overrides::locked_ui(ui, self.overrides.max_parallel.as_ref(), |ui| {
+                    ui.label(t!("settings.max_parallel"));
+                    ui.add(
+                        egui::DragValue::new(&mut self.settings.max_parallel)
+                            .clamp_range(0..=MAX_PARALLEL)
+                            .custom_formatter(|v, _| if v == 0.0 { t!("settings.no_limit") } else { v.to_string() }),
+                    )
+                    .on_hover_text(t!("settings.max_parallel_hint"));
+                }));
+                ui.horizontal(|ui| {
+                    ui.label(t!("settings.player"));
+                    let edit = ui
+                        .add(
+                            egui::TextEdit::singleline(&mut self.settings.player)
+                                .desired_width(160.0)
+                                .hint_text(t!("settings.player_auto")),
+                        )
+                        .on_hover_text(t!("settings.player_hint"));
+                    if edit.changed() {
+                        self.player = player::find(&self.settings.player);
+                    }
+                });
+                ui.checkbox(&mut self.settings.delete_permanently, t!("settings.delete_permanently"))
+                    .on_hover_text(t!("settings.delete_permanently_hint"));
+                ui.checkbox(&mut self.settings.normalize_audio, t!("loudnorm.normalize"))
+                    .on_hover_text(t!("loudnorm.normalize_hint"));
+                ui.add_enabled(
+                    self.settings.normalize_audio,
+                    egui::Checkbox::new(&mut self.settings.keep_original_audio, t!("settings.keep_original_audio")),
+                )
+                .on_hover_text(t!("settings.keep_original_audio_hint"));
+                ui.horizontal(|ui| {
+                    ui.label(t!("settings.ui_scale"));
+                    ui.add(
+                        egui::Slider::new(&mut self.settings.ui_scale, UI_SCALE_RANGE)
+                            .step_by(0.1)
+                            .custom_formatter(|v, _| format!("{:.0}%", v * 100.0)),
+                    )
+                    .on_hover_text(t!("settings.ui_scale_hint"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label(t!("settings.theme"));
+                    egui::ComboBox::from_id_source("theme")
+                        .selected_text(self.settings.theme.label())
+                        .show_ui(ui, |ui| {
+                            for theme in ThemePreference::ALL {
+                                ui.selectable_value(&mut self.settings.theme, theme, theme.label());
+                            }
+                        });
+                });
+                let mut colorblind = self.settings.status_palette == Palette::ColorBlind;
+                if ui.checkbox(&mut colorblind, t!("settings.colorblind")).on_hover_text(t!("settings.colorblind_hint")).changed() {
+                    self.settings.status_palette = if colorblind { Palette::ColorBlind } else { Palette::Standard };
+                }
+                ui.horizontal(|ui| {
+                    ui.label(t!("settings.language"));
+                    let languages = i18n::available();
+                    let selected = match &self.settings.language {
+                        Some(code) => languages.iter().find(|(c, _)| c == code).map_or(code.clone(), |(_, name)| name.clone()),
+                        None => t!("language.system"),
+                    };
+                    egui::ComboBox::from_id_source("language").selected_text(selected).show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.settings.language, None, t!("language.system"));
+                        for (code, name) in languages {
+                            ui.selectable_value(&mut self.settings.language, Some(code), name);
+                        }
+                    });
+                });
+            });
+        });
+
+        self.persist_settings(false);
+        // Nothing else may wake the window to write a change once it has settled
+        if let Some(since) = self.settings_dirty_since {
+            ctx.request_repaint_after(SETTINGS_SAVE_DELAY.saturating_sub(since.elapsed()));
+        }
+        self.persist_session(false);
+        for toast in self.toasts.show(ctx) {
+            self.undo_removal(toast);
+        }
+        self.empty_trash();
+
+        if self.confirm_clear {
+            let failed = self.downloads.iter().filter(|t| matches!(t.status, DownloadStatus::Failed(_))).count();
+            egui::Window::new(t!("clear.title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(t!("clear.confirm", count = failed));
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("button.clear")).clicked() {
+                            self.clear_finished(true);
+                            self.confirm_clear = false;
+                        }
+                        if ui.button(t!("button.cancel")).clicked() {
+                            self.confirm_clear = false;
+                        }
+                    });
+                });
+        }
+
+        if let Some(id) = self.confirm_delete {
+            let title = self.downloads.iter().find(|t| t.id == id).map(|t| t.title.clone());
+            match title {
+                // Finished or removed some other way meanwhile
+                None => self.confirm_delete = None,
+                Some(title) => {
+                    egui::Window::new(t!("delete.title"))
+                        .collapsible(false)
+                        .resizable(false)
+                        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                        .show(ctx, |ui| {
+                            ui.label(t!("delete.message", title = title));
+                            ui.horizontal(|ui| {
+                                if ui.button(t!("delete.confirm")).clicked() {
+                                    self.cancel_task(id);
+                                    self.confirm_delete = None;
+                                }
+                                if ui.button(t!("delete.keep")).clicked() {
+                                    self.confirm_delete = None;
+                                }
+                            });
+                        });
+                }
+            }
+        }
+
+        if let Some(file) = self.confirm_delete_file.clone() {
+            egui::Window::new(t!("delete_file.title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(if self.settings.delete_permanently {
+                        t!("delete_file.permanent", title = file.title)
+                    } else {
+                        t!("delete_file.trash", title = file.title)
+                    });
+                    elide::label(ui, &file.path.display().to_string(), egui::TextStyle::Small, Elide::Middle);
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("button.delete_file")).clicked() {
+                            self.confirm_delete_file = None;
+                            self.delete_file(file);
+                        } else if ui.button(t!("button.cancel")).clicked() {
+                            self.confirm_delete_file = None;
+                        }
+                    });
+                });
+        }
+
+        if let Some((id, path)) = self.conflicts.front().cloned() {
+            let mut choice = None;
+            let batch = self.conflicts.len() > 1
+                || self.downloads.iter().any(|t| t.id != id && matches!(t.status, DownloadStatus::Queued));
+            egui::Window::new(t!("conflict.title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+                    ui.label(t!("conflict.message", name = name));
+                    if batch {
+                        ui.checkbox(&mut self.conflict_apply_all, t!("conflict.apply_all"));
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("button.overwrite")).clicked() {
+                            choice = Some(ConflictChoice::Overwrite);
+                        }
+                        if ui.button(t!("button.rename")).clicked() {
+                            choice = Some(ConflictChoice::Rename);
+                        }
+                        if ui.button(t!("button.cancel")).clicked() {
+                            choice = Some(ConflictChoice::Cancel);
+                        }
+                    });
+                });
+            if let Some(choice) = choice {
+                let ids: Vec<TaskId> = if batch && self.conflict_apply_all {
+                    self.batch_conflict_choice = Some(choice);
+                    self.conflicts.drain(..).map(|(id, _)| id).collect()
+                } else {
+                    self.conflicts.pop_front().map(|(id, _)| id).into_iter().collect()
+                };
+                for id in ids {
+                    self.resolve_conflict(ctx, id, choice);
+                }
+            }
+        }
+
+        if let Some(summary) = &self.import_summary {
+            let mut open = true;
+            let mut download_anyway = false;
+            egui::Window::new(t!("import.title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(t!("import.queued", count = summary.queued));
+                    ui.label(t!("import.duplicates", count = summary.duplicates));
+                    if !summary.downloaded.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label(t!("import.downloaded", count = summary.downloaded.len()));
+                            download_anyway = ui.button(t!("import.download_anyway")).clicked();
+                        });
+                    }
+                    ui.label(t!("import.invalid", count = summary.invalid.len()));
+                    if !summary.invalid.is_empty() {
+                        let lines: Vec<String> = summary.invalid.iter().map(|n| n.to_string()).collect();
+                        ui.label(t!("import.invalid_lines", lines = lines.join(", ")));
+                    }
+                    if ui.button(t!("button.ok")).clicked() {
+                        open = false;
+                    }
+                });
+            if download_anyway && let Some(mut summary) = self.import_summary.take() {
+                for url in std::mem::take(&mut summary.downloaded) {
+                    let quality = self.settings.quality;
+                    let folder = self.settings.download_folder.clone();
+                    if self.start_download(ctx, url, quality, folder).is_some() {
+                        summary.queued += 1;
+                    }
+                }
+                self.import_summary = Some(summary);
+            }
+            if !open {
+                self.import_summary = None;
+            }
+        }
+
+        if let Some(import) = &self.pending_queue_import {
+            let count = import.tasks.len() as u64;
+            let mut answer = None;
+            egui::Window::new(t!("queue_import.title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(t!("queue_import.confirm", count = format_grouped(count)));
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("queue_import.import")).clicked() {
+                            answer = Some(true);
+                        }
+                        if ui.button(t!("button.cancel")).clicked() {
+                            answer = Some(false);
+                        }
+                    });
+                });
+            if let Some(import) = answer.and_then(|yes| self.pending_queue_import.take().filter(|_| yes)) {
+                self.apply_queue_import(ctx, import);
+            }
+        }
+
+        if let Some(summary) = &self.queue_import_summary {
+            let mut open = true;
+            egui::Window::new(t!("import.title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(t!("import.queued", count = summary.queued));
+                    if !summary.errors.is_empty() {
+                        ui.label(t!("queue_import.skipped", count = summary.errors.len()));
+                        egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                            for (entry, error) in &summary.errors {
+                                ui.label(t!("queue_import.entry_error", entry = entry, error = error));
+                            }
+                        });
+                    }
+                    if ui.button(t!("button.ok")).clicked() {
+                        open = false;
+                    }
+                });
+            if !open {
+                self.queue_import_summary = None;
+            }
+        }
+
+        if let Some(url) = self.clipboard_prompt.clone() {
+            egui::Window::new(t!("clipboard.prompt_title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::RIGHT_TOP, [-8.0, 8.0])
+                .show(ctx, |ui| {
+                    ui.label(&url);
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("button.download")).clicked() {
+                            let quality = self.settings.quality;
+                            let folder = self.settings.download_folder.clone();
+                            self.start_download(ctx, url.clone(), quality, folder);
+                            self.clipboard_prompt = None;
+                        }
+                        if ui.button(t!("button.dismiss")).clicked() {
+                            self.clipboard_prompt = None;
+                        }
+                    });
+                });
+        }
+
+        if let Some(session) = self.restore_prompt.clone() {
+            egui::Window::new(t!("restore.title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(t!("restore.message", count = session.tasks.len()));
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("restore.restore")).clicked() {
+                            self.restore_prompt = None;
+                            self.restore_session(ctx, session);
+                        }
+                        if ui.button(t!("restore.discard")).clicked() {
+                            self.restore_prompt = None;
+                        }
+                    });
+                });
+        }
+
+        if let Some(active) = self.exit_prompt {
+            egui::Window::new(t!("exit.title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(t!("exit.active", count = active));
+                    ui.checkbox(&mut self.exit_remember, t!("exit.remember"));
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("exit.quit")).clicked() {
+                            if self.exit_remember {
+                                self.settings.exit_with_active = ExitAction::Quit;
+                            }
+                            // on_exit kills the yt-dlp processes
+                            self.exit_confirmed = true;
+                            self.exit_prompt = None;
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                        if self.tray_available() && ui.button(t!("exit.keep_running")).clicked() {
+                            if self.exit_remember {
+                                self.settings.exit_with_active = ExitAction::KeepRunning;
+                            }
+                            self.exit_prompt = None;
+                            self.hide_to_tray(ctx);
+                        }
+                        if ui.button(t!("exit.dont_quit")).clicked() {
+                            self.exit_prompt = None;
+                        }
+                    });
+                });
+        }
+
+        if let Some(folder) = self.missing_folder.clone() {
+            egui::Window::new(t!("folder.missing_title"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(t!("folder.missing", folder = folder));
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("menu.remove")).clicked() {
+                            self.settings.recent_folders.retain(|f| *f != folder);
+                            self.missing_folder = None;
+                        }
+                        if ui.button(t!("folder.keep")).clicked() {
+                            self.missing_folder = None;
+                        }
+                    });
+                });
+        }
+
+        self.tag_editor_window(ctx);
+        self.gif_window(ctx);
+        self.setup_wizard(ctx);
+        self.reschedule_window(ctx);
+        self.check_queue_finished(ctx);
+        if self.show_logs {
+            self.logs_window(ctx);
+        }
+        if self.show_about {
+            self.about_window(ctx);
+        }
+        if self.show_subscriptions {
+            self.subscriptions_window(ctx);
+        }
+        if self.show_rules {
+            self.rules_window(ctx);
+        }
+        if self.show_encode_profiles {
+            self.encode_profiles_window(ctx);
+        }
+        if self.show_presets {
+            self.presets_window(ctx);
+        }
+
+        // F12 toggles a small overlay with resource counters
+        if ctx.input(|i| i.key_pressed(egui::Key::F12)) {
+            self.show_debug = !self.show_debug;
+        }
+        if let Some(cpu) = frame.info().cpu_usage {
+            self.frame_time = self.frame_time * 0.9 + cpu * 1000.0 * 0.1;
+        }
+        if self.show_debug {
+            egui::Area::new(egui::Id::new("debug_overlay"))
+                .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+                .show(ctx, |ui| {
+                    ui.label(t!("debug.textures", count = self.thumbnails.len()));
+                    ui.label(t!("debug.tasks", count = self.downloads.len()));
+                    ui.label(t!("debug.frame_time", ms = format!("{:.1}", self.frame_time)));
+                    ui.label(t!("debug.rows_built", count = self.rows_built));
+                    ui.label(t!("debug.frames", count = self.frames));
+                });
+        }
+
+        // Running downloads and GIF conversions send progress through channels that don't
+        // wake the UI, and countdowns need to tick. Everything else repaints on input or is
+        // woken by the background job that finished, so an idle window doesn't redraw at all.
+        if self.downloads.iter().any(|t| t.status.is_running()) || self.gif_jobs.iter().any(|j| j.result.is_none()) {
+            ctx.request_repaint_after(PROGRESS_POLL);
+        } else if self.after_queue_at.is_some()
+            || self.downloads.iter().any(|t| matches!(t.status, DownloadStatus::Scheduled(_)))
+        {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+        self.frames += 1;
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Before the processes are killed, so interrupted downloads are saved as running
+        self.persist_session(true);
+        self.shutdown_downloads();
+        self.persist_settings(true);
+    }
+}
+
+/// Right-click menu of a task card, with entries the task's state doesn't allow disabled.
+/// `queue` is the queued task's place in the queue and the queue's length, `gif_blocked`
+/// why "Convert to GIF…" can't be used right now, if so.
+fn card_menu(
+    ui: &mut egui::Ui,
+    task: &DownloadTask,
+    queue: Option<(usize, usize)>,
+    gif_blocked: Option<String>,
+    actions: &mut Vec<(TaskId, CardAction)>,
+) {
+    let running = task.status.is_running();
+    let done = matches!(task.status, DownloadStatus::Done);
+    let retryable = matches!(task.status, DownloadStatus::Failed(_) | DownloadStatus::Paused);
+    let scheduled = matches!(task.status, DownloadStatus::Scheduled(_));
+    let mut item = |ui: &mut egui::Ui, enabled: bool, text: String, action: CardAction| {
+        let response = ui.add_enabled(enabled, egui::Button::new(text));
+        if response.clicked() {
+            actions.push((task.id, action));
+            ui.close_menu();
+        }
+        response
+    };
+    item(ui, true, t!("button.copy_url"), CardAction::CopyUrl);
+    item(ui, task.file_path.is_some(), t!("menu.copy_path"), CardAction::CopyPath);
+    item(ui, task.command.is_some(), t!("menu.copy_command"), CardAction::CopyCommand);
+    ui.separator();
+    item(ui, true, t!("button.open_folder"), CardAction::OpenFolder);
+    let shown = task.file_path.is_some() && !task.file_deleted;
+    item(ui, shown, t!("button.show_in_folder"), CardAction::ShowInFolder);
+    let file_kept = done && task.file_path.is_some() && !task.file_deleted;
+    item(ui, file_kept, t!("button.open_file"), CardAction::OpenFile);
+    item(ui, task.attempts > 0, t!("menu.view_log"), CardAction::ViewLog);
+    item(ui, task.taggable(), t!("button.edit_tags"), CardAction::EditTags);
+    // Read when yt-dlp starts, so only changeable before that
+    let adjustable = task.quality == Quality::AudioOnly && !running && !done;
+    let normalize = if task.normalize { t!("loudnorm.turn_off") } else { t!("loudnorm.turn_on") };
+    item(ui, adjustable, normalize, CardAction::ToggleNormalize);
+    let split = if task.split_tracks { t!("chapters.turn_off") } else { t!("chapters.turn_on") };
+    let split = item(ui, adjustable && !task.chapters.is_empty(), split, CardAction::ToggleSplit);
+    if adjustable && task.chapters.is_empty() {
+        split.on_disabled_hover_text(t!("chapters.none"));
+    }
+    let video_kept = file_kept && task.quality != Quality::AudioOnly;
+    let convert = item(ui, video_kept && gif_blocked.is_none(), t!("gif.menu"), CardAction::ConvertGif);
+    if video_kept && let Some(reason) = gif_blocked {
+        convert.on_disabled_hover_text(reason);
+    }
+    ui.separator();
+    item(ui, retryable, t!("button.retry"), CardAction::Retry);
+    item(ui, scheduled, t!("schedule.start_now"), CardAction::StartNow);
+    item(ui, scheduled, t!("schedule.edit"), CardAction::Reschedule);
+    item(ui, running, t!("button.pause"), CardAction::Pause);
+    item(ui, task.file_missing || task.file_deleted, t!("button.redownload"), CardAction::Redownload);
+    item(ui, file_kept, t!("button.delete_file"), CardAction::DeleteFile);
+    item(ui, !done && !matches!(task.status, DownloadStatus::Failed(_)), t!("button.cancel"), CardAction::Cancel);
+    item(ui, !running, t!("menu.remove"), CardAction::Remove);
+    if let Some((position, last)) = queue {
+        ui.separator();
+        item(ui, position > 1, t!("queue.move_top"), CardAction::MoveToTop);
+        item(ui, position > 1, t!("queue.move_up"), CardAction::MoveUp);
+        item(ui, position < last, t!("queue.move_down"), CardAction::MoveDown);
+    }
+}
+
+/// A GIF conversion under its task's card: progress and Cancel while ffmpeg runs, then
+/// the GIF's name with buttons to open or show it.
+fn gif_row(ui: &mut egui::Ui, job: &GifJob, actions: &mut Vec<(TaskId, CardAction)>) {
+    ui.horizontal(|ui| {
+        ui.add_space(16.0);
+        ui.label("🎞");
+        match &job.result {
+            None => {
+                let progress = *job.progress.borrow();
+                ui.add(egui::ProgressBar::new(progress).desired_width(160.0).show_percentage());
+                if ui.small_button(t!("button.cancel")).clicked() {
+                    actions.push((job.task, CardAction::CancelGif(job.id)));
+                }
+            }
+            Some(Ok(path)) => {
+                let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                ui.add(egui::Label::new(t!("gif.done", name = name)).truncate(true));
+                if ui.small_button(t!("button.open_file")).clicked() {
+                    os_open::open_in_os(path);
+                }
+                if ui.small_button(t!("button.show_in_folder")).clicked() {
+                    os_open::reveal_in_os(path);
+                }
+                if ui.small_button("✖").on_hover_text(t!("gif.dismiss")).clicked() {
+                    actions.push((job.task, CardAction::DismissGif(job.id)));
+                }
+            }
+            Some(Err(e)) => {
+                ui.colored_label(ui.visuals().error_fg_color, t!("gif.failed_short")).on_hover_text(e);
+                if ui.small_button("✖").on_hover_text(t!("gif.dismiss")).clicked() {
+                    actions.push((job.task, CardAction::DismissGif(job.id)));
+                }
+            }
+        }
+    });
+}
+
+fn playlist_header_id(playlist: PlaylistId) -> egui::Id {
+    egui::Id::new(("playlist", playlist))
+}
+
+/// Key of a task's row height, which differs between the full and compact list.
+fn list_row_id(task: TaskId, compact: bool) -> egui::Id {
+    egui::Id::new(("list_row", task, compact))
+}
+
+/// Whether a playlist's group is expanded, so keyboard selection skips hidden tasks.
+fn playlist_open(ctx: &egui::Context, playlist: PlaylistId) -> bool {
+    egui::collapsing_header::CollapsingState::load_with_default_open(ctx, playlist_header_id(playlist), true).is_open()
+}
+
+/// One line of the compact downloads list: status icon, title, slim progress bar and
+/// percentage. Everything else is in the context menu.
+fn compact_row(ui: &mut egui::Ui, task: &DownloadTask, icon: &str, color: egui::Color32, copied: bool) {
+    ui.horizontal(|ui| {
+        let status = if task.status.is_running() {
+            ui.add(egui::Spinner::new().size(10.0).color(color))
+        } else {
+            ui.colored_label(color, icon)
+        };
+        if let DownloadStatus::Failed(e) = &task.status {
+            status.on_hover_text(e);
+        }
+        // Right to left so the title gets whatever width is left over
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            ui.label(format!("{:.0}%", task.progress * 100.0));
+            ui.add(egui::ProgressBar::new(task.progress).desired_width(60.0).desired_height(4.0).fill(color));
+            if copied {
+                ui.small(t!("card.copied"));
+            }
+            ui.add(egui::Label::new(&task.title).truncate(true));
+        });
+    });
+}
+
+/// Draws a task's thumbnail in a fixed-size slot so the card keeps its height
+/// while the image is loading, after it arrives, or when it failed.
+fn thumbnail_slot(
+    ui: &mut egui::Ui,
+    state: ThumbnailState,
+    texture: Option<&egui::TextureHandle>,
+    placeholder: Option<&egui::TextureHandle>,
+) -> egui::Response {
+    let size = egui::vec2(thumbnail::THUMBNAIL_SIZE[0] as f32, thumbnail::THUMBNAIL_SIZE[1] as f32);
+    match (state, texture, placeholder) {
+        (ThumbnailState::Loaded, Some(tex), _) => {
+            ui.add(egui::Image::new(tex).fit_to_exact_size(size).sense(egui::Sense::click()))
+        }
+        (ThumbnailState::Failed, _, Some(tex)) => ui
+            .add(
+                egui::Image::new(tex)
+                    .fit_to_exact_size(size)
+                    .tint(egui::Color32::from_rgb(255, 170, 170))
+                    .sense(egui::Sense::click()),
+            )
+            .on_hover_text(t!("thumb.unavailable")),
+        _ => {
+            // Skeleton box with a spinner while the fetch is running
+            let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+            ui.painter().rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+            let spinner = egui::Rect::from_center_size(rect.center(), egui::vec2(20.0, 20.0));
+            egui::Spinner::new().paint_at(ui, spinner);
+            response.on_hover_text(t!("thumb.loading"))
+        }
+    }
+}
+
+/// Draws the combined download speed of the last minutes as a filled line, newest on
+/// the right, scaled to the highest speed in it. Flat stretches at a steady rate show
+/// throttling or a speed limit at a glance.
+fn bandwidth_graph(ui: &mut egui::Ui, history: &BandwidthHistory) {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 36.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    let peak = history.peak();
+    let step = rect.width() / (model::BANDWIDTH_WINDOW - 1) as f32;
+    let start = rect.right() - step * (history.samples().len() - 1) as f32;
+    let points: Vec<egui::Pos2> = history
+        .samples()
+        .enumerate()
+        .map(|(i, speed)| {
+            let height = if peak > 0.0 { (speed / peak) as f32 } else { 0.0 };
+            egui::pos2(start + step * i as f32, rect.bottom() - 2.0 - height * (rect.height() - 4.0))
+        })
+        .collect();
+    let color = ui.visuals().selection.bg_fill;
+    for pair in points.windows(2) {
+        // One quad per step, as egui only fills convex shapes
+        let area = vec![pair[0], pair[1], egui::pos2(pair[1].x, rect.bottom()), egui::pos2(pair[0].x, rect.bottom())];
+        painter.add(egui::Shape::convex_polygon(area, color.gamma_multiply(0.3), egui::Stroke::NONE));
+    }
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+    painter.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        t!("bandwidth.peak", speed = format_size(peak as u64)),
+        egui::FontId::proportional(10.0),
+        ui.visuals().weak_text_color(),
+    );
+    let current = history.samples().last().unwrap_or_default();
+    response.on_hover_text(t!("bandwidth.hint", speed = format_size(current as u64), peak = format_size(peak as u64)));
+}
+
+/// Paints a small dark badge with `text` in the bottom-right corner of `rect`, red for live streams.
+fn paint_duration_badge(ui: &egui::Ui, rect: egui::Rect, text: &str, live: bool) {
+    let painter = ui.painter();
+    let galley = painter.layout_no_wrap(
+        text.to_string(),
+        egui::FontId::proportional(11.0),
+        egui::Color32::WHITE,
+    );
+    let pad = egui::vec2(4.0, 1.0);
+    let badge = egui::Rect::from_min_size(
+        rect.right_bottom() - galley.size() - pad * 2.0 - egui::vec2(4.0, 4.0),
+        galley.size() + pad * 2.0,
+    );
+    let fill = if live {
+        egui::Color32::from_rgb(204, 0, 0)
+    } else {
+        egui::Color32::from_black_alpha(200)
+    };
+    painter.rect_filled(badge, 3.0, fill);
+    painter.galley(badge.min + pad, galley, egui::Color32::WHITE);
+}
+
+fn watch_url(video_id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={}", video_id)
 }
 
 /// Extracts YouTube  video id
@@ -0,0 +1,248 @@
+use serde::Deserialize;
+use std::process::Stdio;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, BufReader},
+    process::Command,
+};
+use crate::{downloader::ytdlp_path, model::Quality};
+
+/// Subset of the `yt-dlp -J` info JSON the UI cares about.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct VideoMetadata {
+    pub title: Option<String>,
+    pub thumbnail: Option<String>,
+    /// Length in seconds, missing for live streams.
+    pub duration: Option<f64>,
+    pub is_live: Option<bool>,
+    /// Channel name; some extractors only fill in `channel`.
+    pub uploader: Option<String>,
+    pub channel: Option<String>,
+    /// "YYYYMMDD".
+    pub upload_date: Option<String>,
+    pub view_count: Option<u64>,
+    /// Track details YouTube Music adds to songs, used to pre-fill the tag editor.
+    pub track: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub release_year: Option<u32>,
+    /// Available formats, worst first as yt-dlp lists them.
+    #[serde(default)]
+    pub formats: Vec<Format>,
+    /// Sections the uploader marked, `null` in the JSON when there are none.
+    pub chapters: Option<Vec<Chapter>>,
+}
+
+/// A chapter of a video, in seconds from its start.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Chapter {
+    pub start_time: f64,
+    pub end_time: f64,
+    #[serde(default)]
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Format {
+    pub height: Option<u32>,
+    pub vcodec: Option<String>,
+    pub acodec: Option<String>,
+    pub filesize: Option<u64>,
+    pub filesize_approx: Option<u64>,
+}
+
+impl Format {
+    fn has_video(&self) -> bool {
+        self.vcodec.as_deref().is_some_and(|c| c != "none")
+    }
+
+    fn has_audio(&self) -> bool {
+        self.acodec.as_deref().is_some_and(|c| c != "none")
+    }
+
+    fn fits(&self, max_height: Option<u32>) -> bool {
+        max_height.is_none_or(|max| self.height.is_some_and(|h| h <= max))
+    }
+
+    fn size(&self) -> Option<u64> {
+        self.filesize.or(self.filesize_approx)
+    }
+}
+
+impl VideoMetadata {
+    /// Size of the download `quality` would pick, mirroring its `-f` selector:
+    /// the best muxed format under the height cap, or best video plus best audio.
+    pub fn estimate_size(&self, quality: Quality) -> Option<u64> {
+        let best = |pred: &dyn Fn(&Format) -> bool| self.formats.iter().rev().find(|f| pred(f));
+        let muxed = |max| best(&|f| f.has_video() && f.has_audio() && f.fits(max)).and_then(Format::size);
+        let split = |max| {
+            let video = best(&|f| f.has_video() && !f.has_audio() && f.fits(max))?.size()?;
+            let audio = best(&|f| f.has_audio() && !f.has_video())?.size()?;
+            Some(video + audio)
+        };
+        match quality {
+            Quality::Best => split(None).or_else(|| muxed(None)),
+            Quality::P2160 => split(Some(2160)).or_else(|| muxed(Some(2160))),
+            Quality::P1440 => split(Some(1440)).or_else(|| muxed(Some(1440))),
+            Quality::P1080 => split(Some(1080)).or_else(|| muxed(Some(1080))),
+            Quality::P720 => muxed(Some(720)),
+            Quality::P480 => muxed(Some(480)),
+            Quality::P360 => muxed(Some(360)),
+            Quality::AudioOnly => best(&|f| f.has_audio() && !f.has_video()).and_then(Format::size),
+        }
+    }
+}
+
+/// A playlist as `yt-dlp -J --flat-playlist` lists it, without the videos' formats.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PlaylistMetadata {
+    pub title: Option<String>,
+    /// Set for a channel's uploads.
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub entries: Vec<PlaylistEntry>,
+}
+
+impl PlaylistMetadata {
+    /// Entries listed before the first one whose id is in `seen`, which for a channel's
+    /// uploads (newest first) are the ones added since. None if no id of `seen` is listed:
+    /// those videos were deleted, made private or pushed out of the listing, and new
+    /// uploads can't be told from old ones any more.
+    pub fn entries_since(&self, seen: &[String]) -> &[PlaylistEntry] {
+        let end = self.entries.iter().position(|e| e.id.as_ref().is_some_and(|id| seen.contains(id)));
+        &self.entries[..end.unwrap_or(0)]
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PlaylistEntry {
+    /// Video id; missing for entries yt-dlp couldn't resolve.
+    pub id: Option<String>,
+    pub title: Option<String>,
+    /// Length in seconds, missing for live streams.
+    pub duration: Option<f64>,
+    pub channel: Option<String>,
+    pub uploader: Option<String>,
+    /// Title of the playlist the entry is in; only set on lines from `stream_playlist`.
+    pub playlist_title: Option<String>,
+}
+
+/// Reads one line of `yt-dlp -j --flat-playlist` output, which is one entry.
+pub fn parse_playlist_line(line: &str) -> Option<PlaylistEntry> {
+    serde_json::from_str(line.trim()).ok()
+}
+
+/// A video found by `search`.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SearchResult {
+    pub id: String,
+    pub title: Option<String>,
+    pub channel: Option<String>,
+    pub uploader: Option<String>,
+    /// Length in seconds, missing for live streams.
+    pub duration: Option<f64>,
+}
+
+impl SearchResult {
+    pub fn channel(&self) -> Option<&str> {
+        self.channel.as_deref().or(self.uploader.as_deref())
+    }
+}
+
+/// Reads the hits out of `yt-dlp -J --flat-playlist ytsearchN:…` output, skipping
+/// entries without a video id.
+pub fn parse_search_results(json: &[u8]) -> Result<Vec<SearchResult>, serde_json::Error> {
+    #[derive(Deserialize)]
+    struct Page {
+        #[serde(default)]
+        entries: Vec<serde_json::Value>,
+    }
+    let page: Page = serde_json::from_slice(json)?;
+    Ok(page.entries.into_iter().filter_map(|e| serde_json::from_value(e).ok()).collect())
+}
+
+/// Searches YouTube for `query` and returns hits `start` to `start + count - 1`,
+/// counting from 1, so more can be loaded later. yt-dlp is killed if the future is
+/// dropped, which is how a search is cancelled.
+pub async fn search(
+    query: &str,
+    start: usize,
+    count: usize,
+) -> Result<Vec<SearchResult>, Box<dyn std::error::Error + Send + Sync>> {
+    let end = start + count - 1;
+    let output = Command::new(ytdlp_path()?)
+        .args(["-J", "--flat-playlist", "--no-warnings", "--playlist-items"])
+        .arg(format!("{}-{}", start, end))
+        .arg(format!("ytsearch{}:{}", end, query))
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string().into());
+    }
+    Ok(parse_search_results(&output.stdout)?)
+}
+
+/// Lists the videos of a playlist without their formats, handing each entry to
+/// `on_entry` as soon as yt-dlp prints it rather than after the whole list, which
+/// for a long playlist takes many page loads. yt-dlp is killed if the future is dropped.
+pub async fn stream_playlist(
+    url: &str,
+    mut on_entry: impl FnMut(PlaylistEntry),
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut child = Command::new(ytdlp_path()?)
+        .args(["-j", "--flat-playlist", "--no-warnings", url])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let entries = async {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines.next_line().await? {
+            if let Some(entry) = parse_playlist_line(&line) {
+                on_entry(entry);
+            }
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    // Both pipes are read at once so a chatty stderr can't block yt-dlp
+    let mut errors = String::new();
+    let (read, _) = tokio::join!(entries, stderr.read_to_string(&mut errors));
+    read?;
+    if !child.wait().await?.success() {
+        return Err(errors.trim().to_string().into());
+    }
+    Ok(())
+}
+
+/// The first `count` videos of a playlist, e.g. a channel's latest uploads.
+pub async fn fetch_playlist_head(
+    url: &str,
+    count: usize,
+) -> Result<PlaylistMetadata, Box<dyn std::error::Error + Send + Sync>> {
+    let output = Command::new(ytdlp_path()?)
+        .args(["-J", "--flat-playlist", "--no-warnings", "--playlist-end"])
+        .arg(count.to_string())
+        .arg(url)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string().into());
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// Runs `yt-dlp -J` for a single video and parses the result.
+pub async fn fetch_metadata(
+    url: &str,
+) -> Result<VideoMetadata, Box<dyn std::error::Error + Send + Sync>> {
+    let output = Command::new(ytdlp_path()?)
+        .args(["-J", "--no-playlist", "--no-warnings", url])
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string().into());
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
@@ -1,12 +1,669 @@
-﻿#[derive(Clone)]
+﻿use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use crate::{encode::EncodeProfile, metadata::Chapter, tags::Tags};
+
+#[derive(Clone)]
 pub enum DownloadStatus {
+    /// Waiting for metadata or for the user to resolve a file name conflict.
+    Queued,
+    /// Held back until this time, then queued.
+    Scheduled(DateTime<Local>),
     Downloading,
+    /// yt-dlp is merging, converting or fixing up the downloaded file.
+    PostProcessing,
+    /// Stopped by the user, resumable from the partial file.
+    Paused,
     Done,
+    /// yt-dlp failed, with the error it reported.
+    Failed(String),
+}
+
+/// A step of the app's own post-processing, run after yt-dlp's while the task shows
+/// as PostProcessing.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PostStep {
+    Normalizing,
+    /// Writing one file per chapter, `done` of `total` written.
+    Splitting { done: usize, total: usize },
+    /// Re-encoding to the task's profile, `percent` done.
+    Encoding { percent: u8 },
+}
+
+impl PostStep {
+    /// Status text on the card while the step runs.
+    pub fn label(self) -> String {
+        match self {
+            PostStep::Normalizing => t!("status.normalizing"),
+            PostStep::Splitting { done, total } => t!("status.splitting", done = done, total = total),
+            PostStep::Encoding { percent } => t!("status.encoding", percent = percent),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ThumbnailState {
+    Pending,
+    Loaded,
+    Failed,
+}
+
+/// Requested download quality. Stored by its label so older settings and history
+/// files keep loading; unknown labels fall back to the default.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Quality {
+    Best,
+    P2160,
+    P1440,
+    P1080,
+    #[default]
+    P720,
+    P480,
+    P360,
+    AudioOnly,
+}
+
+impl Quality {
+    pub const ALL: [Quality; 8] = [
+        Quality::Best,
+        Quality::P2160,
+        Quality::P1440,
+        Quality::P1080,
+        Quality::P720,
+        Quality::P480,
+        Quality::P360,
+        Quality::AudioOnly,
+    ];
+
+    /// Stable name, also used to store the quality in settings and history.
+    pub fn label(self) -> &'static str {
+        match self {
+            Quality::Best => "Best available",
+            Quality::P2160 => "2160p (4K)",
+            Quality::P1440 => "1440p",
+            Quality::P1080 => "1080p",
+            Quality::P720 => "720p",
+            Quality::P480 => "480p",
+            Quality::P360 => "360p",
+            Quality::AudioOnly => "Audio Only",
+        }
+    }
+
+    /// `label` in the UI language.
+    pub fn display_name(self) -> String {
+        match self {
+            Quality::Best => t!("quality.best"),
+            Quality::P2160 => t!("quality.2160"),
+            Quality::AudioOnly => t!("quality.audio"),
+            q => q.label().to_string(),
+        }
+    }
+
+    /// Whether a file downloaded at this quality is as good as one at `wanted`: video
+    /// at the same or a higher resolution, or audio for audio.
+    pub fn covers(self, wanted: Quality) -> bool {
+        match (self.height(), wanted.height()) {
+            (Some(have), Some(want)) => have >= want,
+            (have, want) => have.is_none() && want.is_none(),
+        }
+    }
+
+    /// Highest resolution this quality allows, `None` for audio only.
+    fn height(self) -> Option<u32> {
+        match self {
+            Quality::Best => Some(u32::MAX),
+            Quality::P2160 => Some(2160),
+            Quality::P1440 => Some(1440),
+            Quality::P1080 => Some(1080),
+            Quality::P720 => Some(720),
+            Quality::P480 => Some(480),
+            Quality::P360 => Some(360),
+            Quality::AudioOnly => None,
+        }
+    }
+
+    /// yt-dlp `-f` selector. YouTube only serves single-file formats up to 720p, so
+    /// 1080p and above select separate video and audio streams, which needs ffmpeg to merge.
+    pub fn format_selector(self) -> String {
+        let split = |h: u32| format!("bestvideo[height<={h}]+bestaudio/best[height<={h}]");
+        match self {
+            Quality::Best => "bestvideo+bestaudio/best".to_string(),
+            Quality::P2160 => split(2160),
+            Quality::P1440 => split(1440),
+            Quality::P1080 => split(1080),
+            Quality::P720 => "best[height<=720]".to_string(),
+            Quality::P480 => "best[height<=480]".to_string(),
+            Quality::P360 => "best[height<=360]".to_string(),
+            Quality::AudioOnly => "bestaudio".to_string(),
+        }
+    }
+}
+
+/// Format "Audio Only" downloads are converted to with `--extract-audio`.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum AudioFormat {
+    /// YouTube serves AAC in m4a, so this usually needs no re-encoding.
+    #[default]
+    M4a,
+    Mp3,
+    Opus,
+    Flac,
+}
+
+impl AudioFormat {
+    pub const ALL: [AudioFormat; 4] = [AudioFormat::M4a, AudioFormat::Mp3, AudioFormat::Opus, AudioFormat::Flac];
+
+    /// `--audio-format` value, also the file extension.
+    pub fn codec(self) -> &'static str {
+        match self {
+            AudioFormat::M4a => "m4a",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+            AudioFormat::Flac => "flac",
+        }
+    }
+
+    /// `--audio-quality` for lossy formats that get re-encoded.
+    pub fn bitrate(self) -> Option<&'static str> {
+        match self {
+            AudioFormat::Mp3 => Some("192K"),
+            _ => None,
+        }
+    }
+
+    /// Whether the audio has to be re-encoded rather than just remuxed, which is slower
+    /// and, for lossy formats, loses quality.
+    pub fn reencodes(self) -> bool {
+        matches!(self, AudioFormat::Mp3 | AudioFormat::Flac)
+    }
+
+    /// yt-dlp `-f` selector, preferring a stream that is already in this format.
+    pub fn format_selector(self) -> &'static str {
+        match self {
+            AudioFormat::M4a => "bestaudio[ext=m4a]/bestaudio",
+            AudioFormat::Opus => "bestaudio[acodec=opus]/bestaudio",
+            AudioFormat::Mp3 | AudioFormat::Flac => "bestaudio",
+        }
+    }
+
+    /// Codec and bitrate for the card, e.g. "mp3 192k".
+    pub fn describe(self) -> String {
+        match self.bitrate() {
+            Some(bitrate) => format!("{} {}", self.codec(), bitrate.to_lowercase()),
+            None => self.codec().to_string(),
+        }
+    }
+}
+
+impl From<String> for Quality {
+    fn from(label: String) -> Self {
+        Quality::ALL.into_iter().find(|q| q.label() == label).unwrap_or_default()
+    }
+}
+
+impl From<Quality> for String {
+    fn from(q: Quality) -> Self {
+        q.label().to_string()
+    }
+}
+
+/// Identifies a task for its whole life; unlike video_id it stays unique when
+/// the same video is queued twice.
+pub type TaskId = u64;
+
+pub type PlaylistId = u64;
+
+/// A playlist link that was expanded into one task per video.
+pub struct Playlist {
+    pub id: PlaylistId,
+    pub title: String,
 }
 
 pub struct DownloadTask {
+    pub id: TaskId,
     pub title: String,
     pub video_id: String,
+    pub url: String,
+    pub quality: Quality,
+    pub audio_format: AudioFormat,
+    /// Normalize the loudness of the audio once downloaded; only for audio only.
+    pub normalize: bool,
+    /// Split the audio into one file per chapter once downloaded.
+    pub split_tracks: bool,
+    /// Chapters from the metadata prefetch.
+    pub chapters: Vec<Chapter>,
+    /// Files written by splitting into tracks, in track order.
+    pub tracks: Vec<PathBuf>,
+    /// Only this part of the video is downloaded.
+    pub clip: Option<Clip>,
+    /// Re-encode the video to this once downloaded; not for audio only.
+    pub encode: Option<EncodeProfile>,
+    /// Name of the preset the task was queued with.
+    pub preset: Option<String>,
+    /// Playlist the task was expanded from, grouping it in the downloads list.
+    pub playlist: Option<PlaylistId>,
+    /// Resolution yt-dlp actually downloaded, e.g. "1280x720" or "audio only".
+    pub resolution: Option<String>,
+    pub folder: String,
+    /// Output file reported by yt-dlp, once known.
+    pub file_path: Option<PathBuf>,
+    /// Set when Play found the output file moved or deleted.
+    pub file_missing: bool,
+    /// Folder a move rule put the finished file in.
+    pub moved_to: Option<String>,
+    /// The finished file was deleted with "Delete file".
+    pub file_deleted: bool,
+    /// Passes `--force-overwrites`, chosen in the file-exists prompt.
+    pub overwrite: bool,
+    /// Output name picked by "Rename" in the file-exists prompt.
+    pub file_stem: Option<String>,
     pub status: DownloadStatus,
+    /// What the app itself is doing to the file while the status is PostProcessing.
+    pub post_step: Option<PostStep>,
+    /// Problem with a post-processing step that left the download itself fine.
+    pub warning: Option<String>,
     pub progress: f32,
+    /// How many times yt-dlp has been started for this task.
+    pub attempts: u32,
+    /// Program and arguments of the latest yt-dlp run, for "Copy yt-dlp command".
+    pub command: Option<Vec<String>>,
+    pub thumbnail: ThumbnailState,
+    /// Thumbnail URL from the metadata prefetch, preferred over the video_id-based one.
+    pub thumbnail_url: Option<String>,
+    /// Length in seconds from the metadata prefetch.
+    pub duration: Option<u64>,
+    pub is_live: bool,
+    pub uploader: Option<String>,
+    /// "YYYY-MM-DD".
+    pub upload_date: Option<String>,
+    pub view_count: Option<u64>,
+    /// Expected file size in bytes for the chosen quality, from the metadata prefetch.
+    pub estimated_size: Option<u64>,
+    /// Bytes of the current stream reported by the last progress line of this attempt.
+    pub transferred: Option<u64>,
+    /// Download speed in bytes per second while running.
+    pub speed: Option<f64>,
+    /// Set once the metadata prefetch finished or failed; the task starts no earlier.
+    pub metadata_ready: bool,
+    /// Queued again after a pause, so it continues its partial file when started.
+    pub resume: bool,
+    /// Tag editor defaults worked out from the metadata prefetch.
+    pub suggested_tags: Option<Tags>,
+}
+
+impl DownloadStatus {
+    /// Whether a yt-dlp process is working on the task.
+    pub fn is_running(&self) -> bool {
+        matches!(self, DownloadStatus::Downloading | DownloadStatus::PostProcessing)
+    }
+}
+
+impl DownloadTask {
+    /// A queued task for `url` with nothing known about the video yet beyond its id.
+    pub fn new(id: TaskId, video_id: String, url: String, quality: Quality, audio_format: AudioFormat, folder: String) -> Self {
+        Self {
+            id,
+            title: t!("task.placeholder_title", id = video_id),
+            video_id,
+            url,
+            quality,
+            audio_format,
+            normalize: false,
+            split_tracks: false,
+            chapters: Vec::new(),
+            tracks: Vec::new(),
+            clip: None,
+            encode: None,
+            preset: None,
+            playlist: None,
+            resolution: None,
+            folder,
+            file_path: None,
+            file_missing: false,
+            moved_to: None,
+            file_deleted: false,
+            overwrite: false,
+            file_stem: None,
+            status: DownloadStatus::Queued,
+            post_step: None,
+            warning: None,
+            progress: 0.0,
+            attempts: 0,
+            command: None,
+            thumbnail: ThumbnailState::Pending,
+            thumbnail_url: None,
+            duration: None,
+            is_live: false,
+            uploader: None,
+            upload_date: None,
+            view_count: None,
+            estimated_size: None,
+            transferred: None,
+            speed: None,
+            metadata_ready: false,
+            resume: false,
+            suggested_tags: None,
+        }
+    }
+
+    /// Finished audio download the tag editor can work on.
+    pub fn taggable(&self) -> bool {
+        matches!(self.status, DownloadStatus::Done) && self.quality == Quality::AudioOnly && self.file_path.is_some()
+    }
+
+    /// Text for the badge on the thumbnail: "LIVE", "12:34" or "1:02:03".
+    pub fn duration_badge(&self) -> Option<String> {
+        if self.is_live {
+            return Some("LIVE".to_string());
+        }
+        self.duration.map(format_duration)
+    }
+
+    /// Secondary card line, e.g. "Rick Astley · 3:33 · 2009-10-25 · 1.5B views".
+    /// Fields the site didn't report are left out.
+    pub fn details_line(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            self.uploader.clone(),
+            self.duration.map(format_duration),
+            self.upload_date.clone(),
+            self.view_count.map(|v| t!("card.views", count = format_count(v))),
+            self.estimated_size.map(|b| format!("≈ {}", format_size(b))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+
+    /// Case-insensitive match of `query` against the title and video id.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.title.to_lowercase().contains(&query) || self.video_id.to_lowercase().contains(&query)
+    }
+}
+
+/// Which statuses the downloads panel shows, toggled by the filter chips.
+pub struct StatusFilter {
+    pub queued: bool,
+    pub downloading: bool,
+    pub done: bool,
+    pub failed: bool,
+}
+
+impl Default for StatusFilter {
+    fn default() -> Self {
+        Self { queued: true, downloading: true, done: true, failed: true }
+    }
+}
+
+impl StatusFilter {
+    pub fn allows(&self, status: &DownloadStatus) -> bool {
+        match status {
+            DownloadStatus::Queued | DownloadStatus::Scheduled(_) => self.queued,
+            DownloadStatus::Downloading | DownloadStatus::PostProcessing | DownloadStatus::Paused => {
+                self.downloading
+            }
+            DownloadStatus::Done => self.done,
+            DownloadStatus::Failed(_) => self.failed,
+        }
+    }
+}
+
+/// Full count with the thousands grouped, like "2,400".
+pub fn format_grouped(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push_str(&t!("format.thousands_separator"));
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Short count like "950", "12K", "3.4M" or "1.5B".
+pub fn format_count(n: u64) -> String {
+    let (value, suffix) = match n {
+        0..1_000 => return n.to_string(),
+        1_000..1_000_000 => (n as f64 / 1e3, "K"),
+        1_000_000..1_000_000_000 => (n as f64 / 1e6, "M"),
+        _ => (n as f64 / 1e9, "B"),
+    };
+    if value < 10.0 {
+        format!("{:.1}{}", value, suffix)
+    } else {
+        format!("{:.0}{}", value, suffix)
+    }
+}
+
+/// Size in binary units, e.g. "214 MB" or "1.4 GB".
+pub fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    let mb = bytes as f64 / MB;
+    if mb >= 1024.0 {
+        format!("{:.1} GB", mb / 1024.0)
+    } else if mb >= 10.0 {
+        format!("{:.0} MB", mb)
+    } else {
+        format!("{:.1} MB", mb)
+    }
+}
+
+pub fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+/// Time left in whole minutes, rounded up: "18 min", "1 h 05 min".
+pub fn format_minutes(secs: u64) -> String {
+    let minutes = secs.div_ceil(60);
+    if minutes >= 60 {
+        t!("format.hours_minutes", hours = minutes / 60, minutes = format!("{:02}", minutes % 60))
+    } else {
+        t!("format.minutes", minutes = minutes)
+    }
+}
+
+/// Reads a position typed as seconds ("75", "75.5") or clock time ("1:15", "1:02:03").
+pub fn parse_timestamp(text: &str) -> Option<f64> {
+    let parts: Vec<&str> = text.trim().split(':').collect();
+    let (last, whole) = parts.split_last()?;
+    if whole.len() > 2 {
+        return None;
+    }
+    let mut seconds = 0.0;
+    for part in whole {
+        let value: u32 = part.parse().ok()?;
+        seconds = seconds * 60.0 + f64::from(value);
+    }
+    // Only the last part has a fraction, and only on its own may it be 60 or more
+    let last: f64 = last.parse().ok().filter(|v: &f64| *v >= 0.0 && (whole.is_empty() || *v < 60.0))?;
+    Some(seconds * 60.0 + last)
+}
+
+/// Part of a video downloaded instead of all of it, in whole seconds.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Clip {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Clip {
+    /// The clip between the typed `start` and `end`, if both are times with the start
+    /// before the end and the end within the video's `duration`.
+    pub fn parse(start: &str, end: &str, duration: u64) -> Option<Clip> {
+        let start = parse_timestamp(start)?.round() as u64;
+        let end = parse_timestamp(end)?.round() as u64;
+        (start < end && end <= duration).then_some(Clip { start, end })
+    }
+
+    /// yt-dlp `--download-sections` value, e.g. "*750-840".
+    pub fn section(self) -> String {
+        format!("*{}-{}", self.start, self.end)
+    }
+
+    /// Suffix for the file name, e.g. "12m30s-14m00s".
+    pub fn file_suffix(self) -> String {
+        let stamp = |secs: u64| match (secs / 3600, secs / 60 % 60, secs % 60) {
+            (0, m, s) => format!("{}m{:02}s", m, s),
+            (h, m, s) => format!("{}h{:02}m{:02}s", h, m, s),
+        };
+        format!("{}-{}", stamp(self.start), stamp(self.end))
+    }
+
+    /// "12:30–14:00" for the card.
+    pub fn range(self) -> String {
+        format!("{}–{}", format_duration(self.start), format_duration(self.end))
+    }
+
+    /// `size` of the whole video scaled down to the clip's share of `duration`.
+    pub fn scale_size(self, size: u64, duration: u64) -> u64 {
+        if duration == 0 {
+            return size;
+        }
+        (size as f64 * (self.end - self.start) as f64 / duration as f64).round() as u64
+    }
+}
+
+/// Totals for the current session. Counted as tasks progress rather than derived
+/// from the task list, so removing cards doesn't change them.
+pub struct SessionStats {
+    pub completed: u32,
+    pub failed: u32,
+    pub bytes: u64,
+    pub started: Instant,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self { completed: 0, failed: 0, bytes: 0, started: Instant::now() }
+    }
+}
+
+impl SessionStats {
+    /// Adds the growth of a task's byte counter. The first report of an attempt is only
+    /// a baseline, so bytes resumed from a partial file aren't counted again. A counter
+    /// that went down means the next stream of a merged download started from zero.
+    pub fn add_transfer(&mut self, previous: Option<u64>, bytes: u64) {
+        if let Some(previous) = previous {
+            self.bytes += bytes.checked_sub(previous).unwrap_or(bytes);
+        }
+    }
+}
+
+/// Seconds of combined download speed the bandwidth graph covers.
+pub const BANDWIDTH_WINDOW: usize = 5 * 60;
+
+/// Combined download speed of all tasks, one sample per second over the last
+/// `BANDWIDTH_WINDOW` seconds of downloading. Older samples are dropped, so it never
+/// holds more than that.
+#[derive(Default)]
+pub struct BandwidthHistory {
+    samples: VecDeque<f64>,
+    sampled: Option<Instant>,
+}
+
+impl BandwidthHistory {
+    /// Records `speed` in bytes per second for each whole second since the last sample,
+    /// so seconds without a repaint aren't missing from the graph.
+    pub fn sample(&mut self, speed: f64, now: Instant) {
+        let seconds = match self.sampled {
+            Some(at) => now.duration_since(at).as_secs() as usize,
+            None => 1,
+        };
+        if seconds == 0 {
+            return;
+        }
+        self.sampled = Some(match self.sampled {
+            Some(at) => at + Duration::from_secs(seconds as u64),
+            None => now,
+        });
+        for _ in 0..seconds.min(BANDWIDTH_WINDOW) {
+            if self.samples.len() == BANDWIDTH_WINDOW {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(speed);
+        }
+    }
+
+    /// Stops sampling while nothing downloads; the idle time is left out of the graph
+    /// rather than drawn as zeros.
+    pub fn pause(&mut self) {
+        self.sampled = None;
+    }
+
+    /// Samples from oldest to newest.
+    pub fn samples(&self) -> impl ExactSizeIterator<Item = f64> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn peak(&self) -> f64 {
+        self.samples.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+/// Order of the cards in the downloads panel. Only affects rendering, the task
+/// list itself stays in insertion order.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SortOrder {
+    #[default]
+    AddedAsc,
+    AddedDesc,
+    Progress,
+    Title,
+    Status,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 5] = [
+        SortOrder::AddedAsc,
+        SortOrder::AddedDesc,
+        SortOrder::Progress,
+        SortOrder::Title,
+        SortOrder::Status,
+    ];
+
+    pub fn label(self) -> String {
+        match self {
+            SortOrder::AddedAsc => t!("sort.added_asc"),
+            SortOrder::AddedDesc => t!("sort.added_desc"),
+            SortOrder::Progress => t!("sort.progress"),
+            SortOrder::Title => t!("sort.title"),
+            SortOrder::Status => t!("sort.status"),
+        }
+    }
+
+    /// Compares two tasks, falling back to insertion order on ties so the list doesn't jump.
+    pub fn compare(self, a: &DownloadTask, b: &DownloadTask) -> Ordering {
+        let by = match self {
+            SortOrder::AddedAsc | SortOrder::AddedDesc => Ordering::Equal,
+            SortOrder::Progress => b.progress.total_cmp(&a.progress),
+            SortOrder::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SortOrder::Status => status_rank(&a.status).cmp(&status_rank(&b.status)),
+        };
+        let added = if self == SortOrder::AddedDesc { b.id.cmp(&a.id) } else { a.id.cmp(&b.id) };
+        by.then(added)
+    }
+}
+
+/// Active downloads first, then queued and paused ones, finished ones last.
+fn status_rank(status: &DownloadStatus) -> u8 {
+    match status {
+        DownloadStatus::Downloading | DownloadStatus::PostProcessing => 0,
+        DownloadStatus::Queued => 1,
+        DownloadStatus::Paused => 2,
+        DownloadStatus::Scheduled(_) => 3,
+        DownloadStatus::Done | DownloadStatus::Failed(_) => 4,
+    }
 }
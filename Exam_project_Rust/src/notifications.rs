@@ -0,0 +1,73 @@
+use eframe::egui;
+use notify_rust::Notification;
+use crate::app_log;
+
+/// Shows a desktop notification on a background thread so a slow notification
+/// daemon can't stall the UI.
+pub fn notify(ctx: &egui::Context, summary: String, body: String) {
+    let ctx = ctx.clone();
+    std::thread::spawn(move || {
+        let mut n = Notification::new();
+        n.appname("YouTube Downloader").summary(&summary).body(&body);
+        show(n, ctx);
+    });
+}
+
+/// Whether notifications can carry buttons, which only the freedesktop backend offers.
+pub const HAS_ACTIONS: bool = cfg!(all(unix, not(target_os = "macos")));
+
+/// Shows a notification with a button that calls `on_accept` on the notification's
+/// thread. Where buttons aren't supported it is shown without one.
+pub fn ask(ctx: &egui::Context, summary: String, body: String, button: String, on_accept: impl FnOnce() + Send + 'static) {
+    let ctx = ctx.clone();
+    std::thread::spawn(move || {
+        let mut n = Notification::new();
+        n.appname("YouTube Downloader").summary(&summary).body(&body);
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            n.action("accept", &button);
+            n.action("default", "Show");
+            match n.show() {
+                Ok(handle) => handle.wait_for_action(|action| match action {
+                    "accept" => {
+                        on_accept();
+                        ctx.request_repaint();
+                    }
+                    "default" => {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                    }
+                    _ => {}
+                }),
+                Err(e) => app_log::warn(format!("Failed to show notification: {}", e)),
+            }
+        }
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        {
+            let _ = (button, on_accept);
+            show(n, ctx);
+        }
+    });
+}
+
+/// The freedesktop backend supports actions, so clicking the notification
+/// brings the window back to the front.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show(mut n: Notification, ctx: egui::Context) {
+    n.action("default", "Show");
+    match n.show() {
+        Ok(handle) => handle.wait_for_action(|action| {
+            if action == "default" {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+        }),
+        Err(e) => app_log::warn(format!("Failed to show notification: {}", e)),
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn show(n: Notification, _ctx: egui::Context) {
+    if let Err(e) = n.show() {
+        app_log::warn(format!("Failed to show notification: {}", e));
+    }
+}
@@ -0,0 +1,107 @@
+use std::{ffi::OsString, io, path::Path, process::Command};
+
+/// Opens a file, folder or URL with the OS default handler without blocking the UI thread.
+pub fn open_in_os(target: impl Into<OsString>) {
+    let target = target.into();
+    std::thread::spawn(move || {
+        #[cfg(target_os = "windows")]
+        {
+            let _ = Command::new("explorer").arg(target).spawn();
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let _ = Command::new("open").arg(target).spawn();
+        }
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            let _ = Command::new("xdg-open").arg(target).spawn();
+        }
+    });
+}
+
+/// Opens the folder holding `file` in the file manager with the file selected, without
+/// blocking the UI thread. Falls back to just opening the folder where selecting isn't
+/// possible, e.g. when the file is gone.
+pub fn reveal_in_os(file: &Path) {
+    if !file.exists() {
+        if let Some(folder) = file.parent() {
+            open_in_os(folder);
+        }
+        return;
+    }
+    let file = file.to_path_buf();
+    std::thread::spawn(move || {
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            // Explorer parses its own command line, so the quoting std would add breaks it
+            let _ = Command::new("explorer").raw_arg(explorer_select_arg(&file)).spawn();
+        }
+        #[cfg(target_os = "macos")]
+        {
+            let _ = Command::new("open").arg("-R").arg(&file).spawn();
+        }
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            let shown = Command::new("dbus-send")
+                .args(show_items_args(&file))
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status()
+                .is_ok_and(|s| s.success());
+            if !shown && let Some(folder) = file.parent() {
+                let _ = Command::new("xdg-open").arg(folder).spawn();
+            }
+        }
+    });
+}
+
+/// Explorer's `/select,` argument for `file`. The path is quoted so commas in it aren't
+/// taken as further arguments; Windows paths can't contain quotes themselves.
+pub fn explorer_select_arg(file: &Path) -> String {
+    format!("/select,\"{}\"", file.display())
+}
+
+/// `dbus-send` arguments for the freedesktop file manager's ShowItems call, which
+/// Nautilus, Dolphin, Nemo and others answer by selecting `file`.
+pub fn show_items_args(file: &Path) -> Vec<String> {
+    vec![
+        "--session".to_string(),
+        "--print-reply".to_string(),
+        "--dest=org.freedesktop.FileManager1".to_string(),
+        "--type=method_call".to_string(),
+        "/org/freedesktop/FileManager1".to_string(),
+        "org.freedesktop.FileManager1.ShowItems".to_string(),
+        format!("array:string:{}", file_uri(file)),
+        "string:".to_string(),
+    ]
+}
+
+/// `file://` URI of an absolute path, with everything but unreserved characters and
+/// slashes percent-encoded. The commas that would split a dbus-send array are encoded too.
+pub fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for &byte in path.as_os_str().as_encoded_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Deletes a file, into the OS trash unless `permanently`. A file that is already gone
+/// counts as deleted.
+pub fn delete_file(path: &Path, permanently: bool) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+    if permanently {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        }
+    } else {
+        trash::delete(path).map_err(|e| e.to_string())
+    }
+}
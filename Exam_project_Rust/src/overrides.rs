@@ -0,0 +1,141 @@
+//! Settings given for one session, for scripts and shortcuts. The value of a setting
+//! comes from, first to last: a command line flag, an environment variable, the
+//! settings file, the built-in default. Overridden settings can't be edited in the
+//! window and are never written back to the settings file.
+use std::path::PathBuf;
+use eframe::egui::Ui;
+use crate::{model::Quality, settings::{Settings, MAX_PARALLEL}};
+
+/// A value and the flag or variable it came from, e.g. "--folder".
+pub struct Overridden<T> {
+    pub value: T,
+    pub from: String,
+    /// What the settings file had, put back when saving.
+    original: Option<T>,
+}
+
+#[derive(Default)]
+pub struct Overrides {
+    /// Settings file to use instead of the one in the config directory.
+    pub config: Option<PathBuf>,
+    pub folder: Option<Overridden<String>>,
+    pub quality: Option<Overridden<Quality>>,
+    pub max_parallel: Option<Overridden<u32>>,
+}
+
+/// (flag, environment variable) of each override.
+const CONFIG: (&str, &str) = ("--config", "EXAM_RUST_CONFIG");
+const FOLDER: (&str, &str) = ("--folder", "EXAM_RUST_FOLDER");
+const QUALITY: (&str, &str) = ("--quality", "EXAM_RUST_QUALITY");
+const MAX_CONCURRENT: (&str, &str) = ("--max-concurrent", "EXAM_RUST_MAX_CONCURRENT");
+
+impl Overrides {
+    /// Takes the override flags out of `args` and reads the environment variables.
+    pub fn parse(args: &mut Vec<String>) -> Result<Self, String> {
+        let mut flags: Vec<(&str, String)> = Vec::new();
+        let mut rest = Vec::new();
+        let mut iter = std::mem::take(args).into_iter();
+        while let Some(arg) = iter.next() {
+            match [CONFIG, FOLDER, QUALITY, MAX_CONCURRENT].into_iter().find(|(flag, _)| *flag == arg) {
+                Some((flag, _)) => {
+                    let value = iter.next().ok_or_else(|| t!("cli.missing_value", flag = flag))?;
+                    flags.push((flag, value));
+                }
+                None => rest.push(arg),
+            }
+        }
+        *args = rest;
+
+        // The last flag wins, then the environment
+        let lookup = |(flag, var): (&str, &str)| -> Option<(String, String)> {
+            match flags.iter().rev().find(|(f, _)| *f == flag) {
+                Some((_, value)) => Some((flag.to_string(), value.clone())),
+                None => std::env::var(var).ok().filter(|v| !v.is_empty()).map(|v| (var.to_string(), v)),
+            }
+        };
+        let bad_value = |from: &str, value: &str| t!("cli.bad_value", flag = from, value = value);
+
+        Ok(Self {
+            config: lookup(CONFIG).map(|(_, path)| PathBuf::from(path)),
+            folder: lookup(FOLDER).map(|(from, folder)| Overridden::new(folder, from)),
+            quality: match lookup(QUALITY) {
+                Some((from, text)) => {
+                    let quality = parse_quality(&text).ok_or_else(|| bad_value(&from, &text))?;
+                    Some(Overridden::new(quality, from))
+                }
+                None => None,
+            },
+            max_parallel: match lookup(MAX_CONCURRENT) {
+                Some((from, text)) => {
+                    let limit: u32 = text.parse().ok().filter(|&n| n <= MAX_PARALLEL).ok_or_else(|| bad_value(&from, &text))?;
+                    Some(Overridden::new(limit, from))
+                }
+                None => None,
+            },
+        })
+    }
+
+    /// Puts the overridden values into `settings`, remembering what they replaced.
+    pub fn apply(&mut self, settings: &mut Settings) {
+        if let Some(o) = &mut self.folder {
+            o.original = Some(std::mem::replace(&mut settings.download_folder, o.value.clone()));
+        }
+        if let Some(o) = &mut self.quality {
+            o.original = Some(std::mem::replace(&mut settings.quality, o.value));
+        }
+        if let Some(o) = &mut self.max_parallel {
+            o.original = Some(std::mem::replace(&mut settings.max_parallel, o.value));
+        }
+    }
+
+    /// `settings` as they should be saved: with the file's own values where overridden.
+    pub fn saved_form(&self, settings: &Settings) -> Settings {
+        let mut saved = settings.clone();
+        if let Some(Overridden { original: Some(folder), .. }) = &self.folder {
+            saved.download_folder = folder.clone();
+        }
+        if let Some(Overridden { original: Some(quality), .. }) = &self.quality {
+            saved.quality = *quality;
+        }
+        if let Some(Overridden { original: Some(limit), .. }) = &self.max_parallel {
+            saved.max_parallel = *limit;
+        }
+        saved
+    }
+}
+
+impl<T> Overridden<T> {
+    fn new(value: T, from: String) -> Self {
+        Self { value, from, original: None }
+    }
+
+    /// Tooltip for the disabled setting.
+    pub fn hint(&self) -> String {
+        t!("settings.overridden", source = self.from)
+    }
+}
+
+/// "720p", "720", "4k", "best" or "audio".
+fn parse_quality(text: &str) -> Option<Quality> {
+    let text = text.to_lowercase();
+    match text.trim_end_matches('p') {
+        "best" => Some(Quality::Best),
+        "audio" => Some(Quality::AudioOnly),
+        "4k" | "2160" => Some(Quality::P2160),
+        "1440" => Some(Quality::P1440),
+        "1080" => Some(Quality::P1080),
+        "720" => Some(Quality::P720),
+        "480" => Some(Quality::P480),
+        "360" => Some(Quality::P360),
+        _ => None,
+    }
+}
+
+/// Shows what `add` draws greyed out while `overridden` is set, with the reason on hover.
+pub fn locked_ui<T, R>(ui: &mut Ui, overridden: Option<&Overridden<T>>, add: impl FnOnce(&mut Ui) -> R) -> R {
+    let shown = ui.add_enabled_ui(overridden.is_none(), add);
+    if let Some(o) = overridden {
+        shown.response.on_disabled_hover_text(o.hint());
+    }
+    shown.inner
+}
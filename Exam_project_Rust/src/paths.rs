@@ -0,0 +1,99 @@
+//! Where the app keeps its files. Normally that's the platform's config, data and
+//! cache directories; in portable mode everything goes into one directory beside the
+//! executable instead, so the app can run from a USB stick without leaving traces.
+use std::path::PathBuf;
+use directories::ProjectDirs;
+use once_cell::sync::OnceCell;
+
+/// A file with this name next to the executable turns on portable mode.
+pub const PORTABLE_FLAG_FILE: &str = "portable.flag";
+/// Command line flag that turns on portable mode.
+pub const PORTABLE_ARG: &str = "--portable";
+/// Directory next to the executable that holds everything in portable mode.
+pub const PORTABLE_DIR: &str = "portable_data";
+
+/// The portable directory once `init` chose portable mode, `None` for the platform
+/// directories. Unset (e.g. in tests) counts as `None`.
+static PORTABLE_ROOT: OnceCell<Option<PathBuf>> = OnceCell::new();
+
+/// Picks the mode for this process: portable if `args` contain `--portable` (which is
+/// removed from them) or the flag file exists. Call once, before anything reads or
+/// writes a file; later calls only strip the flag.
+pub fn init(args: &mut Vec<String>) {
+    let before = args.len();
+    args.retain(|a| a != PORTABLE_ARG);
+    let requested = args.len() != before;
+    let exe_dir = std::env::current_exe().ok().and_then(|exe| Some(exe.parent()?.to_path_buf()));
+    let root = exe_dir
+        .filter(|dir| requested || dir.join(PORTABLE_FLAG_FILE).exists())
+        .map(|dir| dir.join(PORTABLE_DIR));
+    let _ = PORTABLE_ROOT.set(root);
+}
+
+/// The portable directory, `None` when using the platform directories.
+pub fn portable_root() -> Option<PathBuf> {
+    PORTABLE_ROOT.get().cloned().flatten()
+}
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "YouTubeDownloader")
+}
+
+/// Settings.
+pub fn config_dir() -> Option<PathBuf> {
+    match portable_root() {
+        Some(root) => Some(root),
+        None => Some(project_dirs()?.config_dir().to_path_buf()),
+    }
+}
+
+/// History, the saved session and logs.
+pub fn data_dir() -> Option<PathBuf> {
+    match portable_root() {
+        Some(root) => Some(root),
+        None => Some(project_dirs()?.data_dir().to_path_buf()),
+    }
+}
+
+/// Files that can be deleted at any time, like thumbnails.
+pub fn cache_dir() -> Option<PathBuf> {
+    match portable_root() {
+        Some(root) => Some(root.join("cache")),
+        None => Some(project_dirs()?.cache_dir().to_path_buf()),
+    }
+}
+
+/// Where the embedded yt-dlp is extracted to.
+pub fn bin_dir() -> PathBuf {
+    match portable_root() {
+        Some(root) => root.join("bin"),
+        None => std::env::temp_dir(),
+    }
+}
+
+/// A file ffmpeg writes before it replaces or becomes something else. Deleted when
+/// dropped, so a failed or aborted job leaves nothing behind.
+pub(crate) struct TempFile(pub(crate) PathBuf);
+
+impl TempFile {
+    /// A hidden file next to `file` for `purpose`, e.g. ".encode.talk.mp4". In the same
+    /// folder, the rename that puts it in place of `file` can't cross drives; with the
+    /// same extension, ffmpeg picks the same muxer for it. `None` if `file` has no name.
+    pub(crate) fn next_to(file: &std::path::Path, purpose: &str) -> Option<TempFile> {
+        let name = file.file_name()?.to_string_lossy();
+        Some(TempFile(file.with_file_name(format!(".{}.{}", purpose, name))))
+    }
+
+    /// The path, with the file kept from now on.
+    pub(crate) fn keep(mut self) -> PathBuf {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.0.as_os_str().is_empty() {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+};
+
+/// Players looked for on the PATH when none is configured, in order of preference.
+const PLAYERS: [&str; 2] = ["mpv", "vlc"];
+
+/// The player to watch previews with: `configured` if it is set (a path, or a name
+/// on the PATH), otherwise mpv or VLC if either is on the PATH.
+pub fn find(configured: &str) -> Option<PathBuf> {
+    let configured = configured.trim();
+    if configured.is_empty() {
+        return PLAYERS.into_iter().find_map(on_path);
+    }
+    let path = Path::new(configured);
+    if path.components().count() > 1 { path.is_file().then(|| path.to_path_buf()) } else { on_path(configured) }
+}
+
+fn on_path(name: &str) -> Option<PathBuf> {
+    let file = format!("{}{}", name, std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&std::env::var_os("PATH")?).map(|dir| dir.join(&file)).find(|p| p.is_file())
+}
+
+/// A video playing in an external player without being downloaded. Dropping it
+/// closes the player.
+pub struct Playback {
+    player: Child,
+    /// yt-dlp writing the stream to the player's stdin, for players that can't open
+    /// YouTube links themselves.
+    feeder: Option<Child>,
+}
+
+impl Playback {
+    /// Opens `url` in `player`. mpv resolves the link itself through its yt-dlp hook,
+    /// pointed at `ytdlp`; any other player is fed `ytdlp -o - url` on stdin.
+    pub fn start(player: &Path, ytdlp: &Path, url: &str) -> std::io::Result<Self> {
+        let is_mpv = player.file_stem().is_some_and(|s| s.eq_ignore_ascii_case("mpv"));
+        if is_mpv {
+            let player = Command::new(player)
+                .arg(format!("--script-opts=ytdl_hook-ytdl_path={}", ytdlp.display()))
+                .arg("--")
+                .arg(url)
+                .spawn()?;
+            return Ok(Self { player, feeder: None });
+        }
+
+        // Single-file formats only: a merged stream can't be written to a pipe
+        let mut feeder = Command::new(ytdlp)
+            .args(["-f", "best", "--quiet", "--no-part", "-o", "-", url])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stream = feeder.stdout.take().expect("stdout is piped");
+        match Command::new(player).arg("-").stdin(stream).spawn() {
+            Ok(player) => Ok(Self { player, feeder: Some(feeder) }),
+            Err(e) => {
+                let _ = feeder.kill();
+                let _ = feeder.wait();
+                Err(e)
+            }
+        }
+    }
+
+    /// Whether the player window is still open.
+    pub fn running(&mut self) -> bool {
+        matches!(self.player.try_wait(), Ok(None))
+    }
+}
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        for child in std::iter::once(&mut self.player).chain(self.feeder.as_mut()) {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
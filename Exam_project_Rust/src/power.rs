@@ -0,0 +1,82 @@
+use std::process::Command;
+
+/// What happens once every download of a batch has finished. Not saved, so a
+/// forgotten "Shut down" can't surprise anyone on the next launch.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum AfterQueue {
+    #[default]
+    Nothing,
+    Quit,
+    Sleep,
+    Shutdown,
+}
+
+impl AfterQueue {
+    pub const ALL: [AfterQueue; 4] = [AfterQueue::Nothing, AfterQueue::Quit, AfterQueue::Sleep, AfterQueue::Shutdown];
+
+    pub fn label(self) -> String {
+        match self {
+            AfterQueue::Nothing => t!("after.nothing"),
+            AfterQueue::Quit => t!("after.quit"),
+            AfterQueue::Sleep => t!("after.sleep"),
+            AfterQueue::Shutdown => t!("after.shutdown"),
+        }
+    }
+}
+
+/// Puts the computer to sleep.
+pub fn sleep() -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let command = {
+        let mut c = Command::new("rundll32.exe");
+        c.arg("powrprof.dll,SetSuspendState").arg("0,1,0");
+        c
+    };
+    #[cfg(target_os = "macos")]
+    let command = {
+        let mut c = Command::new("pmset");
+        c.arg("sleepnow");
+        c
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let command = {
+        let mut c = Command::new("systemctl");
+        c.arg("suspend");
+        c
+    };
+    run(command)
+}
+
+/// Asks the OS to shut down. The app still has to quit by itself.
+pub fn shutdown() -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let command = {
+        let mut c = Command::new("shutdown.exe");
+        c.args(["/s", "/t", "0"]);
+        c
+    };
+    // `shutdown -h` needs root on macOS, System Events doesn't
+    #[cfg(target_os = "macos")]
+    let command = {
+        let mut c = Command::new("osascript");
+        c.args(["-e", "tell application \"System Events\" to shut down"]);
+        c
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let command = {
+        let mut c = Command::new("systemctl");
+        c.arg("poweroff");
+        c
+    };
+    run(command)
+}
+
+/// Runs a power command, turning a failure (no permission, missing tool) into a message.
+fn run(mut command: Command) -> Result<(), String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    match command.status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("{} exited with {}", program, status)),
+        Err(e) => Err(format!("{}: {}", program, e)),
+    }
+}
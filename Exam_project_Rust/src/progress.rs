@@ -1,11 +1,60 @@
-﻿pub fn parse_progress_from_line(line: &str) -> Option<f32> {
-    if let Some(rest) = line.strip_prefix("downloaded_bytes:") {
-        let trimmed = rest.trim();
-        if let Some(number) = trimmed.strip_suffix('%') {
-            if let Ok(v) = number.trim().parse::<f32>() {
-                return Some(v / 100.0);
-            }
-        }
+﻿use std::path::PathBuf;
+
+/// Reads the percentage from a `--progress-template` line,
+/// "downloaded_bytes: 42.0% bytes:1048576 speed:524288.0 resolution:1280x720".
+pub fn parse_progress_from_line(line: &str) -> Option<f32> {
+    let rest = line.strip_prefix("downloaded_bytes:")?;
+    let number = rest.split_whitespace().next()?.strip_suffix('%')?;
+    number.parse::<f32>().ok().map(|v| v / 100.0)
+}
+
+/// Reads the bytes of the current stream downloaded so far and the speed in bytes
+/// per second from a progress template line. The speed is `None` while yt-dlp can't tell yet.
+pub fn parse_transfer_from_line(line: &str) -> Option<(u64, Option<f64>)> {
+    let rest = line.strip_prefix("downloaded_bytes:")?;
+    let field = |name: &str| rest.split_whitespace().find_map(|w| w.strip_prefix(name));
+    let bytes = field("bytes:")?.parse::<f64>().ok()?;
+    let speed = field("speed:").and_then(|s| s.parse::<f64>().ok());
+    Some((bytes as u64, speed))
+}
+
+/// Reads the resolution of the format being downloaded from a progress template line.
+pub fn parse_resolution_from_line(line: &str) -> Option<String> {
+    let (_, res) = line.strip_prefix("downloaded_bytes:")?.split_once(" resolution:")?;
+    let res = res.trim();
+    (!res.is_empty() && res != "NA").then(|| res.to_string())
+}
+
+/// Picks the output file path out of yt-dlp's "[download] Destination: …" and
+/// "[download] … has already been downloaded" lines, the converted file's
+/// "[ExtractAudio] Destination: …" or the merged one's "[Merger] Merging formats into "…"".
+pub fn parse_destination_from_line(line: &str) -> Option<PathBuf> {
+    if let Some(path) = line.strip_prefix("[ExtractAudio] Destination: ") {
+        return Some(PathBuf::from(path.trim()));
     }
-    None
+    if let Some(path) = line.strip_prefix("[Merger] Merging formats into ") {
+        return Some(PathBuf::from(path.trim().trim_matches('"')));
+    }
+    let rest = line.strip_prefix("[download] ")?;
+    if let Some(path) = rest.strip_prefix("Destination: ") {
+        return Some(PathBuf::from(path.trim()));
+    }
+    rest.strip_suffix(" has already been downloaded").map(|p| PathBuf::from(p.trim()))
+}
+
+/// Line prefixes of the yt-dlp steps that run after the download itself
+/// ("[Fixup" covers FixupM3u8, FixupStretched and friends).
+const POST_PROCESSORS: [&str; 7] = [
+    "[Merger]",
+    "[ExtractAudio]",
+    "[VideoConvertor]",
+    "[VideoRemuxer]",
+    "[Fixup",
+    "[Metadata]",
+    "[EmbedThumbnail]",
+];
+
+/// Whether the line comes from a post-processing step rather than the download.
+pub fn is_post_processing_line(line: &str) -> bool {
+    POST_PROCESSORS.iter().any(|p| line.starts_with(p))
 }
@@ -0,0 +1,164 @@
+use std::{
+    error::Error,
+    io::{BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use eframe::egui;
+use crate::{app_log, links::is_youtube_url};
+#[cfg(not(target_os = "macos"))]
+use std::process::Command;
+
+/// URL scheme a bookmarklet or browser extension uses to hand a page to the app, as
+/// in `ytdlgui://download?url=https%3A%2F%2Fyoutu.be%2F…`.
+pub const SCHEME: &str = "ytdlgui";
+
+/// Local port the first running instance listens on for links from later launches.
+const FORWARD_PORT: u16 = 47815;
+const FORWARD_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// YouTube links received through the URL scheme, waiting to be queued.
+pub type ReceivedLinks = Arc<Mutex<Vec<String>>>;
+
+/// The `ytdlgui://` argument the app was launched with, if any.
+pub fn link_from_args(mut args: impl Iterator<Item = String>) -> Option<String> {
+    args.find(|a| a.starts_with(&format!("{}:", SCHEME)))
+}
+
+/// The YouTube URL inside a `ytdlgui://download?url=…` link. Anything else is
+/// refused, so a web page can't make the app fetch arbitrary sites.
+pub fn video_url(link: &str) -> Option<String> {
+    let link = reqwest::Url::parse(link.trim()).ok()?;
+    if link.scheme() != SCHEME || link.host_str() != Some("download") {
+        return None;
+    }
+    let url = link.query_pairs().find(|(key, _)| key == "url")?.1.into_owned();
+    is_youtube_url(&url).then_some(url)
+}
+
+/// Hands `link` to an instance that is already running. `false` if there is none,
+/// in which case this process should open the window itself.
+pub fn forward(link: &str) -> bool {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, FORWARD_PORT));
+    let Ok(mut stream) = TcpStream::connect_timeout(&addr, FORWARD_TIMEOUT) else { return false };
+    stream.write_all(format!("{}\n", link).as_bytes()).is_ok()
+}
+
+/// Accepts links forwarded by later launches on a background thread. Only the first
+/// instance gets the port; others log it and carry on without.
+pub fn listen(ctx: &egui::Context, received: ReceivedLinks) {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, FORWARD_PORT)) {
+        Ok(l) => l,
+        Err(e) => {
+            app_log::info(format!("Not listening for forwarded links: {}", e));
+            return;
+        }
+    };
+    let ctx = ctx.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = stream.set_read_timeout(Some(FORWARD_TIMEOUT));
+            let mut line = String::new();
+            if BufReader::new(stream.take(4096)).read_line(&mut line).is_err() {
+                continue;
+            }
+            match video_url(&line) {
+                Some(url) => {
+                    received.lock().unwrap().push(url);
+                    ctx.request_repaint();
+                }
+                None => app_log::warn(format!("Ignoring forwarded link: {}", line.trim())),
+            }
+        }
+    });
+}
+
+/// Makes the OS open `ytdlgui://` links with this executable.
+#[cfg(target_os = "windows")]
+pub fn register() -> Result<(), Box<dyn Error>> {
+    let exe = std::env::current_exe()?;
+    let key = format!("HKCU\\Software\\Classes\\{}", SCHEME);
+    reg(&["add", &key, "/ve", "/d", &format!("URL:{} Protocol", SCHEME), "/f"])?;
+    reg(&["add", &key, "/v", "URL Protocol", "/d", "", "/f"])?;
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    reg(&["add", &format!("{}\\shell\\open\\command", key), "/ve", "/d", &command, "/f"])
+}
+
+/// Removes what `register` added.
+#[cfg(target_os = "windows")]
+pub fn unregister() -> Result<(), Box<dyn Error>> {
+    reg(&["delete", &format!("HKCU\\Software\\Classes\\{}", SCHEME), "/f"])
+}
+
+#[cfg(target_os = "windows")]
+fn reg(args: &[&str]) -> Result<(), Box<dyn Error>> {
+    run(Command::new("reg.exe").args(args))
+}
+
+/// Makes the OS open `ytdlgui://` links with this executable.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn register() -> Result<(), Box<dyn Error>> {
+    let exe = std::env::current_exe()?;
+    let path = desktop_file().ok_or("No data directory")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=YouTube Downloader\nExec=\"{}\" %u\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        exe.display(),
+        SCHEME
+    );
+    std::fs::write(&path, entry)?;
+    run(Command::new("xdg-mime").args(["default", &desktop_file_name(), &format!("x-scheme-handler/{}", SCHEME)]))
+}
+
+/// Removes what `register` added: the desktop entry and the default it was given.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn unregister() -> Result<(), Box<dyn Error>> {
+    if let Some(path) = desktop_file() {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    // xdg-mime can set a default but not unset one
+    let Some(dirs) = directories::BaseDirs::new() else { return Ok(()) };
+    let mimeapps = dirs.config_dir().join("mimeapps.list");
+    let Ok(text) = std::fs::read_to_string(&mimeapps) else { return Ok(()) };
+    let prefix = format!("x-scheme-handler/{}=", SCHEME);
+    let kept: Vec<&str> = text.lines().filter(|line| !line.starts_with(&prefix)).collect();
+    std::fs::write(mimeapps, kept.join("\n") + "\n")?;
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn desktop_file_name() -> String {
+    format!("{}-handler.desktop", SCHEME)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn desktop_file() -> Option<std::path::PathBuf> {
+    let dirs = directories::BaseDirs::new()?;
+    Some(dirs.data_dir().join("applications").join(desktop_file_name()))
+}
+
+/// macOS only learns URL schemes from the app bundle's Info.plist, which needs a
+/// `CFBundleURLTypes` entry with `CFBundleURLSchemes` set to `ytdlgui`. Links then
+/// arrive as an Apple Event rather than an argument, which isn't handled yet.
+#[cfg(target_os = "macos")]
+pub fn register() -> Result<(), Box<dyn Error>> {
+    Err(t!("protocol.macos_hint").into())
+}
+
+#[cfg(target_os = "macos")]
+pub fn unregister() -> Result<(), Box<dyn Error>> {
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn run(command: &mut Command) -> Result<(), Box<dyn Error>> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let status = command.status().map_err(|e| format!("{}: {}", program, e))?;
+    if status.success() { Ok(()) } else { Err(format!("{} exited with {}", program, status).into()) }
+}
@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use crate::model::{DownloadStatus, DownloadTask, TaskId};
+
+/// How often the averaged queue speed takes a new sample.
+const SPEED_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Weight of each new sample in the averaged queue speed; lower is steadier but slower
+/// to follow a real change.
+const SPEED_SMOOTHING: f64 = 0.2;
+
+/// Where "Move up", "Move down" and "Move to top" take a queued task.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Move {
+    /// Swaps it with the task queued before it.
+    Up,
+    Down,
+    /// Makes it the next one to start.
+    ToTop,
+}
+
+/// Whether another download may start while `running` are; a `limit` of 0 means no limit.
+pub fn has_slot(running: usize, limit: usize) -> bool {
+    limit == 0 || running < limit
+}
+
+/// Tasks a yt-dlp process is working on, which count against the limit.
+pub fn running(tasks: &[DownloadTask]) -> usize {
+    tasks.iter().filter(|t| t.status.is_running()).count()
+}
+
+/// Queued tasks that may start, in the order they should: their metadata is in, so
+/// the file name can be checked for conflicts.
+pub fn ready(tasks: &[DownloadTask]) -> impl Iterator<Item = &DownloadTask> {
+    tasks.iter().filter(|t| matches!(t.status, DownloadStatus::Queued) && t.metadata_ready)
+}
+
+/// Queued tasks still waiting for their metadata, in queue order. Playlist entries
+/// only get theirs fetched shortly before their turn.
+pub fn awaiting_metadata(tasks: &[DownloadTask]) -> impl Iterator<Item = &DownloadTask> {
+    tasks.iter().filter(|t| matches!(t.status, DownloadStatus::Queued) && !t.metadata_ready)
+}
+
+/// Each queued task's place in the queue, counting from 1.
+pub fn positions(tasks: &[DownloadTask]) -> HashMap<TaskId, usize> {
+    tasks
+        .iter()
+        .filter(|t| matches!(t.status, DownloadStatus::Queued))
+        .enumerate()
+        .map(|(n, t)| (t.id, n + 1))
+        .collect()
+}
+
+/// Moves a queued task past its queued neighbour, or ahead of all of them. The queue
+/// is the order of `tasks`. Returns whether it moved.
+pub fn move_queued(tasks: &mut Vec<DownloadTask>, id: TaskId, step: Move) -> bool {
+    let queued: Vec<usize> = (0..tasks.len()).filter(|&i| matches!(tasks[i].status, DownloadStatus::Queued)).collect();
+    let Some(position) = queued.iter().position(|&i| tasks[i].id == id) else { return false };
+    let target = match step {
+        Move::Up => position.checked_sub(1),
+        Move::Down => Some(position + 1).filter(|&p| p < queued.len()),
+        Move::ToTop => Some(0).filter(|_| position > 0),
+    };
+    let Some(target) = target else { return false };
+    let task = tasks.remove(queued[position]);
+    tasks.insert(queued[target], task);
+    true
+}
+
+/// Bytes the running and queued downloads still have to fetch, and whether that is
+/// short of the real amount because some sizes aren't known. Scheduled tasks aren't
+/// part of the queue yet; post-processing ones have nothing left to fetch.
+pub fn bytes_left(tasks: &[DownloadTask]) -> (u64, bool) {
+    let mut bytes = 0;
+    let mut unknown = false;
+    for task in tasks {
+        let part_left = match task.status {
+            DownloadStatus::Downloading => 1.0 - f64::from(task.progress.clamp(0.0, 1.0)),
+            DownloadStatus::Queued => 1.0,
+            _ => continue,
+        };
+        match task.estimated_size {
+            Some(size) => bytes += (size as f64 * part_left).round() as u64,
+            None => unknown = true,
+        }
+    }
+    (bytes, unknown)
+}
+
+/// Time the rest of the queue should take.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Estimate {
+    pub seconds: u64,
+    /// Some sizes are unknown, so it will take longer.
+    pub at_least: bool,
+}
+
+/// Estimate for `bytes_left` at `speed` bytes per second, `None` without a speed to go by.
+pub fn estimate((bytes, unknown): (u64, bool), speed: Option<f64>) -> Option<Estimate> {
+    let speed = speed.filter(|s| *s > 0.0)?;
+    Some(Estimate { seconds: (bytes as f64 / speed).ceil() as u64, at_least: unknown })
+}
+
+/// The combined speed of the running downloads, averaged over samples taken once per
+/// second so the estimate doesn't jump with every progress line.
+#[derive(Default)]
+pub struct SpeedAverage {
+    average: Option<f64>,
+    sampled: Option<Instant>,
+}
+
+impl SpeedAverage {
+    /// Takes `speed` as a sample if the last one is old enough and returns the average.
+    /// `None`, as between the streams of a merged download, keeps the average as it is.
+    pub fn sample(&mut self, speed: Option<f64>, now: Instant) -> Option<f64> {
+        if self.sampled.is_some_and(|at| now.duration_since(at) < SPEED_SAMPLE_INTERVAL) {
+            return self.average;
+        }
+        self.sampled = Some(now);
+        if let Some(speed) = speed {
+            self.average = Some(match self.average {
+                Some(average) => average + SPEED_SMOOTHING * (speed - average),
+                None => speed,
+            });
+        }
+        self.average
+    }
+
+    /// Forgets the average, for when nothing is downloading any more.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,66 @@
+//! Rules that move finished downloads into other folders, e.g. audio into the music
+//! folder. They are checked in order and the first one that matches wins.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use serde::{Deserialize, Serialize};
+use crate::model::{DownloadTask, Quality};
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoveRule {
+    pub condition: Condition,
+    /// Folder matching files are moved to.
+    pub folder: String,
+}
+
+/// What a rule looks at. Text comparisons ignore case.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    Quality(Quality),
+    UploaderContains(String),
+    TitleContains(String),
+}
+
+impl Condition {
+    pub fn matches(&self, task: &DownloadTask) -> bool {
+        let contains = |text: &str, part: &str| !part.is_empty() && text.to_lowercase().contains(&part.to_lowercase());
+        match self {
+            Condition::Quality(quality) => task.quality == *quality,
+            Condition::UploaderContains(part) => task.uploader.as_deref().is_some_and(|u| contains(u, part)),
+            Condition::TitleContains(part) => contains(&task.title, part),
+        }
+    }
+}
+
+/// The first of `rules` that matches `task` and has a folder set.
+pub fn first_match<'a>(rules: &'a [MoveRule], task: &DownloadTask) -> Option<&'a MoveRule> {
+    rules.iter().find(|r| !r.folder.trim().is_empty() && r.condition.matches(task))
+}
+
+/// Moves `file` into `folder`, creating it if needed, and returns the new path. Across
+/// drives, where a rename isn't possible, the file is copied and the original removed.
+/// An existing file of the same name is never replaced.
+pub fn move_file(file: &Path, folder: &Path) -> io::Result<PathBuf> {
+    let name = file.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+    let target = folder.join(name);
+    if target == file {
+        return Ok(target);
+    }
+    if target.exists() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", target.display())));
+    }
+    fs::create_dir_all(folder)?;
+    match fs::rename(file, &target) {
+        Ok(()) => Ok(target),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(e) = fs::copy(file, &target) {
+                let _ = fs::remove_file(&target);
+                return Err(e);
+            }
+            fs::remove_file(file)?;
+            Ok(target)
+        }
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Duration, Local, NaiveDateTime, NaiveTime, TimeZone};
+
+/// Format scheduled times are shown and edited in, besides a bare "HH:MM".
+pub const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Reads a start time typed by the user: "23:30" is the next time the clock shows
+/// that, "2024-05-01 23:30" that exact moment (which may be in the past).
+pub fn parse_start_time(text: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let text = text.trim();
+    if let Ok(time) = NaiveTime::parse_from_str(text, "%H:%M") {
+        let today = local(now.date_naive().and_time(time))?;
+        return Some(if today > now { today } else { local((now.date_naive() + Duration::days(1)).and_time(time))? });
+    }
+    local(NaiveDateTime::parse_from_str(text, DATE_TIME_FORMAT).ok()?)
+}
+
+/// `time` on the local clock; the earlier one when a DST change makes it ambiguous,
+/// `None` if it falls into the skipped hour.
+fn local(time: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&time).earliest()
+}
+
+/// Waits until `at` on the runtime's timer. Sleeping in steps of at most a minute
+/// keeps the wake-up on time across system suspend and clock changes.
+pub async fn wait_until(at: DateTime<Local>) {
+    loop {
+        let Ok(left) = (at - Local::now()).to_std() else { return };
+        if left.is_zero() {
+            return;
+        }
+        tokio::time::sleep(left.min(std::time::Duration::from_secs(60))).await;
+    }
+}
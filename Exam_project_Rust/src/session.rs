@@ -0,0 +1,181 @@
+use std::{fs, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::{app_log, encode::EncodeProfile, links::is_youtube_url, model::{AudioFormat, Clip, Quality}, paths};
+
+/// Bumped on incompatible format changes. Files with another version are ignored
+/// rather than misread, so older and newer builds can share the data directory.
+const SESSION_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SavedStatus {
+    Queued,
+    /// yt-dlp was running when the app closed; restarts with --continue.
+    Running,
+    Paused,
+    /// Waiting for its start time, as a Unix timestamp.
+    Scheduled(i64),
+}
+
+/// An unfinished task as written to the session file.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedTask {
+    pub url: String,
+    pub title: String,
+    pub quality: Quality,
+    #[serde(default)]
+    pub audio_format: AudioFormat,
+    pub folder: String,
+    pub overwrite: bool,
+    pub file_stem: Option<String>,
+    #[serde(default)]
+    pub normalize: bool,
+    #[serde(default)]
+    pub split_tracks: bool,
+    #[serde(default)]
+    pub clip: Option<Clip>,
+    #[serde(default)]
+    pub encode: Option<EncodeProfile>,
+    #[serde(default)]
+    pub preset: Option<String>,
+    pub status: SavedStatus,
+    pub progress: f32,
+}
+
+impl SavedTask {
+    /// Equal apart from progress, which alone isn't worth rewriting the file for.
+    pub fn same_state(&self, other: &SavedTask) -> bool {
+        self.url == other.url
+            && self.title == other.title
+            && self.quality == other.quality
+            && self.audio_format == other.audio_format
+            && self.folder == other.folder
+            && self.overwrite == other.overwrite
+            && self.file_stem == other.file_stem
+            && self.normalize == other.normalize
+            && self.split_tracks == other.split_tracks
+            && self.clip == other.clip
+            && self.encode == other.encode
+            && self.preset == other.preset
+            && self.status == other.status
+    }
+}
+
+/// What the session file holds: unfinished tasks in queue order and whether
+/// "Pause all" was on.
+#[derive(Clone, Default)]
+pub struct Session {
+    pub tasks: Vec<SavedTask>,
+    pub paused: bool,
+}
+
+impl Session {
+    pub fn same_state(&self, other: &Session) -> bool {
+        self.paused == other.paused
+            && self.tasks.len() == other.tasks.len()
+            && self.tasks.iter().zip(&other.tasks).all(|(a, b)| a.same_state(b))
+    }
+}
+
+#[derive(Deserialize)]
+struct VersionOnly {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SessionFile {
+    version: u32,
+    tasks: Vec<SavedTask>,
+    #[serde(default)]
+    paused: bool,
+}
+
+/// What the last run left unfinished; empty if there is no usable session file.
+pub fn load() -> Session {
+    let Some(text) = session_path().and_then(|path| fs::read_to_string(path).ok()) else { return Session::default() };
+    match serde_json::from_str::<VersionOnly>(&text) {
+        Ok(v) if v.version == SESSION_VERSION => {}
+        Ok(v) => {
+            app_log::warn(format!("Ignoring session file of unknown version {}", v.version));
+            return Session::default();
+        }
+        Err(e) => {
+            app_log::warn(format!("Ignoring corrupt session file: {}", e));
+            return Session::default();
+        }
+    }
+    match serde_json::from_str::<SessionFile>(&text) {
+        Ok(file) => Session { tasks: file.tasks, paused: file.paused },
+        Err(e) => {
+            app_log::warn(format!("Ignoring corrupt session file: {}", e));
+            Session::default()
+        }
+    }
+}
+
+/// A task from a queue file. Only `url` is required, so hand-written files work too;
+/// the rest falls back to the current settings, and a session file's status and
+/// progress are ignored.
+#[derive(Deserialize)]
+pub struct ImportedTask {
+    pub url: String,
+    pub title: Option<String>,
+    pub quality: Option<Quality>,
+    pub audio_format: Option<AudioFormat>,
+    pub folder: Option<String>,
+    #[serde(default)]
+    pub overwrite: bool,
+    pub file_stem: Option<String>,
+}
+
+/// What "Import queue…" read: the usable tasks and why the others were skipped, each
+/// with its entry number counting from 1.
+pub struct QueueImport {
+    pub tasks: Vec<(usize, ImportedTask)>,
+    pub errors: Vec<(usize, String)>,
+}
+
+#[derive(Deserialize)]
+struct QueueFile {
+    version: u32,
+    tasks: Vec<serde_json::Value>,
+}
+
+/// Reads a queue file, which has the session file's layout. Entries are checked one
+/// by one so a bad entry doesn't spoil the rest.
+pub fn import_queue(text: &str) -> Result<QueueImport, String> {
+    let file: QueueFile = serde_json::from_str(text).map_err(|e| t!("queue_import.bad_file", error = e))?;
+    if file.version != SESSION_VERSION {
+        return Err(t!("queue_import.bad_version", version = file.version));
+    }
+    let mut import = QueueImport { tasks: Vec::new(), errors: Vec::new() };
+    for (i, value) in file.tasks.into_iter().enumerate() {
+        let entry = i + 1;
+        match serde_json::from_value::<ImportedTask>(value) {
+            Ok(task) if !is_youtube_url(&task.url) => {
+                import.errors.push((entry, t!("queue_import.bad_url", url = task.url)));
+            }
+            Ok(task) if task.folder.as_deref().is_some_and(|f| f.trim().is_empty()) => {
+                import.errors.push((entry, t!("queue_import.empty_folder")));
+            }
+            Ok(task) => import.tasks.push((entry, task)),
+            Err(e) => import.errors.push((entry, e.to_string())),
+        }
+    }
+    Ok(import)
+}
+
+pub fn save(session: &Session) -> Result<(), Box<dyn std::error::Error>> {
+    let path = session_path().ok_or("No data directory")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = SessionFile { version: SESSION_VERSION, tasks: session.tasks.clone(), paused: session.paused };
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string(&file)?)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+fn session_path() -> Option<PathBuf> {
+    Some(paths::data_dir()?.join("session.json"))
+}
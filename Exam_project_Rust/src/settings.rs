@@ -0,0 +1,339 @@
+use std::{fs, path::PathBuf};
+use eframe::egui::Visuals;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use crate::{app_log, cache::DEFAULT_CACHE_BYTES, clipboard_watch::ClipboardAction, encode::EncodeProfile, model::{AudioFormat, Quality, SortOrder}, paths, rules::MoveRule, status_style::Palette};
+
+/// Range of the UI scale setting.
+pub const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.8..=2.0;
+
+/// Highest parallel download limit offered.
+pub const MAX_PARALLEL: u32 = 10;
+
+/// Minutes between two checks of a subscribed channel.
+pub const SUBSCRIPTION_INTERVAL_RANGE: std::ops::RangeInclusive<u32> = 15..=1440;
+
+/// How many download folders the folder dropdown remembers.
+const RECENT_FOLDERS: usize = 5;
+
+/// Settings file given with `--config`, used instead of the default one.
+static SETTINGS_FILE: OnceCell<PathBuf> = OnceCell::new();
+
+/// User preferences persisted to `settings.json` in the platform config directory.
+/// Missing fields fall back to their defaults so older files keep loading.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub download_folder: String,
+    /// Folders downloads went to, most recent first.
+    pub recent_folders: Vec<String>,
+    pub quality: Quality,
+    /// Used when `quality` is audio only.
+    pub audio_format: AudioFormat,
+    pub thumbnail_cache_mb: u64,
+    /// Downloads running at once, 0 for no limit. Further ones wait in the queue.
+    pub max_parallel: u32,
+    pub sort_order: SortOrder,
+    pub theme: ThemePreference,
+    /// Colors of status badges and progress bars.
+    pub status_palette: Palette,
+    /// Zoom on top of the display's own scale factor, 1.0 being 100%.
+    pub ui_scale: f32,
+    /// UI language code such as "de", `None` to follow the system locale.
+    pub language: Option<String>,
+    /// Window placement from the last session, `None` for the platform default.
+    pub window: Option<WindowGeometry>,
+    pub panel_width: Option<f32>,
+    /// One line per task in the downloads panel instead of full cards.
+    pub compact_list: bool,
+    pub watch_clipboard: bool,
+    pub clipboard_action: ClipboardAction,
+    /// Put a copied YouTube link into the empty URL field when the window gains focus.
+    pub fill_url_on_focus: bool,
+    /// Queue the links in `.txt` and `.url` files dropped into `watch_folder`.
+    pub watch_folder_enabled: bool,
+    pub watch_folder: String,
+    /// Preset the watch folder's links are queued with, `None` for the form's choices.
+    pub watch_preset: Option<String>,
+    /// `ytdlgui://` links were registered with the OS to open in this app.
+    pub protocol_handler: bool,
+    /// Desktop notification when a download finishes or fails.
+    pub notifications: bool,
+    pub notify_only_unfocused: bool,
+    /// What closing the window does while downloads are active.
+    pub exit_with_active: ExitAction,
+    /// Also write the log to daily files in the data directory. Read at startup.
+    pub log_file: bool,
+    /// Player for previews, a path or a program name; empty to look for mpv or VLC.
+    pub player: String,
+    /// Minutes between checks of each subscribed channel for new uploads.
+    pub subscription_interval_min: u32,
+    /// Where finished downloads are moved, first match wins.
+    pub move_rules: Vec<MoveRule>,
+    /// Named quality, format and folder combinations offered next to the Download button.
+    pub presets: Vec<Preset>,
+    /// "Delete file" removes files for good instead of moving them to the trash.
+    pub delete_permanently: bool,
+    /// New audio only downloads get their loudness normalized; each task can override it.
+    pub normalize_audio: bool,
+    /// Normalizing keeps the file as downloaded, as "<name>.orig".
+    pub keep_original_audio: bool,
+    /// Formats video downloads can be re-encoded to once downloaded.
+    pub encode_profiles: Vec<EncodeProfile>,
+    /// Name of the profile new video downloads are re-encoded with, `None` to keep them
+    /// as downloaded.
+    pub encode_profile: Option<String>,
+    /// Closing the window hides it to the tray instead of quitting.
+    #[cfg(feature = "tray")]
+    pub close_to_tray: bool,
+    /// Play a chime when downloads finish.
+    #[cfg(feature = "sound")]
+    pub completion_sound: bool,
+    #[cfg(feature = "sound")]
+    pub sound_when: SoundWhen,
+    /// Volume of the chime, 0.0 to 1.0.
+    #[cfg(feature = "sound")]
+    pub sound_volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            download_folder: "./downloads".to_string(),
+            recent_folders: Vec::new(),
+            quality: Quality::default(),
+            audio_format: AudioFormat::default(),
+            thumbnail_cache_mb: DEFAULT_CACHE_BYTES / (1024 * 1024),
+            max_parallel: 3,
+            sort_order: SortOrder::default(),
+            theme: ThemePreference::default(),
+            status_palette: Palette::default(),
+            ui_scale: 1.0,
+            language: None,
+            window: None,
+            panel_width: None,
+            compact_list: false,
+            watch_clipboard: false,
+            clipboard_action: ClipboardAction::default(),
+            fill_url_on_focus: false,
+            watch_folder_enabled: false,
+            watch_folder: String::new(),
+            watch_preset: None,
+            protocol_handler: false,
+            notifications: true,
+            notify_only_unfocused: true,
+            exit_with_active: ExitAction::default(),
+            log_file: false,
+            player: String::new(),
+            subscription_interval_min: 60,
+            move_rules: Vec::new(),
+            presets: Preset::defaults(),
+            delete_permanently: false,
+            normalize_audio: false,
+            keep_original_audio: false,
+            encode_profiles: EncodeProfile::defaults(),
+            encode_profile: None,
+            #[cfg(feature = "tray")]
+            close_to_tray: false,
+            #[cfg(feature = "sound")]
+            completion_sound: false,
+            #[cfg(feature = "sound")]
+            sound_when: SoundWhen::default(),
+            #[cfg(feature = "sound")]
+            sound_volume: 0.6,
+        }
+    }
+}
+
+/// Quality, format and folder picked together for one download, e.g. "Music" for MP3s
+/// in the music folder. Choosing one leaves the defaults in the form alone.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub quality: Quality,
+    /// Used when `quality` is audio only.
+    pub audio_format: AudioFormat,
+    /// Re-encoding profile for video downloads, by name.
+    pub encode_profile: Option<String>,
+    pub folder: String,
+}
+
+impl Preset {
+    /// Presets offered before the user defines their own.
+    pub fn defaults() -> Vec<Preset> {
+        vec![
+            Preset {
+                name: "Music".to_string(),
+                quality: Quality::AudioOnly,
+                audio_format: AudioFormat::Mp3,
+                encode_profile: None,
+                folder: "./downloads/Music".to_string(),
+            },
+            Preset {
+                name: "Video".to_string(),
+                quality: Quality::P1080,
+                audio_format: AudioFormat::default(),
+                encode_profile: Some("H.264 MP4".to_string()),
+                folder: "./downloads/Videos".to_string(),
+            },
+        ]
+    }
+
+    /// What the preset downloads, e.g. "Audio only · mp3 192k → ./downloads/Music".
+    pub fn summary(&self) -> String {
+        let format = if self.quality == Quality::AudioOnly {
+            Some(self.audio_format.describe())
+        } else {
+            self.encode_profile.clone()
+        };
+        let what = match format {
+            Some(format) => format!("{} · {}", self.quality.display_name(), format),
+            None => self.quality.display_name(),
+        };
+        format!("{} → {}", what, self.folder)
+    }
+}
+
+/// Outer position and inner size of the window, in points.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub maximized: bool,
+}
+
+/// Answer to closing the window while downloads are active, `Ask` unless the user
+/// ticked "Remember my choice".
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ExitAction {
+    #[default]
+    Ask,
+    /// Quit and cancel the downloads.
+    Quit,
+    /// Hide to the tray and let the downloads finish. Treated as `Ask` without a tray icon.
+    KeepRunning,
+}
+
+impl ExitAction {
+    pub const ALL: [ExitAction; 3] = [ExitAction::Ask, ExitAction::Quit, ExitAction::KeepRunning];
+
+    pub fn label(self) -> String {
+        match self {
+            ExitAction::Ask => t!("exit.ask"),
+            ExitAction::Quit => t!("exit.quit"),
+            ExitAction::KeepRunning => t!("exit.keep_running"),
+        }
+    }
+}
+
+/// Which finished downloads the completion chime plays for.
+#[cfg(feature = "sound")]
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SoundWhen {
+    EachDownload,
+    /// Once nothing is downloading or waiting any more.
+    #[default]
+    QueueDone,
+}
+
+#[cfg(feature = "sound")]
+impl SoundWhen {
+    pub const ALL: [SoundWhen; 2] = [SoundWhen::EachDownload, SoundWhen::QueueDone];
+
+    pub fn label(self) -> String {
+        match self {
+            SoundWhen::EachDownload => t!("sound.each_download"),
+            SoundWhen::QueueDone => t!("sound.queue_done"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ThemePreference {
+    #[default]
+    Dark,
+    Light,
+    System,
+}
+
+impl ThemePreference {
+    pub const ALL: [ThemePreference; 3] = [ThemePreference::Dark, ThemePreference::Light, ThemePreference::System];
+
+    pub fn label(self) -> String {
+        match self {
+            ThemePreference::Dark => t!("theme.dark"),
+            ThemePreference::Light => t!("theme.light"),
+            ThemePreference::System => t!("theme.system"),
+        }
+    }
+
+    /// Visuals to use, `system` being the OS theme when known (dark otherwise).
+    pub fn visuals(self, system: Option<eframe::Theme>) -> Visuals {
+        let dark = match self {
+            ThemePreference::Dark => true,
+            ThemePreference::Light => false,
+            ThemePreference::System => system != Some(eframe::Theme::Light),
+        };
+        if dark { Visuals::dark() } else { Visuals::light() }
+    }
+}
+
+impl Settings {
+    /// Loads the settings file, using defaults if it is missing or unreadable.
+    pub fn load() -> Self {
+        let Some(path) = settings_path() else { return Self::default() };
+        let Ok(text) = fs::read_to_string(&path) else { return Self::default() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            app_log::warn(format!("Ignoring corrupt settings file {}: {}", path.display(), e));
+            Self::default()
+        })
+    }
+
+    /// Whether a settings file was written before, i.e. this isn't the first launch.
+    pub fn saved_before() -> bool {
+        settings_path().is_some_and(|path| path.exists())
+    }
+
+    /// Moves `folder` to the front of the recent folders, keeping at most `RECENT_FOLDERS`.
+    pub fn remember_folder(&mut self, folder: &str) {
+        self.recent_folders.retain(|f| f != folder);
+        self.recent_folders.insert(0, folder.to_string());
+        self.recent_folders.truncate(RECENT_FOLDERS);
+    }
+
+    pub fn preset(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+
+    /// The profile picked for new video downloads, if it still exists.
+    pub fn selected_encode_profile(&self) -> Option<&EncodeProfile> {
+        let name = self.encode_profile.as_ref()?;
+        self.encode_profiles.iter().find(|p| &p.name == name)
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = settings_path().ok_or("No config directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write to a temp file first so a crash mid-write can't corrupt the settings
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// Loads and saves the settings at `path` from now on. Call before `load`.
+pub fn use_file(path: PathBuf) {
+    let _ = SETTINGS_FILE.set(path);
+}
+
+fn settings_path() -> Option<PathBuf> {
+    if let Some(path) = SETTINGS_FILE.get() {
+        return Some(path.clone());
+    }
+    Some(paths::config_dir()?.join("settings.json"))
+}
@@ -0,0 +1,28 @@
+//! The chime played when downloads finish. Each play gets a thread of its own that
+//! opens the audio device, so a missing or busy device only means no sound.
+use std::io::Cursor;
+use rodio::{Decoder, OutputStream, Sink};
+
+#[derive(rust_embed::RustEmbed)]
+#[folder = "assets/sounds/"]
+struct Sounds;
+
+/// Plays the completion chime at `volume` (0.0 to 1.0) without blocking the UI thread.
+pub fn play_done(volume: f32) {
+    std::thread::spawn(move || {
+        if let Err(e) = play("done.wav", volume) {
+            tracing::debug!(error = %e, "Couldn't play the completion sound");
+        }
+    });
+}
+
+fn play(name: &str, volume: f32) -> Result<(), Box<dyn std::error::Error>> {
+    let file = Sounds::get(name).ok_or("Sound not embedded")?;
+    // The stream stops playing when dropped, so it lives until the sound ends
+    let (_stream, handle) = OutputStream::try_default()?;
+    let sink = Sink::try_new(&handle)?;
+    sink.set_volume(volume);
+    sink.append(Decoder::new(Cursor::new(file.data))?);
+    sink.sleep_until_end();
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! How download statuses look wherever they are shown: cards, the compact list, the
+//! history and the tray tooltip. Every status has its own icon, so color is never the
+//! only cue.
+use chrono::Local;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::model::{format_duration, DownloadStatus};
+
+/// Colors the statuses are drawn in.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Okabe–Ito colors, which stay apart with red-green color blindness: blue for done
+    /// and vermillion for failed instead of green and red.
+    ColorBlind,
+}
+
+/// The `dark` or `light` variant of a custom color, whichever suits the current theme.
+pub fn themed(visuals: &egui::Visuals, dark: (u8, u8, u8), light: (u8, u8, u8)) -> egui::Color32 {
+    let (r, g, b) = if visuals.dark_mode { dark } else { light };
+    egui::Color32::from_rgb(r, g, b)
+}
+
+/// Icon of a status: hourglass, clock, arrow, gear, pause bars, check or cross.
+pub fn icon(status: &DownloadStatus) -> &'static str {
+    match status {
+        DownloadStatus::Queued => "⏳",
+        DownloadStatus::Scheduled(_) => "🕑",
+        DownloadStatus::Downloading => "⬇",
+        DownloadStatus::PostProcessing => "⚙",
+        DownloadStatus::Paused => "⏸",
+        DownloadStatus::Done => "✔",
+        DownloadStatus::Failed(_) => "✖",
+    }
+}
+
+pub fn label(status: &DownloadStatus) -> String {
+    match status {
+        DownloadStatus::Queued => t!("status.queued"),
+        DownloadStatus::Scheduled(at) => {
+            let left = (*at - Local::now()).num_seconds().max(0) as u64;
+            t!("status.scheduled", time = at.format("%H:%M"), left = format_duration(left))
+        }
+        DownloadStatus::Downloading => t!("status.downloading"),
+        DownloadStatus::PostProcessing => t!("status.processing"),
+        DownloadStatus::Paused => t!("status.paused"),
+        DownloadStatus::Done => t!("status.done"),
+        DownloadStatus::Failed(_) => t!("status.failed"),
+    }
+}
+
+/// Accent color of a status for badges and progress bars, with a dark- and a
+/// light-theme shade of each.
+pub fn color(status: &DownloadStatus, visuals: &egui::Visuals, palette: Palette) -> egui::Color32 {
+    let shade = |dark, light| themed(visuals, dark, light);
+    let gray = shade((160, 160, 160), (110, 110, 110));
+    match (status, palette) {
+        (DownloadStatus::Queued | DownloadStatus::Scheduled(_) | DownloadStatus::Paused, _) => gray,
+        (DownloadStatus::Downloading, Palette::Standard) => shade((90, 160, 255), (0, 90, 200)),
+        (DownloadStatus::PostProcessing, Palette::Standard) => shade((255, 190, 60), (170, 100, 0)),
+        (DownloadStatus::Done, Palette::Standard) => shade((110, 200, 110), (0, 120, 0)),
+        (DownloadStatus::Failed(_), Palette::Standard) => shade((255, 100, 90), (190, 20, 20)),
+        (DownloadStatus::Downloading, Palette::ColorBlind) => shade((204, 121, 167), (170, 68, 153)),
+        (DownloadStatus::PostProcessing, Palette::ColorBlind) => shade((240, 228, 66), (140, 120, 0)),
+        (DownloadStatus::Done, Palette::ColorBlind) => shade((86, 180, 233), (0, 114, 178)),
+        (DownloadStatus::Failed(_), Palette::ColorBlind) => shade((230, 120, 50), (213, 94, 0)),
+    }
+}
+
+/// Icon, label and color of a status together, as cards and the history show them.
+pub fn status_style(status: &DownloadStatus, visuals: &egui::Visuals, palette: Palette) -> (&'static str, String, egui::Color32) {
+    (icon(status), label(status), color(status, visuals, palette))
+}
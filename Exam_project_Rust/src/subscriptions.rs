@@ -0,0 +1,158 @@
+//! Channels checked for new uploads, and the inbox of what the checks found.
+use std::{
+    fs,
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    time::Duration,
+};
+use serde::{Deserialize, Serialize};
+use crate::{metadata::PlaylistMetadata, model::Quality, paths};
+
+/// How long `online` waits for YouTube to answer.
+const ONLINE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A channel whose new uploads are looked for every `Settings::subscription_interval_min`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    /// Uploads tab, as made by `links::channel_uploads_url`.
+    pub url: String,
+    /// Channel name, the URL until the first check.
+    pub title: String,
+    /// Newest upload seen so far. `None` before the first check, which only sets it.
+    pub last_seen: Option<String>,
+    /// Every upload the last check listed, newest first, so the next check still finds
+    /// its place if `last_seen` was deleted or made private in between.
+    #[serde(default)]
+    pub recent: Vec<String>,
+    /// Seconds since the Unix epoch of the last check, whether it worked or not.
+    pub last_checked: Option<i64>,
+    /// Why the last check failed, `None` if it worked.
+    pub last_error: Option<String>,
+    /// Queue new uploads right away instead of listing them under "New videos".
+    pub auto_download: bool,
+    /// Quality for its downloads, `None` for the current setting.
+    pub quality: Option<Quality>,
+    /// Folder for its downloads, `None` for the current setting.
+    pub folder: Option<String>,
+}
+
+impl Subscription {
+    pub fn new(url: String) -> Self {
+        Self {
+            title: url.clone(),
+            url,
+            last_seen: None,
+            recent: Vec::new(),
+            last_checked: None,
+            last_error: None,
+            auto_download: false,
+            quality: None,
+            folder: None,
+        }
+    }
+}
+
+/// An upload a check found, waiting in the inbox to be downloaded or dismissed.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NewVideo {
+    pub video_id: String,
+    pub title: String,
+    /// `Subscription::url` of the channel it came from.
+    pub channel_url: String,
+}
+
+/// Subscribed channels and the inbox, persisted as JSON in the data directory.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Subscriptions {
+    pub channels: Vec<Subscription>,
+    pub inbox: Vec<NewVideo>,
+}
+
+impl Subscriptions {
+    /// Loads the subscriptions file; a missing or corrupt file gives none.
+    pub fn load() -> Self {
+        let Some(path) = subscriptions_path() else { return Self::default() };
+        let Ok(text) = fs::read_to_string(&path) else { return Self::default() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Ignoring corrupt subscriptions file");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let write = || -> Result<(), Box<dyn std::error::Error>> {
+            let path = subscriptions_path().ok_or("No data directory")?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+            fs::rename(tmp, path)?;
+            Ok(())
+        };
+        if let Err(e) = write() {
+            tracing::error!(error = %e, "Failed to save subscriptions");
+        }
+    }
+
+    /// Index of the channel to check next: the one checked longest ago, if that was
+    /// at least `interval` before `now` (seconds since the Unix epoch).
+    pub fn due(&self, interval: Duration, now: i64) -> Option<usize> {
+        let (index, sub) = self.channels.iter().enumerate().min_by_key(|(_, s)| s.last_checked)?;
+        let due = sub.last_checked.is_none_or(|at| now - at >= interval.as_secs() as i64);
+        due.then_some(index)
+    }
+
+    /// Records a check of the channel at `url`, returning its new uploads oldest first.
+    pub fn checked(&mut self, url: &str, listing: Result<PlaylistMetadata, String>, now: i64) -> Vec<NewVideo> {
+        let Some(sub) = self.channels.iter_mut().find(|s| s.url == url) else { return Vec::new() };
+        sub.last_checked = Some(now);
+        let listing = match listing {
+            Ok(listing) => listing,
+            Err(e) => {
+                sub.last_error = Some(e);
+                return Vec::new();
+            }
+        };
+        sub.last_error = None;
+        if let Some(name) = listing.channel.clone().or(listing.title.clone()) {
+            sub.title = name;
+        }
+        let new = match &sub.last_seen {
+            Some(last_seen) => {
+                let mut seen = sub.recent.clone();
+                seen.push(last_seen.clone());
+                listing.entries_since(&seen)
+            }
+            // Subscribing means from now on
+            None => &[],
+        };
+        let new: Vec<NewVideo> = new
+            .iter()
+            .rev()
+            .filter_map(|e| {
+                Some(NewVideo {
+                    video_id: e.id.clone()?,
+                    title: e.title.clone().unwrap_or_else(|| e.id.clone().unwrap_or_default()),
+                    channel_url: sub.url.clone(),
+                })
+            })
+            .collect();
+        if let Some(newest) = listing.entries.iter().find_map(|e| e.id.clone()) {
+            sub.last_seen = Some(newest);
+            sub.recent = listing.entries.iter().filter_map(|e| e.id.clone()).collect();
+        }
+        new
+    }
+}
+
+/// Whether YouTube can be reached at all. Blocks for up to `ONLINE_TIMEOUT`.
+pub fn online() -> bool {
+    let Ok(mut addrs) = ("www.youtube.com", 443).to_socket_addrs() else { return false };
+    addrs.next().is_some_and(|addr| TcpStream::connect_timeout(&addr, ONLINE_TIMEOUT).is_ok())
+}
+
+fn subscriptions_path() -> Option<PathBuf> {
+    Some(paths::data_dir()?.join("subscriptions.json"))
+}
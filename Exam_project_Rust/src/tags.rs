@@ -0,0 +1,66 @@
+use std::{ffi::OsString, fs, path::Path};
+use crate::{ffmpeg, metadata::VideoMetadata, paths::TempFile};
+
+/// What the tag editor writes into a finished audio file.
+#[derive(Clone, Default)]
+pub struct Tags {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub year: String,
+}
+
+impl Tags {
+    /// YouTube Music's track fields when the video has them, otherwise a guess
+    /// from the title and channel.
+    pub fn guess(meta: &VideoMetadata) -> Self {
+        let title = meta.title.as_deref().unwrap_or_default();
+        let uploader = meta.uploader.as_deref().or(meta.channel.as_deref());
+        let mut tags = Self::from_title(title, uploader, meta.upload_date.as_deref());
+        if let Some(track) = &meta.track {
+            tags.title = track.clone();
+        }
+        if let Some(artist) = &meta.artist {
+            tags.artist = artist.clone();
+        }
+        if let Some(album) = &meta.album {
+            tags.album = album.clone();
+        }
+        if let Some(year) = meta.release_year {
+            tags.year = year.to_string();
+        }
+        tags
+    }
+
+    /// Splits "Artist - Title" videos, falling back to the channel as artist
+    /// ("Artist - Topic" channels lose the suffix). `date` starts with the year.
+    pub fn from_title(title: &str, uploader: Option<&str>, date: Option<&str>) -> Self {
+        let uploader = uploader.map(|u| u.strip_suffix(" - Topic").unwrap_or(u));
+        let (artist, title) = match title.split_once(" - ") {
+            Some((artist, title)) => (artist.trim(), title.trim()),
+            None => (uploader.unwrap_or_default(), title),
+        };
+        Self {
+            title: title.to_string(),
+            artist: artist.to_string(),
+            album: String::new(),
+            year: date.and_then(|d| d.get(..4)).unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// Rewrites `path` with `tags` through ffmpeg, copying the streams. The result goes to a
+/// temporary file next to it that only replaces the original once ffmpeg succeeded.
+pub async fn write_tags(path: &Path, tags: &Tags) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let tmp = TempFile::next_to(path, "tagging").ok_or("Not a file name")?;
+    let mut args: Vec<OsString> = ["-v", "error", "-y", "-i"].map(OsString::from).into();
+    args.push(path.into());
+    args.extend(["-map", "0", "-c", "copy", "-map_metadata", "0"].map(OsString::from));
+    for (key, value) in [("title", &tags.title), ("artist", &tags.artist), ("album", &tags.album), ("date", &tags.year)] {
+        args.extend(["-metadata".into(), format!("{}={}", key, value.trim()).into()]);
+    }
+    args.push(tmp.0.clone().into());
+    ffmpeg::run(args).await?;
+    fs::rename(&tmp.0, path)?;
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+use windows::Win32::{
+    Foundation::HWND,
+    System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER},
+    UI::Shell::{ITaskbarList3, TaskbarList, TBPFLAG, TBPF_ERROR, TBPF_NOPROGRESS, TBPF_NORMAL, TBPF_PAUSED},
+};
+use crate::app_log;
+
+/// What the taskbar button shows, progress being 0.0 to 1.0.
+#[derive(Clone, Copy)]
+pub enum TaskbarProgress {
+    None,
+    Normal(f32),
+    Paused(f32),
+    Error(f32),
+}
+
+/// Progress bar on the window's taskbar button (ITaskbarList3).
+pub struct Taskbar {
+    list: ITaskbarList3,
+    hwnd: HWND,
+    /// State and value last sent, in thousandths, to skip redundant COM calls.
+    shown: Option<(TBPFLAG, u64)>,
+}
+
+impl Taskbar {
+    /// `None` if the window handle or the taskbar COM object isn't available.
+    pub fn new(frame: &eframe::Frame) -> Option<Self> {
+        let RawWindowHandle::Win32(handle) = frame.window_handle().ok()?.as_raw() else { return None };
+        // winit has already initialized COM on the UI thread
+        let list: ITaskbarList3 = unsafe { CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER) }
+            .map_err(|e| app_log::warn(format!("Taskbar progress unavailable: {}", e)))
+            .ok()?;
+        unsafe { list.HrInit() }.ok()?;
+        Some(Self { list, hwnd: HWND(handle.hwnd.get()), shown: None })
+    }
+
+    pub fn set(&mut self, progress: TaskbarProgress) {
+        let (flag, value) = match progress {
+            TaskbarProgress::None => (TBPF_NOPROGRESS, 0.0),
+            TaskbarProgress::Normal(v) => (TBPF_NORMAL, v),
+            TaskbarProgress::Paused(v) => (TBPF_PAUSED, v),
+            TaskbarProgress::Error(v) => (TBPF_ERROR, v),
+        };
+        let state = (flag, (value.clamp(0.0, 1.0) * 1000.0) as u64);
+        if self.shown == Some(state) {
+            return;
+        }
+        self.shown = Some(state);
+        unsafe {
+            let _ = self.list.SetProgressState(self.hwnd, flag);
+            if flag != TBPF_NOPROGRESS {
+                let _ = self.list.SetProgressValue(self.hwnd, state.1, 1000);
+            }
+        }
+    }
+}
@@ -1,10 +1,220 @@
-﻿use eframe::egui::ColorImage;
+﻿use std::{fmt, thread, time::Duration};
+use eframe::egui::ColorImage;
+use image::{imageops, imageops::FilterType, Rgba, RgbaImage};
+use once_cell::sync::Lazy;
+use reqwest::{blocking::Client, StatusCode};
+use crate::{cache::{ThumbnailCache, THUMBNAIL_CACHE}, downloader::Asset};
+
+/// Size thumbnails are scaled to before they become textures.
+///
+/// A full 480x360 RGBA texture is ~675 KB, so 100 cards held ~66 MB of GPU
+/// memory; at 160x90 the same 100 cards need ~5.6 MB.
+pub const THUMBNAIL_SIZE: [u32; 2] = [160, 90];
+
+/// Timeouts so a bad network can't pin a blocking-pool thread for minutes.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(8);
+
+static CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+/// Dimensions of the generic image YouTube serves for videos without a thumbnail.
+const YOUTUBE_PLACEHOLDER_SIZE: (u32, u32) = (120, 90);
+
+/// Attempts made by `fetch_thumbnail_with_retry` before giving up.
+const FETCH_ATTEMPTS: u32 = 3;
+/// Delay before the second attempt, doubled for each one after.
+const FETCH_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Why a thumbnail couldn't be produced.
+#[derive(Debug)]
+pub enum ThumbnailError {
+    /// The server answered with a non-success status.
+    Http(StatusCode),
+    /// Connection, DNS, TLS or timeout failure.
+    Network(String),
+    /// The body wasn't an image we can decode.
+    Decode(String),
+    /// YouTube served its grey 120x90 "no thumbnail" image.
+    Placeholder,
+    /// The fetch was dropped before it started because the image is no longer needed.
+    Skipped,
+    /// The fetch panicked; the message is in the log.
+    Internal,
+}
+
+impl ThumbnailError {
+    /// Whether trying again later has a chance of succeeding.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ThumbnailError::Network(_) | ThumbnailError::Skipped => true,
+            ThumbnailError::Http(status) => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            ThumbnailError::Decode(_) | ThumbnailError::Placeholder | ThumbnailError::Internal => false,
+        }
+    }
+}
+
+impl fmt::Display for ThumbnailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThumbnailError::Http(status) => write!(f, "HTTP {}", status),
+            ThumbnailError::Network(e) => write!(f, "network error: {}", e),
+            ThumbnailError::Decode(e) => write!(f, "decode error: {}", e),
+            ThumbnailError::Placeholder => write!(f, "no thumbnail (placeholder image)"),
+            ThumbnailError::Skipped => write!(f, "skipped, no longer visible"),
+            ThumbnailError::Internal => write!(f, "internal error"),
+        }
+    }
+}
+
+impl std::error::Error for ThumbnailError {}
+
+/// Calls `fetch_thumbnail` (or `fetch_thumbnail_from_url` when `url` is given) up to
+/// `FETCH_ATTEMPTS` times with exponential backoff, stopping early on errors that won't go away.
+/// Blocks the calling thread while waiting, so run it on a blocking pool.
+pub fn fetch_thumbnail_with_retry(
+    video_id: &str,
+    url: Option<&str>,
+    target: [u32; 2],
+) -> Result<ColorImage, ThumbnailError> {
+    let mut delay = FETCH_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        let result = match url {
+            Some(url) => fetch_thumbnail_from_url(video_id, url, target),
+            None => fetch_thumbnail(video_id, target),
+        };
+        match result {
+            Err(e) if e.is_retryable() && attempt < FETCH_ATTEMPTS => {
+                tracing::debug!(video_id, attempt, error = %e, "Thumbnail fetch failed, retrying");
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
 
 /// Module for downloading and decoding video thumbnails from YouTube.
-pub fn fetch_thumbnail(video_id: &str) -> Option<ColorImage> {
-    let url = format!("https://img.youtube.com/vi/{}/hqdefault.jpg", video_id);
-    let resp = reqwest::blocking::get(&url).ok()?.bytes().ok()?;
-    let img = image::load_from_memory(&resp).ok()?.to_rgba8();
+/// The image is scaled to fit `target` ([width, height]) with letterboxing.
+pub fn fetch_thumbnail(video_id: &str, target: [u32; 2]) -> Result<ColorImage, ThumbnailError> {
+    // WebP first since the best thumbnails are often only published that way
+    fetch_first_decodable(THUMBNAIL_CACHE.as_ref(), video_id, &thumbnail_urls(video_id), target, true)
+}
+
+/// Same as `fetch_thumbnail` but for an arbitrary thumbnail URL, e.g. the one
+/// reported by the yt-dlp metadata for non-YouTube sites. `cache_key` names the cache entry.
+pub fn fetch_thumbnail_from_url(
+    cache_key: &str,
+    url: &str,
+    target: [u32; 2],
+) -> Result<ColorImage, ThumbnailError> {
+    fetch_first_decodable(THUMBNAIL_CACHE.as_ref(), cache_key, &[url.to_string()], target, false)
+}
+
+/// Returns the image `cache` holds for `cache_key`, otherwise the first of `urls` that
+/// downloads and decodes, which is then cached. When every candidate fails the error of
+/// the last one is returned. `youtube` as for `decode_thumbnail`.
+pub fn fetch_first_decodable(
+    cache: Option<&ThumbnailCache>,
+    cache_key: &str,
+    urls: &[String],
+    target: [u32; 2],
+    youtube: bool,
+) -> Result<ColorImage, ThumbnailError> {
+    if let Some(c) = cache && let Some(bytes) = c.get(cache_key) {
+        if let Ok(img) = decode_thumbnail(&bytes, target, youtube) {
+            return Ok(img);
+        }
+        // Corrupt entry, drop it and fetch a fresh copy
+        tracing::warn!(cache_key, "Dropping undecodable cached thumbnail");
+        c.remove(cache_key);
+    }
+
+    let mut last_err = ThumbnailError::Network("no thumbnail URL".to_string());
+    for url in urls {
+        let img = download(url).and_then(|bytes| {
+            decode_thumbnail(&bytes, target, youtube).map(|img| (img, bytes))
+        });
+        match img {
+            Ok((img, bytes)) => {
+                if let Some(c) = cache {
+                    c.put(cache_key, &bytes);
+                }
+                return Ok(img);
+            }
+            Err(e) => {
+                tracing::debug!(url, error = %e, "Thumbnail candidate failed");
+                last_err = e;
+            }
+        }
+    }
+    Err(last_err)
+}
+
+/// Where YouTube publishes a video's thumbnail, in the order they are tried.
+pub fn thumbnail_urls(video_id: &str) -> [String; 2] {
+    [
+        format!("https://i.ytimg.com/vi_webp/{}/hqdefault.webp", video_id),
+        format!("https://img.youtube.com/vi/{}/hqdefault.jpg", video_id),
+    ]
+}
+
+/// A timed-out request ends up as `Network`, so it shows the unavailable
+/// placeholder and goes through the normal retry path.
+fn download(url: &str) -> Result<Vec<u8>, ThumbnailError> {
+    let resp = CLIENT.get(url).send().map_err(|e| ThumbnailError::Network(e.to_string()))?;
+    if !resp.status().is_success() {
+        return Err(ThumbnailError::Http(resp.status()));
+    }
+    let bytes = resp.bytes().map_err(|e| ThumbnailError::Network(e.to_string()))?;
+    Ok(bytes.to_vec())
+}
+
+/// Embedded image shown on cards whose thumbnail is still loading or unavailable.
+pub fn placeholder_image(target: [u32; 2]) -> Option<ColorImage> {
+    let data = Asset::get("placeholder.png")?;
+    decode_thumbnail(&data.data, target, false).ok()
+}
+
+/// Decodes an image and letterboxes it to `target`. With `youtube`, the grey image
+/// YouTube serves for videos without a thumbnail counts as an error.
+pub fn decode_thumbnail(
+    bytes: &[u8],
+    target: [u32; 2],
+    youtube: bool,
+) -> Result<ColorImage, ThumbnailError> {
+    let img = image::load_from_memory(bytes)
+        .map_err(|e| ThumbnailError::Decode(e.to_string()))?
+        .to_rgba8();
+    if youtube && img.dimensions() == YOUTUBE_PLACEHOLDER_SIZE {
+        return Err(ThumbnailError::Placeholder);
+    }
+    let img = letterbox(&img, target);
     let size = [img.width() as usize, img.height() as usize];
-    Some(ColorImage::from_rgba_unmultiplied(size, &img))
+    Ok(ColorImage::from_rgba_unmultiplied(size, &img))
+}
+
+/// Scales `img` to fit inside `target` keeping its aspect ratio, padding the rest with black.
+fn letterbox(img: &RgbaImage, [tw, th]: [u32; 2]) -> RgbaImage {
+    let (w, h) = img.dimensions();
+    if w == 0 || h == 0 || tw == 0 || th == 0 {
+        return img.clone();
+    }
+    let scale = (tw as f32 / w as f32).min(th as f32 / h as f32);
+    let nw = ((w as f32 * scale).round() as u32).clamp(1, tw);
+    let nh = ((h as f32 * scale).round() as u32).clamp(1, th);
+    let resized = imageops::resize(img, nw, nh, FilterType::Triangle);
+
+    let mut canvas = RgbaImage::from_pixel(tw, th, Rgba([0, 0, 0, 255]));
+    imageops::overlay(&mut canvas, &resized, ((tw - nw) / 2).into(), ((th - nh) / 2).into());
+    canvas
 }
@@ -0,0 +1,84 @@
+use std::time::{Duration, Instant};
+use eframe::egui;
+
+/// How long a toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ToastKind {
+    Info,
+    Error,
+}
+
+struct Toast {
+    id: u64,
+    text: String,
+    kind: ToastKind,
+    expires: Instant,
+    /// Label of the button next to the text; clicks are reported by `show`.
+    action: Option<String>,
+}
+
+/// Short-lived messages stacked in the bottom-right corner of the window.
+#[derive(Default)]
+pub struct Toasts {
+    items: Vec<Toast>,
+    next_id: u64,
+}
+
+impl Toasts {
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(text.into(), ToastKind::Info, TOAST_DURATION, None);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(text.into(), ToastKind::Error, TOAST_DURATION, None);
+    }
+
+    /// An info toast with a button, shown for `duration`. Returns the id `show`
+    /// reports a click on the button with.
+    pub fn with_action(&mut self, text: impl Into<String>, action: impl Into<String>, duration: Duration) -> u64 {
+        self.push(text.into(), ToastKind::Info, duration, Some(action.into()))
+    }
+
+    fn push(&mut self, text: String, kind: ToastKind, duration: Duration, action: Option<String>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.items.push(Toast { id, text, kind, expires: Instant::now() + duration, action });
+        id
+    }
+
+    /// Draws the toasts and returns the ids of those whose button was clicked,
+    /// which are closed.
+    pub fn show(&mut self, ctx: &egui::Context) -> Vec<u64> {
+        let now = Instant::now();
+        self.items.retain(|t| t.expires > now);
+        if self.items.is_empty() {
+            return Vec::new();
+        }
+        let mut clicked = Vec::new();
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-12.0, -12.0])
+            .show(ctx, |ui| {
+                for toast in &self.items {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        let color = match toast.kind {
+                            ToastKind::Info => ui.visuals().text_color(),
+                            ToastKind::Error => ui.visuals().error_fg_color,
+                        };
+                        ui.horizontal(|ui| {
+                            ui.colored_label(color, &toast.text);
+                            if let Some(action) = &toast.action
+                                && ui.button(action).clicked()
+                            {
+                                clicked.push(toast.id);
+                            }
+                        });
+                    });
+                }
+            });
+        self.items.retain(|t| !clicked.contains(&t.id));
+        ctx.request_repaint_after(Duration::from_millis(250));
+        clicked
+    }
+}
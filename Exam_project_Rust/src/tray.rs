@@ -0,0 +1,202 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{channel, Receiver, Sender},
+    Arc, Mutex,
+};
+use eframe::egui;
+use tray_icon::{
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    Icon, TrayIcon, TrayIconBuilder,
+};
+use crate::{app_log, model::DownloadStatus, status_style};
+
+const SHOW_ID: &str = "show";
+const PAUSE_ALL_ID: &str = "pause_all";
+const RESUME_ALL_ID: &str = "resume_all";
+const QUIT_ID: &str = "quit";
+
+/// Menu actions that need the app state, handled in `update()`.
+pub enum TrayCommand {
+    PauseAll,
+    ResumeAll,
+}
+
+/// Tray icon with a "Show window / Pause all / Resume all / Quit" menu, grayed out
+/// while downloads are paused.
+///
+/// Showing the window and quitting are handled directly in the menu event handler,
+/// since `update()` doesn't run while the window is hidden.
+pub struct Tray {
+    commands: Receiver<TrayCommand>,
+    quit_requested: Arc<AtomicBool>,
+    icon: TrayHandle,
+    last_tooltip: String,
+    paused: bool,
+}
+
+impl Tray {
+    pub fn new(ctx: &egui::Context) -> Option<Self> {
+        let (tx, commands) = channel();
+        let quit_requested = Arc::new(AtomicBool::new(false));
+        set_menu_handler(ctx.clone(), tx, Arc::clone(&quit_requested));
+        let icon = TrayHandle::spawn()?;
+        Some(Self { commands, quit_requested, icon, last_tooltip: String::new(), paused: false })
+    }
+
+    pub fn poll(&self) -> Option<TrayCommand> {
+        self.commands.try_recv().ok()
+    }
+
+    /// Set once "Quit" was picked, so the close request isn't turned into hiding.
+    pub fn quit_requested(&self) -> bool {
+        self.quit_requested.load(Ordering::Relaxed)
+    }
+
+    /// Shows "idle", "paused" or the number of running downloads as the tooltip.
+    pub fn set_downloading(&mut self, count: usize) {
+        let tooltip = match count {
+            0 if self.paused => t!("tray.paused", icon = status_style::icon(&DownloadStatus::Paused)),
+            0 => t!("tray.idle"),
+            n => t!("tray.downloading", icon = status_style::icon(&DownloadStatus::Downloading), count = n),
+        };
+        if tooltip != self.last_tooltip {
+            self.icon.set_tooltip(&tooltip);
+            self.last_tooltip = tooltip;
+        }
+    }
+
+    /// Switches to the gray icon while "Pause all" is on.
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused != self.paused {
+            self.paused = paused;
+            self.icon.set_paused(paused);
+        }
+    }
+}
+
+fn set_menu_handler(ctx: egui::Context, tx: Sender<TrayCommand>, quit_requested: Arc<AtomicBool>) {
+    // The handler has to be Sync, which a bare Sender isn't
+    let tx = Mutex::new(tx);
+    MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+        if event.id == SHOW_ID {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        } else if event.id == PAUSE_ALL_ID {
+            let _ = tx.lock().unwrap().send(TrayCommand::PauseAll);
+        } else if event.id == RESUME_ALL_ID {
+            let _ = tx.lock().unwrap().send(TrayCommand::ResumeAll);
+        } else if event.id == QUIT_ID {
+            quit_requested.store(true, Ordering::Relaxed);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+        ctx.request_repaint();
+    }));
+}
+
+/// Must run on the thread that owns the platform event loop.
+fn build_icon() -> Option<TrayIcon> {
+    let menu = Menu::new();
+    let items = menu.append_items(&[
+        &MenuItem::with_id(SHOW_ID, t!("tray.show"), true, None),
+        &MenuItem::with_id(PAUSE_ALL_ID, t!("tray.pause_all"), true, None),
+        &MenuItem::with_id(RESUME_ALL_ID, t!("tray.resume_all"), true, None),
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id(QUIT_ID, t!("tray.quit"), true, None),
+    ]);
+    if let Err(e) = items {
+        app_log::warn(format!("Failed to build tray menu: {}", e));
+        return None;
+    }
+    TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip(t!("app.title"))
+        .with_icon(app_icon(false)?)
+        .build()
+        .map_err(|e| app_log::warn(format!("Failed to create tray icon: {}", e)))
+        .ok()
+}
+
+/// A plain rounded square, generated so no extra asset is needed: red, or gray
+/// while paused.
+fn app_icon(paused: bool) -> Option<Icon> {
+    let [r, g, b] = if paused { [128, 128, 128] } else { [230, 33, 23] };
+    const SIZE: u32 = 32;
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let (dx, dy) = (x.min(SIZE - 1 - x), y.min(SIZE - 1 - y));
+            let corner = dx < 4 && dy < 4 && (4 - dx).pow(2) + (4 - dy).pow(2) > 16;
+            let alpha = if corner { 0 } else { 255 };
+            rgba.extend_from_slice(&[r, g, b, alpha]);
+        }
+    }
+    Icon::from_rgba(rgba, SIZE, SIZE).ok()
+}
+
+/// Changes forwarded to the GTK thread on Linux.
+#[cfg(target_os = "linux")]
+enum IconUpdate {
+    Tooltip(String),
+    Paused(bool),
+}
+
+/// On Linux the icon needs a GTK main loop, so it lives on its own thread and
+/// tooltip and icon changes are forwarded to it.
+#[cfg(target_os = "linux")]
+struct TrayHandle(Sender<IconUpdate>);
+
+#[cfg(target_os = "linux")]
+impl TrayHandle {
+    fn spawn() -> Option<Self> {
+        let (tx, rx) = channel::<IconUpdate>();
+        std::thread::spawn(move || {
+            if let Err(e) = gtk::init() {
+                app_log::warn(format!("Failed to start GTK for the tray icon: {}", e));
+                return;
+            }
+            let Some(icon) = build_icon() else { return };
+            gtk::glib::timeout_add_local(std::time::Duration::from_millis(250), move || {
+                while let Ok(update) = rx.try_recv() {
+                    match update {
+                        IconUpdate::Tooltip(tooltip) => {
+                            let _ = icon.set_tooltip(Some(tooltip));
+                        }
+                        IconUpdate::Paused(paused) => {
+                            let _ = icon.set_icon(app_icon(paused));
+                        }
+                    }
+                }
+                gtk::glib::ControlFlow::Continue
+            });
+            gtk::main();
+        });
+        Some(Self(tx))
+    }
+
+    fn set_tooltip(&self, tooltip: &str) {
+        let _ = self.0.send(IconUpdate::Tooltip(tooltip.to_string()));
+    }
+
+    fn set_paused(&self, paused: bool) {
+        let _ = self.0.send(IconUpdate::Paused(paused));
+    }
+}
+
+/// Windows and macOS drive the icon from the window's own event loop.
+#[cfg(not(target_os = "linux"))]
+struct TrayHandle(TrayIcon);
+
+#[cfg(not(target_os = "linux"))]
+impl TrayHandle {
+    fn spawn() -> Option<Self> {
+        build_icon().map(Self)
+    }
+
+    fn set_tooltip(&self, tooltip: &str) {
+        let _ = self.0.set_tooltip(Some(tooltip));
+    }
+
+    fn set_paused(&self, paused: bool) {
+        let _ = self.0.set_icon(app_icon(paused));
+    }
+}
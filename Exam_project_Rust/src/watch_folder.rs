@@ -0,0 +1,159 @@
+//! Queues the links in `.txt` and `.url` files dropped into a folder, for scripts and
+//! other programs that can't drive the window. The folder is polled on the runtime and
+//! a file is only read once its size and modification time stayed the same for a whole
+//! poll, so one that is still being written waits for the next round.
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tokio::{runtime::Handle, task::AbortHandle};
+use crate::{app_log, links};
+
+/// How often the folder is listed.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Subfolder that read files are moved into.
+pub const PROCESSED: &str = "processed";
+/// Reads of a settled file that may fail (locked by the writer, cut off mid-character)
+/// before it is reported and left alone until it changes.
+const READ_ATTEMPTS: u32 = 5;
+
+/// Something the watcher found, picked up by the app on its next frame.
+pub enum WatchEvent {
+    /// The links in a file, which has been moved to the processed folder.
+    File { name: String, urls: Vec<String> },
+    Error(String),
+}
+
+/// Size and modification time, which stop changing once the writer is done.
+type Signature = (u64, Option<SystemTime>);
+
+struct Seen {
+    signature: Signature,
+    failed_reads: u32,
+}
+
+/// What is known about the files in the folder between two polls.
+struct Scanner {
+    folder: PathBuf,
+    seen: HashMap<PathBuf, Seen>,
+    /// Whether listing the folder failed last time, so the error is reported once.
+    unreadable: bool,
+}
+
+impl Scanner {
+    fn scan(&mut self) -> Vec<WatchEvent> {
+        let mut events = Vec::new();
+        let entries = match fs::read_dir(&self.folder) {
+            Ok(entries) => entries,
+            Err(e) => {
+                if !self.unreadable {
+                    events.push(WatchEvent::Error(t!("watch.folder_error", folder = self.folder.display(), error = e)));
+                }
+                self.unreadable = true;
+                return events;
+            }
+        };
+        self.unreadable = false;
+        let mut present = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(meta) = entry.metadata() else { continue };
+            if !meta.is_file() || !is_link_file(&path) {
+                continue;
+            }
+            present.push(path.clone());
+            let signature = (meta.len(), meta.modified().ok());
+            match self.seen.get_mut(&path) {
+                Some(seen) if seen.signature == signature => {
+                    if seen.failed_reads >= READ_ATTEMPTS {
+                        continue;
+                    }
+                }
+                // New, still being written, or replaced after failed reads: wait for it to settle
+                _ => {
+                    self.seen.insert(path, Seen { signature, failed_reads: 0 });
+                    continue;
+                }
+            }
+            match read(&path) {
+                Ok(urls) => {
+                    self.seen.remove(&path);
+                    events.push(self.processed(&path, urls));
+                }
+                Err(e) => {
+                    let Some(seen) = self.seen.get_mut(&path) else { continue };
+                    seen.failed_reads += 1;
+                    if seen.failed_reads == READ_ATTEMPTS {
+                        events.push(WatchEvent::Error(t!("watch.read_error", file = path.display(), error = e)));
+                    }
+                }
+            }
+        }
+        self.seen.retain(|path, _| present.contains(path));
+        events
+    }
+
+    /// Moves a read file out of the way and reports its links.
+    fn processed(&self, path: &Path, urls: Vec<String>) -> WatchEvent {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        match move_to_processed(path, &self.folder.join(PROCESSED)) {
+            Ok(_) => WatchEvent::File { name, urls },
+            // Queueing it anyway would queue it again on every poll
+            Err(e) => WatchEvent::Error(t!("watch.move_error", file = name, error = e)),
+        }
+    }
+}
+
+fn is_link_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("txt") || ext.eq_ignore_ascii_case("url"))
+}
+
+/// The links in a file. An empty file is most likely still being created, so it
+/// counts as a failed read until it has content or runs out of attempts.
+fn read(path: &Path) -> io::Result<Vec<String>> {
+    if fs::metadata(path)?.len() == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "empty file"));
+    }
+    links::urls_from_file(path)
+}
+
+/// Moves `file` into `folder` under its own name, numbered if that is taken.
+fn move_to_processed(file: &Path, folder: &Path) -> io::Result<PathBuf> {
+    fs::create_dir_all(folder)?;
+    let stem = file.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = file.extension().map(|e| e.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut target = folder.join(format!("{}.{}", stem, extension));
+    let mut n = 2;
+    while target.exists() {
+        target = folder.join(format!("{} ({}).{}", stem, n, extension));
+        n += 1;
+    }
+    fs::rename(file, &target)?;
+    Ok(target)
+}
+
+/// Polls `folder` on `runtime` until the returned handle is aborted, handing what it
+/// finds to `found`.
+pub fn spawn(runtime: &Handle, folder: PathBuf, found: impl Fn(Vec<WatchEvent>) + Send + 'static) -> AbortHandle {
+    app_log::info(format!("Watching {} for link files", folder.display()));
+    let mut scanner = Scanner { folder, seen: HashMap::new(), unreadable: false };
+    runtime
+        .spawn(async move {
+            loop {
+                let scanned = tokio::task::spawn_blocking(move || {
+                    let events = scanner.scan();
+                    (scanner, events)
+                })
+                .await;
+                let Ok((back, events)) = scanned else { return };
+                scanner = back;
+                if !events.is_empty() {
+                    found(events);
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .abort_handle()
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::path::Path;
+use exam_project_rust::{
+    chapters::{split, track_args, track_file_name, tracks_folder, Album},
+    metadata::{Chapter, VideoMetadata},
+};
+use common::{text, TempDir};
+
+fn album() -> Album {
+    let json = r#"{"title": "Full Album", "chapters": [
+        {"start_time": 0.0, "end_time": 83.5, "title": "Intro"},
+        {"start_time": 83.5, "end_time": 300.0, "title": "Song: Part 2"}
+    ]}"#;
+    let meta: VideoMetadata = serde_json::from_str(json).unwrap();
+    Album { title: meta.title.unwrap(), artist: Some("Band".into()), chapters: meta.chapters.unwrap() }
+}
+
+#[test]
+fn chapters_come_from_the_info_json() {
+    assert_eq!(album().chapters[1], Chapter { start_time: 83.5, end_time: 300.0, title: "Song: Part 2".into() });
+    let none: VideoMetadata = serde_json::from_str(r#"{"title": "Talk", "chapters": null}"#).unwrap();
+    assert_eq!(none.chapters, None);
+}
+
+#[test]
+fn track_names_sort_in_album_order() {
+    assert_eq!(track_file_name(3, 12, "Intro", "mp3"), "03 - Intro.mp3");
+    assert_eq!(track_file_name(7, 120, "a/b", "m4a"), "007 - a⧸b.m4a");
+    assert_eq!(track_file_name(1, 2, " ", "opus"), "01 - Track 1.opus");
+    assert_eq!(tracks_folder(Path::new("/music/Full Album.mp3")), Path::new("/music/Full Album"));
+}
+
+#[test]
+fn each_track_is_cut_and_tagged() {
+    let args = track_args(Path::new("/m/a.mp3"), Path::new("/m/a/02 - x.mp3"), &album(), 2);
+    let line = text(&args);
+    assert!(line.contains("-ss 83.500 -to 300.000 -i /m/a.mp3"));
+    assert!(line.contains("-c copy"));
+    for tag in ["track=2/2", "title=Song: Part 2", "album=Full Album", "artist=Band"] {
+        assert!(args.iter().any(|a| a == tag), "missing {}", tag);
+    }
+    assert_eq!(args.last().unwrap(), "/m/a/02 - x.mp3");
+}
+
+#[tokio::test]
+async fn a_failed_split_leaves_no_folder_behind() {
+    let dir = TempDir::new("split_cleanup");
+    let source = dir.join("Full Album.mp3");
+    std::fs::write(&source, b"not audio").unwrap();
+    // Fails whether ffmpeg is installed or not
+    assert!(split(&source, &album(), |_| {}).await.is_err());
+    assert!(!tracks_folder(&source).exists());
+
+    // A folder that was there before stays
+    std::fs::create_dir(tracks_folder(&source)).unwrap();
+    assert!(split(&source, &album(), |_| {}).await.is_err());
+    assert!(tracks_folder(&source).exists());
+}
@@ -0,0 +1,42 @@
+//! Helpers shared by the integration tests. Every test file compiles this module on
+//! its own and most use only part of it.
+#![allow(dead_code)]
+use std::{
+    ffi::OsString,
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// Command line arguments joined by spaces, for `contains` and `ends_with` checks.
+pub fn text(args: &[OsString]) -> String {
+    args.iter().map(|a| a.to_string_lossy().into_owned()).collect::<Vec<_>>().join(" ")
+}
+
+/// An empty directory in the system's temp directory, removed with everything in it
+/// when dropped, so a failing assertion doesn't leave it behind.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `name` keeps tests apart, the process id keeps parallel runs apart.
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
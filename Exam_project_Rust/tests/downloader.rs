@@ -0,0 +1,134 @@
+use exam_project_rust::{
+    downloader::{posix_quote, sanitize_title, windows_quote, ytdlp_args, DownloadJob},
+    model::{AudioFormat, Clip, Quality},
+};
+
+const TEMPLATE: &str = "downloaded_bytes:%(progress._percent_str)s bytes:%(progress.downloaded_bytes)s speed:%(progress.speed)s resolution:%(info.resolution)s";
+
+fn job(quality: Quality) -> DownloadJob {
+    DownloadJob {
+        url: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string(),
+        quality,
+        audio_format: AudioFormat::M4a,
+        folder: "/tmp/videos".to_string(),
+        resume: false,
+        overwrite: false,
+        file_stem: None,
+        clip: None,
+        normalize: false,
+        keep_original: false,
+        split: None,
+        encode: None,
+    }
+}
+
+#[test]
+fn video_args() {
+    assert_eq!(
+        ytdlp_args(&job(Quality::P720)),
+        [
+            "-f",
+            "best[height<=720]",
+            "--progress-template",
+            TEMPLATE,
+            "--newline",
+            "-o",
+            "/tmp/videos/%(title)s.%(ext)s",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+        ]
+    );
+}
+
+#[test]
+fn split_stream_args() {
+    let args = ytdlp_args(&job(Quality::P1440));
+    assert_eq!(args[..2], ["-f", "bestvideo[height<=1440]+bestaudio/best[height<=1440]"]);
+    // Nothing above 720p comes as a single file
+    let args = ytdlp_args(&job(Quality::P1080));
+    assert_eq!(args[..2], ["-f", "bestvideo[height<=1080]+bestaudio/best[height<=1080]"]);
+}
+
+#[test]
+fn audio_args() {
+    let mut job = job(Quality::AudioOnly);
+    job.audio_format = AudioFormat::Mp3;
+    assert_eq!(
+        ytdlp_args(&job),
+        [
+            "-f",
+            "bestaudio",
+            "--extract-audio",
+            "--audio-format",
+            "mp3",
+            "--audio-quality",
+            "192K",
+            "--progress-template",
+            TEMPLATE,
+            "--newline",
+            "-o",
+            "/tmp/videos/%(title)s.%(ext)s",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+        ]
+    );
+
+    job.audio_format = AudioFormat::M4a;
+    assert_eq!(ytdlp_args(&job)[..5], ["-f", "bestaudio[ext=m4a]/bestaudio", "--extract-audio", "--audio-format", "m4a"]);
+}
+
+#[test]
+fn resume_overwrite_and_renamed_output() {
+    let mut job = job(Quality::Best);
+    job.resume = true;
+    job.overwrite = true;
+    job.file_stem = Some("100% done (1)".to_string());
+    assert_eq!(
+        ytdlp_args(&job),
+        [
+            "-f",
+            "bestvideo+bestaudio/best",
+            "--progress-template",
+            TEMPLATE,
+            "--newline",
+            "--continue",
+            "--force-overwrites",
+            "-o",
+            "/tmp/videos/100%% done (1).%(ext)s",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+        ]
+    );
+}
+
+#[test]
+fn clip_args() {
+    let mut job = job(Quality::P720);
+    job.clip = Some(Clip { start: 750, end: 840 });
+    let args = ytdlp_args(&job);
+    let at = args.iter().position(|a| a == "--download-sections").unwrap();
+    assert_eq!(args[at..at + 3], ["--download-sections", "*750-840", "--force-keyframes-at-cuts"]);
+    assert!(args.contains(&"/tmp/videos/%(title)s [12m30s-14m00s].%(ext)s".to_string()));
+}
+
+#[test]
+fn titles_become_file_names_like_ytdlp_makes_them() {
+    assert_eq!(sanitize_title("AC/DC: Live?"), "AC⧸DC： Live？");
+    assert_eq!(sanitize_title("Trailing dots..."), "Trailing dots");
+}
+
+#[test]
+fn posix_shell_quoting() {
+    assert_eq!(posix_quote("--newline"), "--newline");
+    assert_eq!(posix_quote("https://www.youtube.com/watch?v=dQw4w9WgXcQ"), "'https://www.youtube.com/watch?v=dQw4w9WgXcQ'");
+    assert_eq!(posix_quote("/tmp/videos/%(title)s.%(ext)s"), "'/tmp/videos/%(title)s.%(ext)s'");
+    assert_eq!(posix_quote("Don't stop"), r"'Don'\''t stop'");
+    assert_eq!(posix_quote(""), "''");
+}
+
+#[test]
+fn windows_command_line_quoting() {
+    assert_eq!(windows_quote(r"C:\Tools\yt-dlp.exe"), r"C:\Tools\yt-dlp.exe");
+    assert_eq!(windows_quote(r"C:\My Videos\%(title)s.%(ext)s"), r#""C:\My Videos\%(title)s.%(ext)s""#);
+    // Backslashes are doubled only where a quote follows
+    assert_eq!(windows_quote(r#"say "hi""#), r#""say \"hi\"""#);
+    assert_eq!(windows_quote(r"C:\My Videos\"), r#""C:\My Videos\\""#);
+    assert_eq!(windows_quote(""), r#""""#);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use std::path::Path;
+use exam_project_rust::{
+    encode::{encode_args, output_path, Container, EncodeProfile, RateControl, VideoCodec},
+    ffmpeg::{parse_input_info, InputInfo},
+};
+use common::text;
+
+fn profile(codec: VideoCodec, container: Container, rate: RateControl) -> EncodeProfile {
+    EncodeProfile { name: "Test".to_string(), codec, container, rate }
+}
+
+#[test]
+fn reads_codec_and_duration_from_the_input_description() {
+    let stderr = "Input #0, matroska,webm, from 'talk.webm':\n  Duration: 00:03:33.02, start: -0.007000, bitrate: 1215 kb/s\n  Stream #0:0(eng): Video: vp9 (Profile 0), yuv420p(tv, bt709), 1920x1080, SAR 1:1 DAR 16:9, 25 fps\n  Stream #0:1(eng): Audio: opus, 48000 Hz, stereo, fltp (default)\nAt least one output file must be specified";
+    let info = parse_input_info(stderr);
+    assert_eq!(info.video_codec.as_deref(), Some("vp9"));
+    assert!((info.duration.unwrap() - 213.02).abs() < 1e-6);
+    assert_eq!(parse_input_info("Input #0, mp3, from 'a.mp3':\n  Duration: N/A, bitrate: N/A"), InputInfo::default());
+}
+
+#[test]
+fn only_files_in_the_target_format_match() {
+    let h264 = profile(VideoCodec::H264, Container::Mp4, RateControl::Crf(23));
+    assert!(h264.matches(Path::new("/v/a.MP4"), Some("h264")));
+    assert!(!h264.matches(Path::new("/v/a.mkv"), Some("h264")));
+    assert!(!h264.matches(Path::new("/v/a.mp4"), Some("av1")));
+    assert!(!h264.matches(Path::new("/v/a.mp4"), None));
+    assert!(profile(VideoCodec::H265, Container::Mkv, RateControl::Crf(28)).matches(Path::new("a.mkv"), Some("hevc")));
+    assert_eq!(output_path(Path::new("/v/talk.webm"), &h264), Path::new("/v/talk.mp4"));
+}
+
+#[test]
+fn webm_only_takes_vp9_and_av1() {
+    assert!(Container::Webm.supports(VideoCodec::Vp9));
+    assert!(Container::Webm.supports(VideoCodec::Av1));
+    assert!(!Container::Webm.supports(VideoCodec::H264));
+    assert!(VideoCodec::ALL.iter().all(|&c| Container::Mkv.supports(c) && Container::Mp4.supports(c)));
+}
+
+#[test]
+fn arguments_follow_the_profile() {
+    let source = Path::new("/v/talk.webm");
+    let output = Path::new("/v/.encode.talk.mp4");
+    let h264 = text(&encode_args(source, output, &profile(VideoCodec::H264, Container::Mp4, RateControl::Crf(20))));
+    assert!(h264.contains("-progress pipe:1 -i /v/talk.webm"));
+    assert!(h264.contains("-c:v libx264 -crf 20 -c:a aac"));
+    assert!(h264.ends_with("-movflags +faststart /v/.encode.talk.mp4"));
+
+    let vp9 = text(&encode_args(source, Path::new("/v/o.webm"), &profile(VideoCodec::Vp9, Container::Webm, RateControl::Crf(32))));
+    assert!(vp9.contains("-c:v libvpx-vp9 -crf 32 -b:v 0 -c:a libopus"));
+
+    let hevc = text(&encode_args(source, Path::new("/v/o.mkv"), &profile(VideoCodec::H265, Container::Mkv, RateControl::Bitrate(2500))));
+    assert!(hevc.contains("-map 0:s? -c:v libx265 -b:v 2500k -c:a copy -c:s copy"));
+    assert!(!hevc.contains("-crf"));
+}
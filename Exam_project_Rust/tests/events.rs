@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+use tokio::sync::{mpsc::unbounded_channel, watch};
+use exam_project_rust::{
+    downloader::OutputParser,
+    events::{apply_progress, reduce, DownloadEvent, Effect, Outcome, Progress},
+    model::{AudioFormat, DownloadStatus, DownloadTask, PostStep, Quality, SessionStats, TaskId},
+    thumbnail::ThumbnailError,
+};
+
+fn task(id: TaskId) -> DownloadTask {
+    let mut task = DownloadTask::new(
+        id,
+        format!("video{}", id),
+        format!("https://youtu.be/video{}", id),
+        Quality::Best,
+        AudioFormat::M4a,
+        "/tmp".to_string(),
+    );
+    task.status = DownloadStatus::Downloading;
+    task
+}
+
+fn at(percent: f32, bytes: u64, speed: Option<f64>) -> Progress {
+    Progress { percent, bytes: Some(bytes), speed, post_processing: false }
+}
+
+const POST_PROCESSING: Progress = Progress { percent: 1.0, bytes: None, speed: None, post_processing: true };
+
+/// Feeds `events` in order and returns the effects that weren't `Effect::None`.
+fn feed(tasks: &mut [DownloadTask], stats: &mut SessionStats, events: Vec<(TaskId, DownloadEvent)>) -> Vec<Effect> {
+    events
+        .into_iter()
+        .map(|(id, event)| reduce(tasks, stats, id, event))
+        .filter(|effect| !matches!(effect, Effect::None))
+        .collect()
+}
+
+#[test]
+fn merged_download_runs_to_done() {
+    let mut tasks = vec![task(1)];
+    let mut stats = SessionStats::default();
+    for progress in [
+        at(0.5, 1000, Some(500.0)),
+        at(1.0, 3000, Some(500.0)),
+        // The audio stream starts from zero again
+        at(0.2, 200, None),
+        POST_PROCESSING,
+    ] {
+        apply_progress(&mut tasks[0], &mut stats, progress);
+    }
+    let effects = feed(
+        &mut tasks,
+        &mut stats,
+        vec![
+            (1, DownloadEvent::Line("[download] Destination: /tmp/a.f137.mp4".into())),
+            (1, DownloadEvent::Warning("WARNING: slow merge".into())),
+            (
+                1,
+                DownloadEvent::Finished(Ok(Outcome::Finished {
+                    path: Some(PathBuf::from("/tmp/a.mp4")),
+                    resolution: Some("1920x1080".into()),
+                })),
+            ),
+        ],
+    );
+
+    let task = &tasks[0];
+    assert!(matches!(task.status, DownloadStatus::Done));
+    assert_eq!(task.progress, 1.0);
+    assert_eq!(task.file_path, Some(PathBuf::from("/tmp/a.mp4")));
+    assert_eq!(task.resolution.as_deref(), Some("1920x1080"));
+    assert_eq!(task.speed, None);
+    // 1000 is the baseline, then +2000, then the new stream's 200
+    assert_eq!(stats.bytes, 2200);
+    assert_eq!((stats.completed, stats.failed), (1, 0));
+    assert!(matches!(&effects[..], [Effect::Output(_), Effect::Warning(_), Effect::Ended]));
+}
+
+#[test]
+fn progress_never_goes_backwards_and_post_processing_clears_speed() {
+    let mut tasks = [task(1)];
+    let mut stats = SessionStats::default();
+    apply_progress(&mut tasks[0], &mut stats, at(0.8, 8, Some(4.0)));
+    apply_progress(&mut tasks[0], &mut stats, at(0.1, 10, Some(5.0)));
+    assert_eq!(tasks[0].progress, 0.8);
+    assert_eq!(tasks[0].speed, Some(5.0));
+
+    apply_progress(&mut tasks[0], &mut stats, POST_PROCESSING);
+    assert!(matches!(tasks[0].status, DownloadStatus::PostProcessing));
+    assert_eq!(tasks[0].speed, None);
+}
+
+#[test]
+fn own_post_processing_steps_show_and_warn() {
+    let mut tasks = vec![task(1)];
+    let mut stats = SessionStats::default();
+    let effects = feed(&mut tasks, &mut stats, vec![(1, DownloadEvent::Step(PostStep::Normalizing))]);
+    assert!(matches!(tasks[0].status, DownloadStatus::PostProcessing));
+    assert_eq!(tasks[0].post_step, Some(PostStep::Normalizing));
+    assert!(effects.is_empty());
+
+    let effects = feed(
+        &mut tasks,
+        &mut stats,
+        vec![
+            (1, DownloadEvent::StepFailed("normalizing failed".into())),
+            (1, DownloadEvent::Finished(Ok(Outcome::Finished { path: Some(PathBuf::from("/tmp/a.m4a")), resolution: None }))),
+        ],
+    );
+    // The download still counts as done, with the warning kept on the task
+    assert!(matches!(tasks[0].status, DownloadStatus::Done));
+    assert_eq!(tasks[0].post_step, None);
+    assert_eq!(tasks[0].warning.as_deref(), Some("normalizing failed"));
+    assert!(matches!(&effects[..], [Effect::Warning(_), Effect::Ended]));
+}
+
+#[test]
+fn cancelled_and_failed_runs() {
+    let mut tasks = vec![task(1), task(2)];
+    let mut stats = SessionStats::default();
+    apply_progress(&mut tasks[0], &mut stats, at(0.3, 30, None));
+    apply_progress(&mut tasks[1], &mut stats, at(0.6, 60, None));
+    let effects = feed(
+        &mut tasks,
+        &mut stats,
+        vec![
+            (1, DownloadEvent::Finished(Ok(Outcome::Cancelled))),
+            (2, DownloadEvent::Finished(Err("ERROR: Video unavailable".into()))),
+        ],
+    );
+    assert!(matches!(tasks[0].status, DownloadStatus::Paused));
+    assert_eq!(tasks[0].progress, 0.3);
+    assert!(matches!(&tasks[1].status, DownloadStatus::Failed(e) if e == "ERROR: Video unavailable"));
+    assert_eq!((stats.completed, stats.failed), (0, 1));
+    assert_eq!(effects.len(), 2);
+}
+
+#[test]
+fn events_for_removed_tasks_are_dropped_but_thumbnails_pass() {
+    let mut tasks = vec![task(1)];
+    let mut stats = SessionStats::default();
+    let effects = feed(
+        &mut tasks,
+        &mut stats,
+        vec![
+            (7, DownloadEvent::Line("[download] 50%".into())),
+            (7, DownloadEvent::Finished(Err("killed".into()))),
+            (7, DownloadEvent::Thumbnail { video_id: "video1".into(), image: Err(ThumbnailError::Skipped) }),
+        ],
+    );
+    assert_eq!(tasks[0].progress, 0.0);
+    assert_eq!(stats.failed, 0);
+    assert!(matches!(&effects[..], [Effect::Thumbnail { video_id, .. }] if video_id == "video1"));
+}
+
+#[test]
+fn progress_flood_keeps_only_the_latest_value() {
+    let (progress_tx, progress) = watch::channel(Progress::default());
+    let (events_tx, mut events) = unbounded_channel();
+    let mut parser = OutputParser::default();
+    for n in 0..100_000u64 {
+        let line = format!(
+            "downloaded_bytes: {}.0% bytes:{} speed:1000.0 resolution:1280x720",
+            n / 1000,
+            n * 10
+        );
+        parser.line(1, line, &progress_tx, &events_tx);
+    }
+    parser.line(1, "WARNING: still going".into(), &progress_tx, &events_tx);
+
+    // Nothing queued up but the one line that wasn't progress
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events.try_recv(), Ok((1, DownloadEvent::Line(line))) if line == "WARNING: still going"));
+    assert_eq!(*progress.borrow(), at(0.99, 999_990, Some(1000.0)));
+    assert_eq!(parser.resolution.as_deref(), Some("1280x720"));
+}
@@ -0,0 +1,169 @@
+//! Runs `spawn_download` against tests/fake_ytdlp/yt-dlp, a script that plays back the
+//! scenario file it is given as the URL, and checks what the app makes of the output.
+use std::{path::{Path, PathBuf}, sync::Once};
+use tokio::sync::{mpsc::unbounded_channel, oneshot, watch};
+use exam_project_rust::{
+    downloader::{spawn_download, DownloadJob, YTDLP_PATH_VAR},
+    events::{apply_progress, reduce, DownloadEvent, Outcome, Progress},
+    model::{AudioFormat, DownloadStatus, DownloadTask, Quality, SessionStats},
+};
+
+const FAKE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fake_ytdlp");
+
+fn use_fake_ytdlp() {
+    static SET: Once = Once::new();
+    SET.call_once(|| {
+        let script = if cfg!(windows) { "yt-dlp.cmd" } else { "yt-dlp" };
+        // SAFETY: every test calls this before starting a process and the others wait
+        // on the Once meanwhile, so nothing else reads the environment during the write.
+        unsafe { std::env::set_var(YTDLP_PATH_VAR, Path::new(FAKE_DIR).join(script)) };
+    });
+}
+
+/// What one run reported.
+struct Run {
+    /// Progress values in the order they were seen; a fast run may skip some.
+    progress: Vec<Progress>,
+    events: Vec<DownloadEvent>,
+    result: Result<Outcome, String>,
+}
+
+impl Run {
+    fn lines(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|e| match e {
+                DownloadEvent::Line(line) | DownloadEvent::Warning(line) => Some(line.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The task as the app would show it once everything was handled.
+    fn settle(self) -> DownloadTask {
+        let mut task = DownloadTask::new(1, "fake".into(), "fake".into(), Quality::Best, AudioFormat::M4a, "/videos".into());
+        task.status = DownloadStatus::Downloading;
+        let mut stats = SessionStats::default();
+        if let Some(&last) = self.progress.last() {
+            apply_progress(&mut task, &mut stats, last);
+        }
+        let tasks = std::slice::from_mut(&mut task);
+        for event in self.events {
+            reduce(tasks, &mut stats, 1, event);
+        }
+        reduce(tasks, &mut stats, 1, DownloadEvent::Finished(self.result));
+        task
+    }
+}
+
+/// Plays `scenario`, cancelling once progress reaches `cancel_at` if given.
+async fn play(scenario: &str, cancel_at: Option<f32>) -> Run {
+    use_fake_ytdlp();
+    let job = DownloadJob {
+        url: Path::new(FAKE_DIR).join(format!("{}.txt", scenario)).display().to_string(),
+        quality: Quality::Best,
+        audio_format: AudioFormat::M4a,
+        folder: std::env::temp_dir().display().to_string(),
+        resume: false,
+        overwrite: false,
+        file_stem: None,
+        clip: None,
+        normalize: false,
+        keep_original: false,
+        split: None,
+        encode: None,
+    };
+    let (progress_tx, mut progress_rx) = watch::channel(Progress::default());
+    let (events_tx, mut events_rx) = unbounded_channel();
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    let mut until = progress_rx.clone();
+    let download = tokio::spawn(spawn_download(1, job, progress_tx, events_tx, cancel_rx));
+
+    let watcher = tokio::spawn(async move {
+        let mut seen = Vec::new();
+        while progress_rx.changed().await.is_ok() {
+            seen.push(*progress_rx.borrow_and_update());
+        }
+        seen
+    });
+    if let Some(at) = cancel_at {
+        until.wait_for(|p| p.percent >= at).await.unwrap();
+        cancel_tx.send(()).unwrap();
+    } else {
+        drop(cancel_tx);
+    }
+
+    let result = download.await.unwrap().map_err(|e| e.to_string());
+    let progress = watcher.await.unwrap();
+    let mut events = Vec::new();
+    while let Ok((_, event)) = events_rx.try_recv() {
+        events.push(event);
+    }
+    Run { progress, events, result }
+}
+
+#[tokio::test]
+async fn normal_run() {
+    let run = play("normal", None).await;
+    assert_eq!(run.lines(), ["[youtube] fake: Downloading webpage", "[download] Destination: /videos/Normal.mp4"]);
+    assert!(run.progress.windows(2).all(|w| w[0].percent <= w[1].percent));
+    assert_eq!(
+        run.progress.last(),
+        Some(&Progress { percent: 1.0, bytes: Some(10000), speed: Some(2500.0), post_processing: false })
+    );
+
+    let task = run.settle();
+    assert!(matches!(task.status, DownloadStatus::Done));
+    assert_eq!(task.file_path, Some(PathBuf::from("/videos/Normal.mp4")));
+    // The command the run used, for copying
+    let command = task.command.as_deref().unwrap();
+    assert!(command[0].contains("fake_ytdlp"));
+    assert!(command.last().unwrap().ends_with("normal.txt"));
+    assert_eq!(task.resolution.as_deref(), Some("1280x720"));
+    assert_eq!(task.progress, 1.0);
+}
+
+#[tokio::test]
+async fn failure_reports_the_error_line() {
+    let run = play("failure", None).await;
+    assert!(matches!(&run.events[..], [
+        DownloadEvent::Started(_),
+        DownloadEvent::Line(_),
+        DownloadEvent::Warning(warning),
+        DownloadEvent::Line(error),
+    ] if warning.starts_with("WARNING:") && error.starts_with("ERROR:")));
+    assert!(run.progress.is_empty());
+
+    let task = run.settle();
+    assert!(matches!(&task.status, DownloadStatus::Failed(e) if e == "ERROR: Video unavailable"));
+}
+
+#[tokio::test]
+async fn stalled_run_can_be_cancelled() {
+    let run = play("stalled", Some(0.5)).await;
+    assert!(matches!(run.result, Ok(Outcome::Cancelled)));
+    assert_eq!(run.progress.last().map(|p| p.percent), Some(0.5));
+
+    let task = run.settle();
+    assert!(matches!(task.status, DownloadStatus::Paused));
+    assert_eq!(task.progress, 0.5);
+}
+
+#[tokio::test]
+async fn two_streams_are_merged() {
+    let run = play("two_stream", None).await;
+    assert!(run.progress.last().is_some_and(|p| p.post_processing));
+
+    let task = run.settle();
+    assert!(matches!(task.status, DownloadStatus::Done));
+    assert_eq!(task.file_path, Some(PathBuf::from("/videos/Both.mp4")));
+    // The audio stream's "audio only" doesn't replace the video's resolution
+    assert_eq!(task.resolution.as_deref(), Some("1920x1080"));
+}
+
+#[tokio::test]
+async fn unicode_destination() {
+    let task = play("unicode", None).await.settle();
+    assert!(matches!(task.status, DownloadStatus::Done));
+    assert_eq!(task.file_path, Some(PathBuf::from("/videos/Ünïcødé – 日本語 🎵.mp4")));
+}
@@ -0,0 +1,16 @@
+@echo off
+rem Windows version of the yt-dlp script next to it, see there. Scenario lines must
+rem not contain parentheses, which would end the block below.
+chcp 65001 >nul
+:last
+if "%~2"=="" goto play
+shift
+goto last
+:play
+for /f "usebackq tokens=1,* delims= " %%a in ("%~1") do (
+    if "%%a"=="out" echo(%%b
+    if "%%a"=="err" 1>&2 echo(%%b
+    if "%%a"=="sleep" ping -n %%b 127.0.0.1 >nul
+    if "%%a"=="exit" exit /b %%b
+)
+exit /b 0
@@ -0,0 +1,72 @@
+use exam_project_rust::feed::{is_feed_url, parse_feed, parse_opml};
+
+const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns:media="http://search.yahoo.com/mrss/" xmlns="http://www.w3.org/2005/Atom">
+ <id>yt:channel:UCuAXFkgsw1L7xaCfnd5JJOw</id>
+ <title>Rick Astley</title>
+ <entry>
+  <id>yt:video:dQw4w9WgXcQ</id>
+  <yt:videoId>dQw4w9WgXcQ</yt:videoId>
+  <title>Never Gonna Give You Up</title>
+  <author><name>Rick Astley</name></author>
+  <published>2009-10-25T06:57:33+00:00</published>
+  <media:group><media:title>Never Gonna Give You Up</media:title></media:group>
+ </entry>
+ <entry>
+  <id>yt:video:yPYZpwSpKmA</id>
+  <title>Together Forever</title>
+ </entry>
+ <entry>
+  <title>No id at all</title>
+ </entry>
+</feed>"#;
+
+#[test]
+fn youtube_feed() {
+    let feed = parse_feed(FEED).unwrap();
+    assert_eq!(feed.title.as_deref(), Some("Rick Astley"));
+    assert_eq!(feed.entries.len(), 2);
+    let first = &feed.entries[0];
+    assert_eq!(first.video_id, "dQw4w9WgXcQ");
+    assert_eq!(first.title, "Never Gonna Give You Up");
+    assert_eq!(first.author.as_deref(), Some("Rick Astley"));
+    assert_eq!(first.published.as_deref(), Some("2009-10-25T06:57:33+00:00"));
+    // Without <yt:videoId> the id comes from <id>
+    assert_eq!(feed.entries[1].video_id, "yPYZpwSpKmA");
+    assert_eq!(feed.entries[1].author, None);
+}
+
+#[test]
+fn not_a_feed() {
+    assert!(parse_feed("<html><body/></html>").is_err());
+    assert!(parse_feed("not xml").is_err());
+}
+
+#[test]
+fn opml_with_nested_outlines() {
+    let opml = r#"<?xml version="1.0"?>
+<opml version="1.1">
+ <head><title>YouTube subscriptions</title></head>
+ <body>
+  <outline text="YouTube Subscriptions" title="YouTube Subscriptions">
+   <outline text="Rick Astley" type="rss" xmlUrl="https://www.youtube.com/feeds/videos.xml?channel_id=UCuAXFkgsw1L7xaCfnd5JJOw"/>
+   <outline text="Folder only"/>
+  </outline>
+  <outline text="Other" type="rss" xmlUrl=" https://www.youtube.com/feeds/videos.xml?playlist_id=PL123 "/>
+ </body>
+</opml>"#;
+    assert_eq!(
+        parse_opml(opml).unwrap(),
+        [
+            "https://www.youtube.com/feeds/videos.xml?channel_id=UCuAXFkgsw1L7xaCfnd5JJOw",
+            "https://www.youtube.com/feeds/videos.xml?playlist_id=PL123",
+        ]
+    );
+    assert!(parse_opml(FEED).is_err());
+}
+
+#[test]
+fn feed_urls() {
+    assert!(is_feed_url("https://www.youtube.com/feeds/videos.xml?channel_id=UC123"));
+    assert!(!is_feed_url("https://www.youtube.com/@channel"));
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use std::path::Path;
+use exam_project_rust::{
+    ffmpeg::parse_progress_line,
+    gif::{output_path, palette_args, render_args, GifOptions},
+};
+use common::{text, TempDir};
+
+#[test]
+fn two_passes_share_the_clip_and_scaling() {
+    let options = GifOptions { start: 12.5, duration: 4.0, width: 320, fps: 10 };
+    let first = text(&palette_args(Path::new("/v/clip.mp4"), Path::new("/v/p.png"), &options));
+    let second = text(&render_args(Path::new("/v/clip.mp4"), Path::new("/v/p.png"), Path::new("/v/out"), &options));
+    assert!(first.contains("-ss 12.500 -t 4.000 -i /v/clip.mp4"));
+    assert!(first.ends_with("-vf fps=10,scale=320:-2:flags=lanczos,palettegen /v/p.png"));
+    assert!(second.contains("-ss 12.500 -t 4.000 -i /v/clip.mp4 -i /v/p.png"));
+    assert!(second.ends_with("-lavfi fps=10,scale=320:-2:flags=lanczos[x];[x][1:v]paletteuse -f gif /v/out"));
+    assert!(first.contains("-progress pipe:1"));
+}
+
+#[test]
+fn progress_and_output_names() {
+    assert_eq!(parse_progress_line("out_time_us=2500000"), Some(2.5));
+    assert_eq!(parse_progress_line("out_time_us=N/A"), None);
+    assert_eq!(parse_progress_line("frame=12"), None);
+
+    let dir = TempDir::new("gif_names");
+    let source = dir.join("clip.mp4");
+    assert_eq!(output_path(&source), dir.join("clip.gif"));
+    std::fs::write(dir.join("clip.gif"), b"").unwrap();
+    assert_eq!(output_path(&source), dir.join("clip (2).gif"));
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::{fs, path::Path};
+use exam_project_rust::loudnorm::{
+    measure_args, normalize_args, original_path, parse_measurement, parse_sample_rate, replace_file, Measurement,
+};
+use common::{text, TempDir};
+
+const REPORT: &str = r#"Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'song.m4a':
+  Duration: 00:03:33.02, start: 0.000000, bitrate: 130 kb/s
+  Stream #0:0[0x1](und): Audio: aac (LC) (mp4a / 0x6134706D), 44100 Hz, stereo, fltp, 128 kb/s (default)
+[Parsed_loudnorm_0 @ 0x55d0c8a3c440]
+{
+	"input_i" : "-27.61",
+	"input_tp" : "-4.47",
+	"input_lra" : "18.06",
+	"input_thresh" : "-39.20",
+	"output_i" : "-16.58",
+	"output_tp" : "-1.50",
+	"output_lra" : "14.78",
+	"output_thresh" : "-27.71",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.58"
+}
+"#;
+
+#[test]
+fn reads_the_measurement_and_sample_rate() {
+    let measured = parse_measurement(REPORT).unwrap();
+    assert_eq!(
+        measured,
+        Measurement { input_i: -27.61, input_tp: -4.47, input_lra: 18.06, input_thresh: -39.2, target_offset: 0.58 }
+    );
+    assert_eq!(parse_sample_rate(REPORT), Some(44100));
+    // Silence measures as -inf, which can't be normalized
+    assert_eq!(parse_measurement(&REPORT.replace("\"-27.61\"", "\"-inf\"")), None);
+    assert_eq!(parse_measurement("no json here"), None);
+}
+
+#[test]
+fn second_pass_uses_the_measurement() {
+    let measured = parse_measurement(REPORT).unwrap();
+    assert!(text(&measure_args(Path::new("/a/song.m4a"))).contains("-af loudnorm=I=-16:TP=-1.5:LRA=11:print_format=json -f null -"));
+    let args = text(&normalize_args(Path::new("/a/song.mp3"), Path::new("/a/.loudnorm.song.mp3"), &measured, 44100));
+    assert!(args.contains("measured_I=-27.61:measured_TP=-4.47:measured_LRA=18.06:measured_thresh=-39.2:offset=0.58:linear=true"));
+    assert!(args.ends_with("-c:a libmp3lame -b:a 192k -ar 44100 /a/.loudnorm.song.mp3"));
+    let opus = text(&normalize_args(Path::new("/a/song.opus"), Path::new("/a/.loudnorm.song.opus"), &measured, 44100));
+    assert!(opus.contains("-c:a libopus -b:a 160k -ar 48000"));
+}
+
+#[test]
+fn replacing_keeps_the_original_if_asked() {
+    let dir = TempDir::new("loudnorm");
+    let file = dir.join("song.mp3");
+    let normalized = dir.join(".loudnorm.song.mp3");
+
+    fs::write(&file, b"quiet").unwrap();
+    fs::write(&normalized, b"loud").unwrap();
+    replace_file(&file, &normalized, true).unwrap();
+    assert_eq!(original_path(&file), dir.join("song.mp3.orig"));
+    assert_eq!(fs::read(&file).unwrap(), b"loud");
+    assert_eq!(fs::read(original_path(&file)).unwrap(), b"quiet");
+    assert!(!normalized.exists());
+
+    fs::write(&normalized, b"louder").unwrap();
+    replace_file(&file, &normalized, false).unwrap();
+    assert_eq!(fs::read(&file).unwrap(), b"louder");
+}
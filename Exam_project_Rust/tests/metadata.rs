@@ -0,0 +1,57 @@
+use exam_project_rust::metadata::{parse_playlist_line, parse_search_results, PlaylistEntry, PlaylistMetadata};
+
+#[test]
+fn search_results_skip_entries_without_id() {
+    let json = br#"{
+        "_type": "playlist",
+        "id": "rick astley",
+        "entries": [
+            {"_type": "url", "id": "dQw4w9WgXcQ", "title": "Never Gonna Give You Up", "channel": "Rick Astley", "duration": 213.0},
+            {"_type": "url", "title": "Broken entry"},
+            {"_type": "url", "id": "yPYZpwSpKmA", "title": "Together Forever", "uploader": "RickAstleyVEVO"}
+        ]
+    }"#;
+    let results = parse_search_results(json).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].id, "dQw4w9WgXcQ");
+    assert_eq!(results[0].channel(), Some("Rick Astley"));
+    assert_eq!(results[0].duration, Some(213.0));
+    assert_eq!(results[1].channel(), Some("RickAstleyVEVO"));
+    assert_eq!(results[1].duration, None);
+}
+
+#[test]
+fn search_without_hits() {
+    assert!(parse_search_results(br#"{"_type": "playlist", "entries": []}"#).unwrap().is_empty());
+    assert!(parse_search_results(b"not json").is_err());
+}
+
+#[test]
+fn uploads_since_the_last_seen_video() {
+    let json = r#"{"title": "Channel - Videos", "channel": "Channel", "entries": [
+        {"id": "new2", "title": "Newest"},
+        {"id": "new1", "title": "Newer"},
+        {"id": "seen", "title": "Seen"},
+        {"id": "old", "title": "Old"}
+    ]}"#;
+    let listing: PlaylistMetadata = serde_json::from_str(json).unwrap();
+    let ids = |entries: &[PlaylistEntry]| entries.iter().map(|e| e.id.clone().unwrap()).collect::<Vec<_>>();
+    let seen = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+    assert_eq!(ids(listing.entries_since(&seen(&["seen"]))), ["new2", "new1"]);
+    assert!(listing.entries_since(&seen(&["new2"])).is_empty());
+    // The first listed id of the set is where the new ones end
+    assert_eq!(ids(listing.entries_since(&seen(&["gone", "old", "new1"]))), ["new2"]);
+    // Nothing seen before is listed any more, so nothing can be told apart as new
+    assert!(listing.entries_since(&seen(&["gone"])).is_empty());
+}
+
+#[test]
+fn streamed_playlist_lines() {
+    let line = r#"{"_type": "url", "id": "dQw4w9WgXcQ", "title": "Never Gonna Give You Up", "duration": 213.0, "channel": "Rick Astley", "playlist_title": "Hits", "playlist_index": 1}"#;
+    let entry = parse_playlist_line(line).unwrap();
+    assert_eq!(entry.id.as_deref(), Some("dQw4w9WgXcQ"));
+    assert_eq!(entry.duration, Some(213.0));
+    assert_eq!(entry.playlist_title.as_deref(), Some("Hits"));
+    // yt-dlp's own messages, should one end up on stdout
+    assert!(parse_playlist_line("[youtube:tab] Downloading page 2").is_none());
+}
@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+use exam_project_rust::model::{format_minutes, parse_timestamp, BandwidthHistory, Clip, Quality, BANDWIDTH_WINDOW};
+
+#[test]
+fn better_downloads_cover_worse_ones() {
+    assert!(Quality::P1080.covers(Quality::P720));
+    assert!(Quality::P720.covers(Quality::P720));
+    assert!(!Quality::P480.covers(Quality::P1080));
+    assert!(Quality::Best.covers(Quality::P2160));
+    assert!(!Quality::P2160.covers(Quality::Best));
+    // A video file is no substitute for the audio file, nor the other way round
+    assert!(Quality::AudioOnly.covers(Quality::AudioOnly));
+    assert!(!Quality::Best.covers(Quality::AudioOnly));
+    assert!(!Quality::AudioOnly.covers(Quality::P360));
+}
+
+#[test]
+fn timestamps() {
+    assert_eq!(parse_timestamp("75"), Some(75.0));
+    assert_eq!(parse_timestamp(" 1:15.5 "), Some(75.5));
+    assert_eq!(parse_timestamp("1:02:03"), Some(3723.0));
+    assert_eq!(parse_timestamp("1:75"), None);
+    assert_eq!(parse_timestamp("1:2:3:4"), None);
+    assert_eq!(parse_timestamp("-3"), None);
+    assert_eq!(parse_timestamp(""), None);
+}
+
+#[test]
+fn clips_stay_within_the_video() {
+    let clip = Clip::parse("12:30", "14:00", 900).unwrap();
+    assert_eq!(clip, Clip { start: 750, end: 840 });
+    assert_eq!(clip.section(), "*750-840");
+    assert_eq!(clip.file_suffix(), "12m30s-14m00s");
+    assert_eq!(clip.range(), "12:30–14:00");
+    assert_eq!(clip.scale_size(900_000, 900), 90_000);
+    assert_eq!(Clip { start: 3600, end: 3725 }.file_suffix(), "1h00m00s-1h02m05s");
+    assert_eq!(Clip::parse("14:00", "12:30", 900), None);
+    assert_eq!(Clip::parse("0:00", "15:01", 900), None);
+    assert_eq!(Clip::parse("", "1:00", 900), None);
+}
+
+#[test]
+fn time_left_rounds_up_to_minutes() {
+    assert_eq!(format_minutes(1), "1 min");
+    assert_eq!(format_minutes(60), "1 min");
+    assert_eq!(format_minutes(61), "2 min");
+    assert_eq!(format_minutes(65 * 60), "1 h 05 min");
+}
+
+#[test]
+fn bandwidth_keeps_one_sample_a_second_for_five_minutes() {
+    let start = Instant::now();
+    let mut history = BandwidthHistory::default();
+    history.sample(100.0, start);
+    history.sample(500.0, start + Duration::from_millis(400));
+    assert_eq!(history.samples().collect::<Vec<_>>(), [100.0]);
+    // Seconds without a frame are filled in
+    history.sample(200.0, start + Duration::from_millis(3500));
+    assert_eq!(history.samples().collect::<Vec<_>>(), [100.0, 200.0, 200.0, 200.0]);
+    assert_eq!(history.peak(), 200.0);
+
+    // Idle time isn't drawn
+    history.pause();
+    history.sample(50.0, start + Duration::from_secs(60));
+    assert_eq!(history.samples().len(), 5);
+
+    history.sample(10.0, start + Duration::from_secs(3600));
+    assert_eq!(history.samples().len(), BANDWIDTH_WINDOW);
+    assert!(history.samples().all(|s| s == 10.0));
+}
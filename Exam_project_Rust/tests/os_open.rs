@@ -0,0 +1,24 @@
+use std::path::Path;
+use exam_project_rust::os_open::{explorer_select_arg, file_uri, show_items_args};
+
+#[test]
+fn explorer_gets_the_path_quoted_after_the_comma() {
+    assert_eq!(explorer_select_arg(Path::new(r"C:\Videos\clip.mp4")), r#"/select,"C:\Videos\clip.mp4""#);
+    // Unquoted, Explorer would take everything after the comma in the name as another argument
+    assert_eq!(
+        explorer_select_arg(Path::new(r"D:\My Videos\Live, 2024.mp4")),
+        r#"/select,"D:\My Videos\Live, 2024.mp4""#
+    );
+}
+
+#[test]
+fn file_manager_gets_an_encoded_uri() {
+    assert_eq!(file_uri(Path::new("/home/me/Videos/clip.mp4")), "file:///home/me/Videos/clip.mp4");
+    assert_eq!(file_uri(Path::new("/tmp/Live, 2024 #1.mp4")), "file:///tmp/Live%2C%202024%20%231.mp4");
+    assert_eq!(file_uri(Path::new("/tmp/Ünï.mp4")), "file:///tmp/%C3%9Cn%C3%AF.mp4");
+
+    let args = show_items_args(Path::new("/tmp/a,b.mp4"));
+    assert!(args.contains(&"org.freedesktop.FileManager1.ShowItems".to_string()));
+    // A raw comma would split the array into two items
+    assert_eq!(args[args.len() - 2], "array:string:file:///tmp/a%2Cb.mp4");
+}
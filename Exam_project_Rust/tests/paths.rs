@@ -0,0 +1,17 @@
+use exam_project_rust::paths::{self, PORTABLE_ARG, PORTABLE_DIR};
+
+// One test, as the mode is chosen once per process
+#[test]
+fn portable_flag_moves_everything_beside_the_executable() {
+    let mut args = vec![PORTABLE_ARG.to_string(), "--url".to_string(), "x".to_string()];
+    paths::init(&mut args);
+    assert_eq!(args, ["--url", "x"]);
+
+    let exe = std::env::current_exe().unwrap();
+    let root = exe.parent().unwrap().join(PORTABLE_DIR);
+    assert_eq!(paths::portable_root(), Some(root.clone()));
+    assert_eq!(paths::config_dir(), Some(root.clone()));
+    assert_eq!(paths::data_dir(), Some(root.clone()));
+    assert!(paths::cache_dir().is_some_and(|dir| dir.starts_with(&root)));
+    assert!(paths::bin_dir().starts_with(&root));
+}
@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+use exam_project_rust::progress::{
+    is_post_processing_line, parse_destination_from_line, parse_progress_from_line, parse_resolution_from_line,
+    parse_transfer_from_line,
+};
+
+const LINE: &str = "downloaded_bytes:  42.0% bytes:1048576 speed:524288.5 resolution:1280x720";
+
+#[test]
+fn progress_template_line() {
+    assert_eq!(parse_progress_from_line(LINE), Some(0.42));
+    assert_eq!(parse_transfer_from_line(LINE), Some((1048576, Some(524288.5))));
+    assert_eq!(parse_resolution_from_line(LINE).as_deref(), Some("1280x720"));
+}
+
+#[test]
+fn unknown_speed_and_resolution() {
+    let line = "downloaded_bytes:   0.0% bytes:1024 speed:NA resolution:NA";
+    assert_eq!(parse_transfer_from_line(line), Some((1024, None)));
+    assert_eq!(parse_resolution_from_line(line), None);
+}
+
+#[test]
+fn other_lines_are_not_progress() {
+    let line = "[download]  42.0% of 10.00MiB at 1.00MiB/s ETA 00:05";
+    assert_eq!(parse_progress_from_line(line), None);
+    assert_eq!(parse_transfer_from_line(line), None);
+}
+
+#[test]
+fn destinations() {
+    let cases = [
+        ("[download] Destination: /tmp/a b.mp4", Some("/tmp/a b.mp4")),
+        ("[download] /tmp/a.mp4 has already been downloaded", Some("/tmp/a.mp4")),
+        ("[ExtractAudio] Destination: /tmp/a.mp3", Some("/tmp/a.mp3")),
+        ("[Merger] Merging formats into \"/tmp/a.mkv\"", Some("/tmp/a.mkv")),
+        ("[youtube] dQw4w9WgXcQ: Downloading webpage", None),
+    ];
+    for (line, path) in cases {
+        assert_eq!(parse_destination_from_line(line), path.map(PathBuf::from), "{}", line);
+    }
+}
+
+#[test]
+fn post_processing_steps() {
+    assert!(is_post_processing_line("[Merger] Merging formats into \"a.mkv\""));
+    assert!(is_post_processing_line("[FixupM3u8] Fixing MPEG-TS in MP4 container"));
+    assert!(!is_post_processing_line("[download] 100% of 10.00MiB"));
+}
@@ -0,0 +1,104 @@
+use std::time::{Duration, Instant};
+use exam_project_rust::{
+    model::{AudioFormat, DownloadStatus, DownloadTask, Quality, TaskId},
+    queue::{self, Estimate, Move, SpeedAverage},
+};
+
+fn task(id: TaskId, status: DownloadStatus) -> DownloadTask {
+    let mut task = DownloadTask::new(
+        id,
+        format!("video{}", id),
+        format!("https://youtu.be/video{}", id),
+        Quality::P720,
+        AudioFormat::M4a,
+        "/tmp".to_string(),
+    );
+    task.status = status;
+    task.metadata_ready = true;
+    task
+}
+
+fn ids(tasks: &[DownloadTask]) -> Vec<TaskId> {
+    tasks.iter().map(|t| t.id).collect()
+}
+
+#[test]
+fn limit_of_zero_means_no_limit() {
+    assert!(queue::has_slot(2, 3));
+    assert!(!queue::has_slot(3, 3));
+    assert!(queue::has_slot(100, 0));
+}
+
+#[test]
+fn only_queued_tasks_with_metadata_are_ready() {
+    let mut tasks = vec![
+        task(1, DownloadStatus::Downloading),
+        task(2, DownloadStatus::Queued),
+        task(3, DownloadStatus::Queued),
+        task(4, DownloadStatus::Paused),
+        task(5, DownloadStatus::Queued),
+    ];
+    tasks[2].metadata_ready = false;
+    assert_eq!(queue::running(&tasks), 1);
+    assert_eq!(queue::ready(&tasks).map(|t| t.id).collect::<Vec<_>>(), [2, 5]);
+    assert_eq!(queue::positions(&tasks)[&5], 3);
+    assert_eq!(queue::awaiting_metadata(&tasks).map(|t| t.id).collect::<Vec<_>>(), [3]);
+}
+
+#[test]
+fn moving_skips_tasks_that_are_not_queued() {
+    let mut tasks = vec![
+        task(1, DownloadStatus::Queued),
+        task(2, DownloadStatus::Done),
+        task(3, DownloadStatus::Queued),
+        task(4, DownloadStatus::Queued),
+    ];
+    assert!(queue::move_queued(&mut tasks, 3, Move::Up));
+    assert_eq!(ids(&tasks), [3, 1, 2, 4]);
+    assert!(queue::move_queued(&mut tasks, 3, Move::Down));
+    assert_eq!(ids(&tasks), [1, 3, 2, 4]);
+    assert!(queue::move_queued(&mut tasks, 4, Move::ToTop));
+    assert_eq!(ids(&tasks), [4, 1, 3, 2]);
+}
+
+#[test]
+fn moving_past_the_ends_does_nothing() {
+    let mut tasks = vec![task(1, DownloadStatus::Queued), task(2, DownloadStatus::Queued), task(3, DownloadStatus::Done)];
+    assert!(!queue::move_queued(&mut tasks, 1, Move::Up));
+    assert!(!queue::move_queued(&mut tasks, 1, Move::ToTop));
+    assert!(!queue::move_queued(&mut tasks, 2, Move::Down));
+    assert!(!queue::move_queued(&mut tasks, 3, Move::Up));
+    assert_eq!(ids(&tasks), [1, 2, 3]);
+}
+
+#[test]
+fn estimate_counts_what_is_left_of_running_and_queued_tasks() {
+    let mut running = task(1, DownloadStatus::Downloading);
+    running.estimated_size = Some(1000);
+    running.progress = 0.25;
+    let mut queued = task(2, DownloadStatus::Queued);
+    queued.estimated_size = Some(500);
+    let mut done = task(3, DownloadStatus::Done);
+    done.estimated_size = Some(9000);
+    let mut tasks = vec![running, queued, done];
+    assert_eq!(queue::bytes_left(&tasks), (1250, false));
+    assert_eq!(queue::estimate(queue::bytes_left(&tasks), Some(10.0)), Some(Estimate { seconds: 125, at_least: false }));
+    assert_eq!(queue::estimate(queue::bytes_left(&tasks), None), None);
+
+    tasks.push(task(4, DownloadStatus::Queued));
+    assert_eq!(queue::bytes_left(&tasks), (1250, true));
+}
+
+#[test]
+fn speed_average_samples_once_a_second() {
+    let start = Instant::now();
+    let mut average = SpeedAverage::default();
+    assert_eq!(average.sample(Some(100.0), start), Some(100.0));
+    // Too soon: ignored
+    assert_eq!(average.sample(Some(1000.0), start + Duration::from_millis(300)), Some(100.0));
+    let smoothed = average.sample(Some(200.0), start + Duration::from_secs(1)).unwrap();
+    assert!(smoothed > 100.0 && smoothed < 200.0);
+    assert_eq!(average.sample(None, start + Duration::from_secs(2)), Some(smoothed));
+    average.reset();
+    assert_eq!(average.sample(None, start + Duration::from_secs(3)), None);
+}
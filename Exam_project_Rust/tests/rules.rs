@@ -0,0 +1,51 @@
+mod common;
+
+use std::fs;
+use exam_project_rust::{
+    model::{AudioFormat, DownloadTask, Quality},
+    rules::{self, Condition, MoveRule},
+};
+use common::TempDir;
+
+fn task(quality: Quality, title: &str, uploader: Option<&str>) -> DownloadTask {
+    let mut task = DownloadTask::new(1, "id".into(), "https://youtu.be/id".into(), quality, AudioFormat::M4a, "/videos".into());
+    task.title = title.to_string();
+    task.uploader = uploader.map(str::to_string);
+    task
+}
+
+fn rule(condition: Condition, folder: &str) -> MoveRule {
+    MoveRule { condition, folder: folder.to_string() }
+}
+
+#[test]
+fn first_matching_rule_wins() {
+    let rules = [
+        rule(Condition::Quality(Quality::AudioOnly), "/music"),
+        rule(Condition::UploaderContains("lecture".into()), "/courses"),
+        rule(Condition::TitleContains("".into()), "/everything"),
+        rule(Condition::TitleContains("Talk".into()), "/talks"),
+    ];
+    let folder = |task: &DownloadTask| rules::first_match(&rules, task).map(|r| r.folder.as_str());
+    assert_eq!(folder(&task(Quality::AudioOnly, "Song", Some("Lecture Hall"))), Some("/music"));
+    assert_eq!(folder(&task(Quality::P720, "Week 1", Some("MIT Lectures"))), Some("/courses"));
+    // An empty text matches nothing
+    assert_eq!(folder(&task(Quality::P720, "A talk on Rust", None)), Some("/talks"));
+    assert_eq!(folder(&task(Quality::P720, "Cats", None)), None);
+}
+
+#[test]
+fn moving_keeps_the_name_and_never_replaces() {
+    let dir = TempDir::new("move_rules");
+    let file = dir.join("clip.mp4");
+    fs::write(&file, b"video").unwrap();
+
+    let moved = rules::move_file(&file, &dir.join("sorted")).unwrap();
+    assert_eq!(moved, dir.join("sorted").join("clip.mp4"));
+    assert!(!file.exists());
+    assert_eq!(fs::read(&moved).unwrap(), b"video");
+
+    fs::write(&file, b"other").unwrap();
+    assert!(rules::move_file(&file, &dir.join("sorted")).is_err());
+    assert!(file.exists());
+}
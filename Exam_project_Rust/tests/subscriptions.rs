@@ -0,0 +1,41 @@
+use exam_project_rust::{
+    metadata::PlaylistMetadata,
+    subscriptions::{NewVideo, Subscription, Subscriptions},
+};
+
+const URL: &str = "https://www.youtube.com/@channel/videos";
+
+/// A channel's uploads tab listing `ids`, newest first.
+fn listing(ids: &[&str]) -> Result<PlaylistMetadata, String> {
+    let entries: Vec<String> = ids.iter().map(|id| format!(r#"{{"id": "{id}", "title": "Video {id}"}}"#)).collect();
+    let json = format!(r#"{{"channel": "Channel", "entries": [{}]}}"#, entries.join(","));
+    Ok(serde_json::from_str(&json).unwrap())
+}
+
+fn ids(new: Vec<NewVideo>) -> Vec<String> {
+    new.into_iter().map(|v| v.video_id).collect()
+}
+
+#[test]
+fn new_uploads_come_oldest_first() {
+    let mut subs = Subscriptions { channels: vec![Subscription::new(URL.into())], inbox: Vec::new() };
+    // Subscribing means from now on
+    assert!(subs.checked(URL, listing(&["b", "a"]), 1).is_empty());
+    assert_eq!(subs.channels[0].title, "Channel");
+    assert_eq!(ids(subs.checked(URL, listing(&["d", "c", "b", "a"]), 2)), ["c", "d"]);
+    assert!(subs.checked(URL, Err("offline".into()), 3).is_empty());
+    assert_eq!(subs.channels[0].last_error.as_deref(), Some("offline"));
+    assert!(subs.checked(URL, listing(&["d", "c", "b", "a"]), 4).is_empty());
+}
+
+#[test]
+fn a_vanished_marker_reports_nothing_old() {
+    let mut subs = Subscriptions { channels: vec![Subscription::new(URL.into())], inbox: Vec::new() };
+    subs.checked(URL, listing(&["c", "b", "a"]), 1);
+    // "c", the newest seen, was deleted; the others still mark the place
+    assert_eq!(ids(subs.checked(URL, listing(&["e", "d", "b", "a"]), 2)), ["d", "e"]);
+    // Everything seen is gone: the old uploads left can't be told from new ones
+    assert!(subs.checked(URL, listing(&["h", "g", "f"]), 3).is_empty());
+    // The check after that picks up from what was listed then
+    assert_eq!(ids(subs.checked(URL, listing(&["i", "h", "g", "f"]), 4)), ["i"]);
+}
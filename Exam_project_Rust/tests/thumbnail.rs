@@ -0,0 +1,106 @@
+use std::{
+    io::{BufRead, BufReader, Cursor, Write},
+    net::TcpListener,
+    thread,
+};
+use eframe::egui::Color32;
+use image::{ImageOutputFormat, Rgba, RgbaImage};
+use exam_project_rust::thumbnail::{decode_thumbnail, fetch_first_decodable, thumbnail_urls, ThumbnailError};
+
+/// Answers requests for each path in `routes` with its status and body, and anything
+/// else with a 404, until the test ends. Returns the server's base URL.
+fn serve(routes: Vec<(&'static str, u16, Vec<u8>)>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap() > 2 {
+                header.clear();
+            }
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let (status, body) = routes.iter().find(|(p, ..)| *p == path).map_or((404, &[][..]), |(_, s, b)| (*s, &b[..]));
+            write!(stream, "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len()).unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+    base
+}
+
+fn jpeg(width: u32, height: u32, color: [u8; 3]) -> Vec<u8> {
+    let [r, g, b] = color;
+    let img = RgbaImage::from_pixel(width, height, Rgba([r, g, b, 255]));
+    let mut bytes = Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgba8(img).write_to(&mut bytes, ImageOutputFormat::Jpeg(90)).unwrap();
+    bytes.into_inner()
+}
+
+/// Fetches `url` as a YouTube thumbnail, without the cache.
+fn fetch(url: String) -> Result<(), ThumbnailError> {
+    fetch_first_decodable(None, "test", &[url], [160, 90], true).map(|_| ())
+}
+
+#[test]
+fn webp_fixture_decodes_and_fills_the_target() {
+    let img = decode_thumbnail(include_bytes!("fixtures/tiny.webp"), [32, 18], true).unwrap();
+    assert_eq!(img.size, [32, 18]);
+    // 16:9 like the target, so there are no black bars
+    assert_ne!(img.pixels[0], Color32::BLACK);
+    let [webp, jpg] = thumbnail_urls("abc");
+    assert!(webp.ends_with("/vi_webp/abc/hqdefault.webp"));
+    assert!(jpg.ends_with("/vi/abc/hqdefault.jpg"));
+}
+
+#[test]
+fn undecodable_webp_falls_through_to_the_jpeg() {
+    let base = serve(vec![
+        ("/vi_webp/abc/hqdefault.webp", 200, b"<html>not an image</html>".to_vec()),
+        ("/vi/abc/hqdefault.jpg", 200, jpeg(48, 27, [200, 30, 30])),
+    ]);
+    let urls = [format!("{}/vi_webp/abc/hqdefault.webp", base), format!("{}/vi/abc/hqdefault.jpg", base)];
+    let img = fetch_first_decodable(None, "abc", &urls, [160, 90], true).unwrap();
+    assert_eq!(img.size, [160, 90]);
+}
+
+#[test]
+fn http_errors_keep_their_status() {
+    let base = serve(vec![("/busy.jpg", 503, Vec::new())]);
+    let missing = fetch(format!("{}/missing.jpg", base)).unwrap_err();
+    assert!(matches!(missing, ThumbnailError::Http(status) if status.as_u16() == 404), "{}", missing);
+    assert!(!missing.is_retryable());
+    let busy = fetch(format!("{}/busy.jpg", base)).unwrap_err();
+    assert!(matches!(busy, ThumbnailError::Http(status) if status.as_u16() == 503), "{}", busy);
+    assert!(busy.is_retryable());
+}
+
+#[test]
+fn refused_connection_is_a_network_error() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/a.jpg", listener.local_addr().unwrap());
+    drop(listener);
+    let refused = fetch(url).unwrap_err();
+    assert!(matches!(refused, ThumbnailError::Network(_)), "{}", refused);
+    assert!(refused.is_retryable());
+}
+
+#[test]
+fn garbage_and_the_grey_placeholder_are_final() {
+    let base = serve(vec![
+        ("/garbage.jpg", 200, b"\xff\xd8 truncated".to_vec()),
+        ("/grey.jpg", 200, jpeg(120, 90, [204, 204, 204])),
+    ]);
+    let garbage = fetch(format!("{}/garbage.jpg", base)).unwrap_err();
+    assert!(matches!(garbage, ThumbnailError::Decode(_)), "{}", garbage);
+    assert!(!garbage.is_retryable());
+    let grey = fetch(format!("{}/grey.jpg", base)).unwrap_err();
+    assert!(matches!(grey, ThumbnailError::Placeholder), "{}", grey);
+    assert!(!grey.is_retryable());
+    // Only YouTube's own thumbnails can be its placeholder
+    let other = fetch_first_decodable(None, "test", &[format!("{}/grey.jpg", base)], [160, 90], false);
+    assert!(other.is_ok());
+    assert!(ThumbnailError::Skipped.is_retryable());
+    assert!(!ThumbnailError::Internal.is_retryable());
+}